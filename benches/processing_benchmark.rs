@@ -74,9 +74,9 @@ fn benchmark_data_merger(c: &mut Criterion) {
             
             for record in &temp_records {
                 match record.souid {
-                    1 => min_temps.entry(record.staid).or_insert_with(Vec::new).push(record.clone()),
-                    2 => max_temps.entry(record.staid).or_insert_with(Vec::new).push(record.clone()),
-                    3 => avg_temps.entry(record.staid).or_insert_with(Vec::new).push(record.clone()),
+                    1 => min_temps.entry(record.staid).or_default().push(record.clone()),
+                    2 => max_temps.entry(record.staid).or_default().push(record.clone()),
+                    3 => avg_temps.entry(record.staid).or_default().push(record.clone()),
                     _ => {}
                 }
             }
@@ -115,9 +115,9 @@ fn benchmark_integrity_checker(c: &mut Criterion) {
     
     for record in &temp_records {
         match record.souid {
-            1 => min_temps.entry(record.staid).or_insert_with(Vec::new).push(record.clone()),
-            2 => max_temps.entry(record.staid).or_insert_with(Vec::new).push(record.clone()),
-            3 => avg_temps.entry(record.staid).or_insert_with(Vec::new).push(record.clone()),
+            1 => min_temps.entry(record.staid).or_default().push(record.clone()),
+            2 => max_temps.entry(record.staid).or_default().push(record.clone()),
+            3 => avg_temps.entry(record.staid).or_default().push(record.clone()),
             _ => {}
         }
     }
@@ -152,7 +152,7 @@ fn benchmark_temperature_validation(c: &mut Criterion) {
             let mut valid_count = 0;
             for &temp in &test_temps {
                 // V2 validation logic - basic range check
-                if temp >= -50.0 && temp <= 50.0 && temp.is_finite() {
+                if (-50.0..=50.0).contains(&temp) && temp.is_finite() {
                     valid_count += 1;
                 }
             }
@@ -206,9 +206,9 @@ fn benchmark_varying_data_sizes(c: &mut Criterion) {
                     
                     for record in &temp_records {
                         match record.souid {
-                            1 => min_temps.entry(record.staid).or_insert_with(Vec::new).push(record.clone()),
-                            2 => max_temps.entry(record.staid).or_insert_with(Vec::new).push(record.clone()),
-                            3 => avg_temps.entry(record.staid).or_insert_with(Vec::new).push(record.clone()),
+                            1 => min_temps.entry(record.staid).or_default().push(record.clone()),
+                            2 => max_temps.entry(record.staid).or_default().push(record.clone()),
+                            3 => avg_temps.entry(record.staid).or_default().push(record.clone()),
                             _ => {}
                         }
                     }
@@ -0,0 +1,286 @@
+use crate::analyzers::IndexPeriod;
+use crate::error::{ProcessingError, Result};
+use crate::models::weather::DataQuality;
+use crate::models::WeatherRecord;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Lowest `DataQuality` a daily value needs to count towards an aggregate
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MinimumQuality {
+    /// Only values with no ECAD flag and no physical suspicion
+    Valid,
+    /// Valid and suspect values; invalid values are dropped
+    #[default]
+    Suspect,
+    /// Every value present, including physically invalid ones
+    Any,
+}
+
+impl MinimumQuality {
+    pub fn accepts(&self, quality: DataQuality) -> bool {
+        match quality {
+            DataQuality::Missing => false,
+            DataQuality::Valid => true,
+            DataQuality::SuspectOriginal | DataQuality::SuspectRange | DataQuality::SuspectBoth => {
+                *self != MinimumQuality::Valid
+            }
+            DataQuality::Invalid => *self == MinimumQuality::Any,
+        }
+    }
+}
+
+impl FromStr for MinimumQuality {
+    type Err = ProcessingError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "valid" => Ok(MinimumQuality::Valid),
+            "suspect" => Ok(MinimumQuality::Suspect),
+            "any" | "all" => Ok(MinimumQuality::Any),
+            other => Err(ProcessingError::Config(format!(
+                "Unsupported quality level: {} (expected valid, suspect or any)",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for MinimumQuality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MinimumQuality::Valid => write!(f, "valid"),
+            MinimumQuality::Suspect => write!(f, "suspect"),
+            MinimumQuality::Any => write!(f, "any"),
+        }
+    }
+}
+
+/// Aggregated values for one station and period. A value is None when fewer
+/// than the required number of days passed the quality filter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeriodAggregate {
+    pub station_id: u32,
+    pub station_name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub year: i32,
+    /// `annual`, a month `01`-`12`, or a season `DJF`/`MAM`/`JJA`/`SON`
+    pub period: String,
+    pub days_in_period: u32,
+    pub temp_min_mean: Option<f32>,
+    pub temp_max_mean: Option<f32>,
+    pub temp_avg_mean: Option<f32>,
+    pub precipitation_total: Option<f32>,
+    pub wind_speed_mean: Option<f32>,
+    pub wind_speed_max: Option<f32>,
+    pub temp_min_days: u32,
+    pub temp_max_days: u32,
+    pub temp_avg_days: u32,
+    pub precipitation_days: u32,
+    pub wind_speed_days: u32,
+}
+
+/// Running totals for one element within a period
+#[derive(Debug, Clone, Copy, Default)]
+struct Accumulator {
+    sum: f64,
+    max: Option<f32>,
+    days: u32,
+}
+
+impl Accumulator {
+    fn add(&mut self, value: Option<f32>, quality: DataQuality, minimum: MinimumQuality) {
+        let Some(value) = value.filter(|_| minimum.accepts(quality)) else {
+            return;
+        };
+        self.sum += value as f64;
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+        self.days += 1;
+    }
+
+    fn mean(&self, min_days: u32) -> Option<f32> {
+        (self.days >= min_days.max(1)).then(|| (self.sum / self.days as f64) as f32)
+    }
+
+    fn total(&self, min_days: u32) -> Option<f32> {
+        (self.days >= min_days.max(1)).then_some(self.sum as f32)
+    }
+
+    fn max(&self, min_days: u32) -> Option<f32> {
+        self.max.filter(|_| self.days >= min_days.max(1))
+    }
+}
+
+#[derive(Default)]
+struct PeriodTotals {
+    temp_min: Accumulator,
+    temp_max: Accumulator,
+    temp_avg: Accumulator,
+    precipitation: Accumulator,
+    wind_speed: Accumulator,
+}
+
+/// Aggregates daily weather records to monthly, seasonal or annual values
+pub struct TemporalAggregator {
+    period: IndexPeriod,
+    min_valid_days: u32,
+    min_quality: MinimumQuality,
+}
+
+impl TemporalAggregator {
+    pub fn new(period: IndexPeriod) -> Self {
+        Self {
+            period,
+            min_valid_days: 1,
+            min_quality: MinimumQuality::default(),
+        }
+    }
+
+    /// Days that must pass the quality filter for an element's aggregate to
+    /// be reported
+    pub fn with_min_valid_days(mut self, min_valid_days: u32) -> Self {
+        self.min_valid_days = min_valid_days;
+        self
+    }
+
+    pub fn with_min_quality(mut self, min_quality: MinimumQuality) -> Self {
+        self.min_quality = min_quality;
+        self
+    }
+
+    /// Aggregate records, returning one row per station and period in
+    /// station then chronological order
+    pub fn aggregate(&self, records: &[WeatherRecord]) -> Vec<PeriodAggregate> {
+        let mut periods: BTreeMap<(u32, i32, &'static str), (&WeatherRecord, PeriodTotals)> =
+            BTreeMap::new();
+
+        for record in records {
+            let (year, label) = self.period.period_of(record.date);
+            let (_, totals) = periods
+                .entry((record.station_id, year, label))
+                .or_insert_with(|| (record, PeriodTotals::default()));
+
+            let temp_quality = record.assess_temperature_quality();
+            let minimum = self.min_quality;
+            totals.temp_min.add(record.temp_min, temp_quality, minimum);
+            totals.temp_max.add(record.temp_max, temp_quality, minimum);
+            totals.temp_avg.add(record.temp_avg, temp_quality, minimum);
+            totals.precipitation.add(
+                record.precipitation,
+                record.assess_precipitation_quality(),
+                minimum,
+            );
+            totals
+                .wind_speed
+                .add(record.wind_speed, record.assess_wind_quality(), minimum);
+        }
+
+        let min_days = self.min_valid_days;
+        periods
+            .into_iter()
+            .map(|((station_id, year, label), (first, totals))| {
+                let days_in_period = self
+                    .period
+                    .date_range(year, label)
+                    .map_or(0, |(start, end)| (end - start).num_days() as u32 + 1);
+
+                PeriodAggregate {
+                    station_id,
+                    station_name: first.station_name.clone(),
+                    latitude: first.latitude,
+                    longitude: first.longitude,
+                    year,
+                    period: label.to_string(),
+                    days_in_period,
+                    temp_min_mean: totals.temp_min.mean(min_days),
+                    temp_max_mean: totals.temp_max.mean(min_days),
+                    temp_avg_mean: totals.temp_avg.mean(min_days),
+                    precipitation_total: totals.precipitation.total(min_days),
+                    wind_speed_mean: totals.wind_speed.mean(min_days),
+                    wind_speed_max: totals.wind_speed.max(min_days),
+                    temp_min_days: totals.temp_min.days,
+                    temp_max_days: totals.temp_max.days,
+                    temp_avg_days: totals.temp_avg.days,
+                    precipitation_days: totals.precipitation.days,
+                    wind_speed_days: totals.wind_speed.days,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn record(day: u32, temp_avg: f32, rr: f32, precip_quality: &str) -> WeatherRecord {
+        WeatherRecord::new(
+            257,
+            "Test Station".to_string(),
+            NaiveDate::from_ymd_opt(2023, 1, day).unwrap(),
+            52.4,
+            -1.8,
+            None,
+            None,
+            Some(temp_avg),
+            Some(rr),
+            None,
+            Some("0".to_string()),
+            Some(precip_quality.to_string()),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_monthly_aggregates() {
+        let records = vec![
+            record(1, 4.0, 2.0, "0"),
+            record(2, 6.0, 3.0, "1"), // suspect precipitation
+            record(3, 8.0, 5.0, "0"),
+        ];
+
+        let aggregates = TemporalAggregator::new(IndexPeriod::Monthly).aggregate(&records);
+        assert_eq!(aggregates.len(), 1);
+
+        let january = &aggregates[0];
+        assert_eq!(january.period, "01");
+        assert_eq!(january.days_in_period, 31);
+        assert_eq!(january.temp_avg_mean, Some(6.0));
+        assert_eq!(january.temp_avg_days, 3);
+        assert_eq!(january.precipitation_total, Some(10.0));
+        assert_eq!(january.temp_min_mean, None);
+        assert_eq!(january.wind_speed_days, 0);
+
+        // Only unflagged values count at the strictest level
+        let aggregates = TemporalAggregator::new(IndexPeriod::Monthly)
+            .with_min_quality(MinimumQuality::Valid)
+            .aggregate(&records);
+        assert_eq!(aggregates[0].precipitation_total, Some(7.0));
+        assert_eq!(aggregates[0].precipitation_days, 2);
+
+        // Too few valid days voids the aggregate but keeps the count
+        let aggregates = TemporalAggregator::new(IndexPeriod::Annual)
+            .with_min_valid_days(300)
+            .aggregate(&records);
+        assert_eq!(aggregates[0].temp_avg_mean, None);
+        assert_eq!(aggregates[0].temp_avg_days, 3);
+        assert_eq!(aggregates[0].days_in_period, 365);
+    }
+
+    #[test]
+    fn test_minimum_quality() {
+        assert!(MinimumQuality::Suspect.accepts(DataQuality::SuspectRange));
+        assert!(!MinimumQuality::Suspect.accepts(DataQuality::Invalid));
+        assert!(MinimumQuality::Any.accepts(DataQuality::Invalid));
+        assert!(!MinimumQuality::Any.accepts(DataQuality::Missing));
+        assert!(!MinimumQuality::Valid.accepts(DataQuality::SuspectOriginal));
+        assert_eq!(
+            "VALID".parse::<MinimumQuality>().unwrap(),
+            MinimumQuality::Valid
+        );
+        assert!("best".parse::<MinimumQuality>().is_err());
+    }
+}
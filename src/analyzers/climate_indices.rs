@@ -0,0 +1,803 @@
+use crate::error::{ProcessingError, Result};
+use crate::models::weather::PhysicalValidity;
+use crate::models::WeatherRecord;
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// Daily precipitation (mm) at or above which a day counts as wet
+const WET_DAY_MM: f32 = 1.0;
+
+/// Half-width of the calendar-day window used for percentile thresholds
+const PERCENTILE_WINDOW: i64 = 2;
+
+/// Minimum share of base-period days needed to derive a percentile threshold
+const MIN_BASE_COVERAGE: f64 = 0.7;
+
+/// Aggregation period for climate indices
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexPeriod {
+    Annual,
+    Monthly,
+    Seasonal,
+}
+
+impl IndexPeriod {
+    /// ETCCDI completeness rule: more missing days than this voids a value
+    pub fn max_missing_days(&self) -> usize {
+        match self {
+            IndexPeriod::Annual => 15,
+            IndexPeriod::Seasonal => 9,
+            IndexPeriod::Monthly => 3,
+        }
+    }
+
+    /// The (year, label) of the period a date falls in. December belongs to
+    /// the following year's DJF season.
+    pub fn period_of(&self, date: NaiveDate) -> (i32, &'static str) {
+        const MONTHS: [&str; 12] = [
+            "01", "02", "03", "04", "05", "06", "07", "08", "09", "10", "11", "12",
+        ];
+        match self {
+            IndexPeriod::Annual => (date.year(), "annual"),
+            IndexPeriod::Monthly => (date.year(), MONTHS[date.month0() as usize]),
+            IndexPeriod::Seasonal => match date.month() {
+                12 => (date.year() + 1, "DJF"),
+                1 | 2 => (date.year(), "DJF"),
+                3..=5 => (date.year(), "MAM"),
+                6..=8 => (date.year(), "JJA"),
+                _ => (date.year(), "SON"),
+            },
+        }
+    }
+
+    /// First and last day of a period
+    pub fn date_range(&self, year: i32, label: &str) -> Option<(NaiveDate, NaiveDate)> {
+        let first = |y, m| NaiveDate::from_ymd_opt(y, m, 1);
+        let last = |y, m: u32| {
+            let (ny, nm) = if m == 12 { (y + 1, 1) } else { (y, m + 1) };
+            first(ny, nm).map(|d| d - Duration::days(1))
+        };
+
+        let (start, end) = match (self, label) {
+            (IndexPeriod::Annual, _) => (first(year, 1)?, last(year, 12)?),
+            (IndexPeriod::Monthly, month) => {
+                let month = month.parse().ok()?;
+                (first(year, month)?, last(year, month)?)
+            }
+            (IndexPeriod::Seasonal, "DJF") => (first(year - 1, 12)?, last(year, 2)?),
+            (IndexPeriod::Seasonal, "MAM") => (first(year, 3)?, last(year, 5)?),
+            (IndexPeriod::Seasonal, "JJA") => (first(year, 6)?, last(year, 8)?),
+            (IndexPeriod::Seasonal, "SON") => (first(year, 9)?, last(year, 11)?),
+            _ => return None,
+        };
+        Some((start, end))
+    }
+}
+
+impl FromStr for IndexPeriod {
+    type Err = ProcessingError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "annual" | "year" | "yearly" => Ok(IndexPeriod::Annual),
+            "monthly" | "month" => Ok(IndexPeriod::Monthly),
+            "seasonal" | "season" => Ok(IndexPeriod::Seasonal),
+            other => Err(ProcessingError::Config(format!(
+                "Unsupported index period: {} (expected annual, monthly or seasonal)",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for IndexPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IndexPeriod::Annual => write!(f, "annual"),
+            IndexPeriod::Monthly => write!(f, "monthly"),
+            IndexPeriod::Seasonal => write!(f, "seasonal"),
+        }
+    }
+}
+
+/// Inclusive range of years used for percentile thresholds, e.g. 1961-1990
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BasePeriod {
+    pub start_year: i32,
+    pub end_year: i32,
+}
+
+impl BasePeriod {
+    pub fn new(start_year: i32, end_year: i32) -> Result<Self> {
+        if start_year > end_year {
+            return Err(ProcessingError::Config(format!(
+                "Base period start {} is after end {}",
+                start_year, end_year
+            )));
+        }
+        Ok(Self {
+            start_year,
+            end_year,
+        })
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        (self.start_year..=self.end_year).contains(&date.year())
+    }
+
+    pub fn years(&self) -> usize {
+        (self.end_year - self.start_year + 1) as usize
+    }
+}
+
+impl Default for BasePeriod {
+    fn default() -> Self {
+        Self {
+            start_year: 1961,
+            end_year: 1990,
+        }
+    }
+}
+
+impl fmt::Display for BasePeriod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.start_year, self.end_year)
+    }
+}
+
+impl FromStr for BasePeriod {
+    type Err = ProcessingError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || ProcessingError::Config(format!("Invalid base period: {}", s));
+        let (start, end) = s.split_once('-').ok_or_else(invalid)?;
+        Self::new(
+            start.trim().parse().map_err(|_| invalid())?,
+            end.trim().parse().map_err(|_| invalid())?,
+        )
+    }
+}
+
+/// One index value for a station and period
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexValue {
+    pub station_id: u32,
+    pub station_name: String,
+    pub year: i32,
+    /// `annual`, a month `01`-`12`, or a season `DJF`/`MAM`/`JJA`/`SON`
+    pub period: String,
+    pub index: &'static str,
+    /// None when the period has too many missing days
+    pub value: Option<f64>,
+    pub units: &'static str,
+}
+
+/// Computes ETCCDI climate extremes indices from daily weather records
+pub struct ClimateIndexCalculator {
+    period: IndexPeriod,
+    base_period: BasePeriod,
+}
+
+impl ClimateIndexCalculator {
+    pub fn new(period: IndexPeriod) -> Self {
+        Self {
+            period,
+            base_period: BasePeriod::default(),
+        }
+    }
+
+    pub fn with_base_period(mut self, base_period: BasePeriod) -> Self {
+        self.base_period = base_period;
+        self
+    }
+
+    /// Compute every index for every station and period in `records`.
+    ///
+    /// Indices are only reported for elements a station actually measures.
+    pub fn compute(&self, records: &[WeatherRecord]) -> Vec<IndexValue> {
+        let mut stations: BTreeMap<u32, Vec<&WeatherRecord>> = BTreeMap::new();
+        for record in records {
+            stations.entry(record.station_id).or_default().push(record);
+        }
+
+        stations
+            .into_values()
+            .flat_map(|station_records| self.compute_station(&station_records))
+            .collect()
+    }
+
+    fn compute_station(&self, records: &[&WeatherRecord]) -> Vec<IndexValue> {
+        let Some(first) = records.first() else {
+            return Vec::new();
+        };
+
+        let daily: BTreeMap<NaiveDate, Day> = records
+            .iter()
+            .map(|r| (r.date, Day::from_record(r)))
+            .collect();
+
+        let has_tx = daily.values().any(|d| d.tx.is_some());
+        let has_tn = daily.values().any(|d| d.tn.is_some());
+        let has_tg = daily.values().any(|d| d.tg.is_some());
+        let has_rr = daily.values().any(|d| d.rr.is_some());
+
+        let thresholds = Thresholds::from_base_period(&daily, &self.base_period);
+        // Every period the station's record spans, so gaps show up as nulls
+        let (Some(start), Some(end)) = (daily.keys().next(), daily.keys().next_back()) else {
+            return Vec::new();
+        };
+        let periods: BTreeSet<(i32, &'static str)> = start
+            .iter_days()
+            .take_while(|date| date <= end)
+            .map(|date| self.period.period_of(date))
+            .collect();
+        let max_missing = self.period.max_missing_days();
+
+        let mut values = Vec::new();
+        for (year, label) in periods {
+            let Some((start, end)) = self.period.date_range(year, label) else {
+                continue;
+            };
+
+            let days: Vec<(NaiveDate, Day)> = start
+                .iter_days()
+                .take_while(|date| *date <= end)
+                .map(|date| (date, daily.get(&date).copied().unwrap_or_default()))
+                .collect();
+            let tx: Vec<Option<f32>> = days.iter().map(|(_, d)| d.tx).collect();
+            let tn: Vec<Option<f32>> = days.iter().map(|(_, d)| d.tn).collect();
+            let rr: Vec<Option<f32>> = days.iter().map(|(_, d)| d.rr).collect();
+            let dtr: Vec<Option<f32>> = days
+                .iter()
+                .map(|(_, d)| d.tx.zip(d.tn).map(|(x, n)| x - n))
+                .collect();
+            let dates: Vec<NaiveDate> = days.iter().map(|(date, _)| *date).collect();
+
+            let mut push = |index, units, value| {
+                values.push(IndexValue {
+                    station_id: first.station_id,
+                    station_name: first.station_name.clone(),
+                    year,
+                    period: label.to_string(),
+                    index,
+                    value,
+                    units,
+                });
+            };
+
+            if has_tn {
+                push("FD", "days", count_days(&tn, max_missing, |t| t < 0.0));
+                push("TR", "days", count_days(&tn, max_missing, |t| t > 20.0));
+                push("TNn", "°C", extreme(&tn, max_missing, f32::min));
+            }
+            if has_tx {
+                push("SU", "days", count_days(&tx, max_missing, |t| t > 25.0));
+                push("ID", "days", count_days(&tx, max_missing, |t| t < 0.0));
+                push("TXx", "°C", extreme(&tx, max_missing, f32::max));
+            }
+            if has_tx && has_tn {
+                push("DTR", "°C", mean(&dtr, max_missing));
+            }
+            if has_tg && self.period == IndexPeriod::Annual {
+                let gsl = if first.latitude >= 0.0 {
+                    growing_season_length(&days, max_missing)
+                } else {
+                    None // Southern Hemisphere seasons span July-June
+                };
+                push("GSL", "days", gsl);
+            }
+            if has_rr {
+                push("Rx1day", "mm", extreme(&rr, max_missing, f32::max));
+                push("Rx5day", "mm", max_window_sum(&rr, max_missing, 5));
+                push("SDII", "mm/day", wet_day_intensity(&rr, max_missing));
+                push("R10mm", "days", count_days(&rr, max_missing, |p| p >= 10.0));
+                push("R20mm", "days", count_days(&rr, max_missing, |p| p >= 20.0));
+                push(
+                    "CDD",
+                    "days",
+                    longest_spell(&rr, max_missing, |p| p < WET_DAY_MM),
+                );
+                push(
+                    "CWD",
+                    "days",
+                    longest_spell(&rr, max_missing, |p| p >= WET_DAY_MM),
+                );
+                push(
+                    "PRCPTOT",
+                    "mm",
+                    sum_where(&rr, max_missing, |p| p >= WET_DAY_MM),
+                );
+            }
+            if has_tx {
+                push(
+                    "TX90p",
+                    "%",
+                    percent_beyond(&dates, &tx, max_missing, |date, t| {
+                        thresholds.tx90_exceedance(date, t)
+                    }),
+                );
+            }
+            if has_tn {
+                push(
+                    "TN10p",
+                    "%",
+                    percent_beyond(&dates, &tn, max_missing, |date, t| {
+                        thresholds.tn10_exceedance(date, t)
+                    }),
+                );
+            }
+            if has_rr {
+                let r95 = thresholds
+                    .r95
+                    .and_then(|q| sum_where(&rr, max_missing, |p| p >= WET_DAY_MM && p > q));
+                push("R95p", "mm", r95);
+            }
+        }
+
+        values
+    }
+}
+
+/// The element values of one day, with physically invalid values dropped
+#[derive(Debug, Clone, Copy, Default)]
+struct Day {
+    tx: Option<f32>,
+    tn: Option<f32>,
+    tg: Option<f32>,
+    rr: Option<f32>,
+}
+
+impl Day {
+    fn from_record(record: &WeatherRecord) -> Self {
+        let temp_ok = record.temp_validation != Some(PhysicalValidity::Invalid);
+        let precip_ok = record.precip_validation != Some(PhysicalValidity::Invalid);
+        Self {
+            tx: record.temp_max.filter(|_| temp_ok),
+            tn: record.temp_min.filter(|_| temp_ok),
+            tg: record.temp_avg.filter(|_| temp_ok),
+            rr: record.precipitation.filter(|_| precip_ok),
+        }
+    }
+}
+
+/// Calendar-day percentile thresholds from the base period.
+///
+/// Days inside the base period are judged with the ETCCDI bootstrap (Zhang et
+/// al., 2005) rather than the plain thresholds, which would otherwise make
+/// in-base exceedance rates jump at the period's edges: their year is left
+/// out, each other base year is counted twice in turn, and the exceedances
+/// against those thresholds are averaged.
+struct Thresholds {
+    tx90: Vec<Option<f32>>,
+    tn10: Vec<Option<f32>>,
+    r95: Option<f32>,
+    /// Per calendar day, the window values contributed by each base year
+    tx_windows: Vec<BTreeMap<i32, Vec<f32>>>,
+    tn_windows: Vec<BTreeMap<i32, Vec<f32>>>,
+    base: BasePeriod,
+    min_window_values: usize,
+}
+
+impl Thresholds {
+    fn from_base_period(daily: &BTreeMap<NaiveDate, Day>, base: &BasePeriod) -> Self {
+        let mut tx_windows: Vec<BTreeMap<i32, Vec<f32>>> = vec![BTreeMap::new(); 365];
+        let mut tn_windows: Vec<BTreeMap<i32, Vec<f32>>> = vec![BTreeMap::new(); 365];
+        let mut wet_days = Vec::new();
+        let mut rr_days = 0;
+
+        for (date, day) in daily.iter().filter(|(date, _)| base.contains(**date)) {
+            // Each value counts toward the 5-day window centred on its neighbours
+            let centre = calendar_day(*date) as i64;
+            for offset in -PERCENTILE_WINDOW..=PERCENTILE_WINDOW {
+                let idx = (centre + offset).rem_euclid(365) as usize;
+                if let Some(tx) = day.tx {
+                    tx_windows[idx].entry(date.year()).or_default().push(tx);
+                }
+                if let Some(tn) = day.tn {
+                    tn_windows[idx].entry(date.year()).or_default().push(tn);
+                }
+            }
+            if let Some(rr) = day.rr {
+                rr_days += 1;
+                if rr >= WET_DAY_MM {
+                    wet_days.push(rr);
+                }
+            }
+        }
+
+        let window_days = (2 * PERCENTILE_WINDOW + 1) as usize * base.years();
+        let min_window_values = (window_days as f64 * MIN_BASE_COVERAGE).ceil() as usize;
+
+        let r95 = if rr_days as f64 >= 365.0 * base.years() as f64 * MIN_BASE_COVERAGE {
+            percentile(&mut wet_days, 0.95)
+        } else {
+            None
+        };
+
+        let all_years = |windows: &[BTreeMap<i32, Vec<f32>>], p| {
+            windows
+                .iter()
+                .map(|by_year| {
+                    let values = by_year.values().flatten().copied().collect();
+                    window_threshold(values, p, min_window_values)
+                })
+                .collect()
+        };
+
+        Self {
+            tx90: all_years(&tx_windows, 0.9),
+            tn10: all_years(&tn_windows, 0.1),
+            r95,
+            tx_windows,
+            tn_windows,
+            base: *base,
+            min_window_values,
+        }
+    }
+
+    /// Share (0-1) of the TX 90th percentile thresholds for `date` that `tx` exceeds
+    fn tx90_exceedance(&self, date: NaiveDate, tx: f32) -> Option<f64> {
+        self.exceedance(date, tx, &self.tx90, &self.tx_windows, 0.9, |t, q| t > q)
+    }
+
+    /// Share (0-1) of the TN 10th percentile thresholds for `date` that `tn` is below
+    fn tn10_exceedance(&self, date: NaiveDate, tn: f32) -> Option<f64> {
+        self.exceedance(date, tn, &self.tn10, &self.tn_windows, 0.1, |t, q| t < q)
+    }
+
+    fn exceedance(
+        &self,
+        date: NaiveDate,
+        value: f32,
+        plain: &[Option<f32>],
+        windows: &[BTreeMap<i32, Vec<f32>>],
+        p: f64,
+        beyond: impl Fn(f32, f32) -> bool,
+    ) -> Option<f64> {
+        let day = calendar_day(date);
+        // A single base year leaves nothing to resample
+        if !self.base.contains(date) || self.base.years() < 2 {
+            return plain[day].map(|q| if beyond(value, q) { 1.0 } else { 0.0 });
+        }
+
+        let year = date.year();
+        let by_year = &windows[day];
+        // Sorted once, so each replicate only appends a few values to a sorted run
+        let mut left_out: Vec<f32> = by_year
+            .iter()
+            .filter(|(y, _)| **y != year)
+            .flat_map(|(_, v)| v.iter().copied())
+            .collect();
+        left_out.sort_by(|a, b| a.total_cmp(b));
+        let outcomes: Vec<bool> = (self.base.start_year..=self.base.end_year)
+            .filter(|&other| other != year)
+            .filter_map(|other| {
+                let mut values = left_out.clone();
+                values.extend(by_year.get(&other).into_iter().flatten());
+                window_threshold(values, p, self.min_window_values)
+            })
+            .map(|q| beyond(value, q))
+            .collect();
+        if outcomes.is_empty() {
+            return None;
+        }
+        let exceeded = outcomes.iter().filter(|e| **e).count();
+        Some(exceeded as f64 / outcomes.len() as f64)
+    }
+}
+
+/// Percentile of a calendar-day window, if it has enough base-period values
+fn window_threshold(mut values: Vec<f32>, p: f64, min_values: usize) -> Option<f32> {
+    (values.len() >= min_values)
+        .then(|| percentile(&mut values, p))
+        .flatten()
+}
+
+/// Day of a 365-day calendar (0-364), with 29 February folded onto the 28th
+pub(crate) fn calendar_day(date: NaiveDate) -> usize {
+    let day = if date.month() == 2 && date.day() == 29 {
+        28
+    } else {
+        date.day()
+    };
+    NaiveDate::from_ymd_opt(2001, date.month(), day)
+        .map(|d| d.ordinal0() as usize)
+        .unwrap_or(0)
+}
+
+/// Linearly interpolated percentile (`p` in 0-1) of `values`
+fn percentile(values: &mut [f32], p: f64) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let rank = p * (values.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let fraction = (rank - lower as f64) as f32;
+    Some(values[lower] + (values[upper] - values[lower]) * fraction)
+}
+
+fn is_complete(values: &[Option<f32>], max_missing: usize) -> bool {
+    values.iter().filter(|v| v.is_none()).count() <= max_missing
+}
+
+fn count_days(
+    values: &[Option<f32>],
+    max_missing: usize,
+    pred: impl Fn(f32) -> bool,
+) -> Option<f64> {
+    is_complete(values, max_missing)
+        .then(|| values.iter().flatten().filter(|v| pred(**v)).count() as f64)
+}
+
+fn sum_where(
+    values: &[Option<f32>],
+    max_missing: usize,
+    pred: impl Fn(f32) -> bool,
+) -> Option<f64> {
+    is_complete(values, max_missing).then(|| {
+        values
+            .iter()
+            .flatten()
+            .filter(|v| pred(**v))
+            .map(|v| *v as f64)
+            .sum()
+    })
+}
+
+fn extreme(values: &[Option<f32>], max_missing: usize, pick: fn(f32, f32) -> f32) -> Option<f64> {
+    if !is_complete(values, max_missing) {
+        return None;
+    }
+    values.iter().flatten().copied().reduce(pick).map(f64::from)
+}
+
+fn mean(values: &[Option<f32>], max_missing: usize) -> Option<f64> {
+    if !is_complete(values, max_missing) {
+        return None;
+    }
+    let present: Vec<f64> = values.iter().flatten().map(|v| *v as f64).collect();
+    (!present.is_empty()).then(|| present.iter().sum::<f64>() / present.len() as f64)
+}
+
+/// Mean precipitation on wet days (SDII)
+fn wet_day_intensity(values: &[Option<f32>], max_missing: usize) -> Option<f64> {
+    let wet: Vec<Option<f32>> = values
+        .iter()
+        .map(|v| v.filter(|p| *p >= WET_DAY_MM))
+        .collect();
+    let total = sum_where(values, max_missing, |p| p >= WET_DAY_MM)?;
+    let count = wet.iter().flatten().count();
+    Some(if count == 0 {
+        0.0
+    } else {
+        total / count as f64
+    })
+}
+
+/// Longest run of consecutive days matching `pred`; a missing day ends a run
+fn longest_spell(
+    values: &[Option<f32>],
+    max_missing: usize,
+    pred: impl Fn(f32) -> bool,
+) -> Option<f64> {
+    if !is_complete(values, max_missing) {
+        return None;
+    }
+    let mut longest = 0;
+    let mut current = 0;
+    for value in values {
+        if value.is_some_and(&pred) {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    Some(longest as f64)
+}
+
+/// Largest total over `window` consecutive days with no missing values
+fn max_window_sum(values: &[Option<f32>], max_missing: usize, window: usize) -> Option<f64> {
+    if !is_complete(values, max_missing) {
+        return None;
+    }
+    values
+        .windows(window)
+        .filter_map(|w| w.iter().map(|v| v.map(f64::from)).sum::<Option<f64>>())
+        .reduce(f64::max)
+}
+
+/// Percentage of days with a value and a threshold that lie beyond it, where
+/// `exceedance` gives each day's share (0-1) of thresholds exceeded
+fn percent_beyond(
+    dates: &[NaiveDate],
+    values: &[Option<f32>],
+    max_missing: usize,
+    exceedance: impl Fn(NaiveDate, f32) -> Option<f64>,
+) -> Option<f64> {
+    if !is_complete(values, max_missing) {
+        return None;
+    }
+    let shares: Vec<f64> = dates
+        .iter()
+        .zip(values)
+        .filter_map(|(date, v)| v.and_then(|v| exceedance(*date, v)))
+        .collect();
+    if shares.is_empty() {
+        return None;
+    }
+    Some(shares.iter().sum::<f64>() / shares.len() as f64 * 100.0)
+}
+
+/// Days between the first 6-day spell with TG > 5°C and the first 6-day
+/// spell with TG < 5°C after 1 July (Northern Hemisphere)
+fn growing_season_length(days: &[(NaiveDate, Day)], max_missing: usize) -> Option<f64> {
+    let tg: Vec<Option<f32>> = days.iter().map(|(_, d)| d.tg).collect();
+    if !is_complete(&tg, max_missing) {
+        return None;
+    }
+
+    let spell_start = |from: usize, pred: &dyn Fn(f32) -> bool| {
+        (from..tg.len().saturating_sub(5))
+            .find(|&i| tg[i..i + 6].iter().all(|v| v.is_some_and(pred)))
+    };
+
+    let Some(start) = spell_start(0, &|t| t > 5.0) else {
+        return Some(0.0);
+    };
+    let july = days
+        .iter()
+        .position(|(date, _)| date.month() >= 7)
+        .unwrap_or(days.len());
+    let end = spell_start(july.max(start), &|t| t < 5.0).unwrap_or(days.len());
+
+    Some((end - start) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(date: NaiveDate, tn: f32, tx: f32, rr: f32) -> WeatherRecord {
+        WeatherRecord::new(
+            257,
+            "Test Station".to_string(),
+            date,
+            52.4,
+            -1.8,
+            Some(tn),
+            Some(tx),
+            Some((tn + tx) / 2.0),
+            Some(rr),
+            None,
+            Some("0".to_string()),
+            Some("0".to_string()),
+            None,
+        )
+    }
+
+    fn year_of_records(year: i32, f: impl Fn(NaiveDate) -> (f32, f32, f32)) -> Vec<WeatherRecord> {
+        NaiveDate::from_ymd_opt(year, 1, 1)
+            .unwrap()
+            .iter_days()
+            .take_while(|d| d.year() == year)
+            .map(|d| {
+                let (tn, tx, rr) = f(d);
+                record(d, tn, tx, rr)
+            })
+            .collect()
+    }
+
+    fn value(values: &[IndexValue], index: &str) -> Option<f64> {
+        values.iter().find(|v| v.index == index).unwrap().value
+    }
+
+    #[test]
+    fn test_annual_indices() {
+        // Frosty January, hot July, a wet week in October
+        let records = year_of_records(2001, |d| match d.month() {
+            1 => (-3.0, 2.0, 0.0),
+            7 => (21.0, 30.0, 0.0),
+            10 if d.day() <= 7 => (8.0, 14.0, 12.0),
+            _ => (8.0, 14.0, 0.0),
+        });
+
+        let values = ClimateIndexCalculator::new(IndexPeriod::Annual)
+            .with_base_period(BasePeriod::new(2001, 2001).unwrap())
+            .compute(&records);
+
+        assert_eq!(value(&values, "FD"), Some(31.0));
+        assert_eq!(value(&values, "TR"), Some(31.0));
+        assert_eq!(value(&values, "SU"), Some(31.0));
+        assert_eq!(value(&values, "ID"), Some(0.0));
+        assert_eq!(value(&values, "TXx"), Some(30.0));
+        assert_eq!(value(&values, "TNn"), Some(-3.0));
+        assert_eq!(value(&values, "Rx1day"), Some(12.0));
+        assert_eq!(value(&values, "Rx5day"), Some(60.0));
+        assert_eq!(value(&values, "R10mm"), Some(7.0));
+        assert_eq!(value(&values, "R20mm"), Some(0.0));
+        assert_eq!(value(&values, "CWD"), Some(7.0));
+        assert_eq!(value(&values, "PRCPTOT"), Some(84.0));
+        assert_eq!(value(&values, "SDII"), Some(12.0));
+        assert_eq!(value(&values, "CDD"), Some(273.0));
+
+        // TG > 5°C from February until the year ends
+        assert_eq!(value(&values, "GSL"), Some(334.0));
+        assert!(value(&values, "TX90p").is_some());
+    }
+
+    #[test]
+    fn test_incomplete_period_is_missing() {
+        let mut records = year_of_records(2001, |_| (5.0, 10.0, 0.0));
+        records.retain(|r| r.date.month() != 3); // 31 missing days
+
+        // March still gets a row for each index, with no value
+        let values = ClimateIndexCalculator::new(IndexPeriod::Monthly).compute(&records);
+        let march: Vec<&IndexValue> = values.iter().filter(|v| v.period == "03").collect();
+        assert!(march.iter().any(|v| v.index == "FD"));
+        assert!(march.iter().all(|v| v.value.is_none()));
+
+        let values = ClimateIndexCalculator::new(IndexPeriod::Annual).compute(&records);
+        assert_eq!(value(&values, "FD"), None);
+    }
+
+    #[test]
+    fn test_in_base_exceedance_is_bootstrapped() {
+        // Warm 2001, cool 2002, warm again after the base period
+        let mut records = year_of_records(2001, |_| (10.0, 20.0, 0.0));
+        records.extend(year_of_records(2002, |_| (0.0, 10.0, 0.0)));
+        records.extend(year_of_records(2003, |_| (10.0, 20.0, 0.0)));
+
+        let values = ClimateIndexCalculator::new(IndexPeriod::Annual)
+            .with_base_period(BasePeriod::new(2001, 2002).unwrap())
+            .compute(&records);
+        let tx90p = |year| {
+            values
+                .iter()
+                .find(|v| v.year == year && v.index == "TX90p")
+                .unwrap()
+                .value
+        };
+
+        // Judged against 2002 counted twice, every 2001 day is warm
+        assert_eq!(tx90p(2001), Some(100.0));
+        assert_eq!(tx90p(2002), Some(0.0));
+        // Outside the base period the plain threshold (20°C) applies
+        assert_eq!(tx90p(2003), Some(0.0));
+    }
+
+    #[test]
+    fn test_seasonal_periods() {
+        let dec = NaiveDate::from_ymd_opt(2000, 12, 15).unwrap();
+        assert_eq!(IndexPeriod::Seasonal.period_of(dec), (2001, "DJF"));
+        assert_eq!(
+            IndexPeriod::Seasonal.date_range(2001, "DJF"),
+            Some((
+                NaiveDate::from_ymd_opt(2000, 12, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2001, 2, 28).unwrap()
+            ))
+        );
+        assert_eq!(
+            "season".parse::<IndexPeriod>().unwrap(),
+            IndexPeriod::Seasonal
+        );
+        assert_eq!(
+            "1971-2000".parse::<BasePeriod>().unwrap(),
+            BasePeriod::new(1971, 2000).unwrap()
+        );
+        assert!("2000-1971".parse::<BasePeriod>().is_err());
+    }
+
+    #[test]
+    fn test_percentile() {
+        let mut values = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(percentile(&mut values, 0.5), Some(3.0));
+        assert_eq!(percentile(&mut values, 0.9), Some(4.6));
+        assert_eq!(percentile(&mut [], 0.9), None);
+    }
+}
@@ -0,0 +1,641 @@
+use crate::error::{ProcessingError, Result};
+use crate::models::weather::DataQuality;
+use crate::models::{BoundingBox, WeatherRecord};
+use crate::utils::coordinates::haversine_distance;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Value columns that can be gridded. Wind direction is left out because
+/// inverse-distance weighting of angles is meaningless.
+pub const GRIDDABLE_METRICS: [&str; 12] = [
+    "temp_min",
+    "temp_max",
+    "temp_avg",
+    "precipitation",
+    "wind_speed",
+    "sunshine",
+    "humidity",
+    "pressure",
+    "cloud_cover",
+    "snow_depth",
+    "wind_gust",
+    "global_radiation",
+];
+
+/// Largest grid accepted, to catch a resolution given in the wrong units
+const MAX_CELLS: usize = 10_000_000;
+
+/// Distances are clamped to this so a station at a cell centre gets a
+/// large but finite weight
+const MIN_DISTANCE_KM: f64 = 0.01;
+
+/// A regular latitude/longitude grid. The bounding box gives the outer cell
+/// edges and values are estimated at cell centres.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridSpec {
+    pub min_latitude: f64,
+    pub max_latitude: f64,
+    pub min_longitude: f64,
+    pub max_longitude: f64,
+    /// Cell size in degrees
+    pub resolution: f64,
+    rows: usize,
+    cols: usize,
+}
+
+impl GridSpec {
+    pub fn new(
+        min_latitude: f64,
+        max_latitude: f64,
+        min_longitude: f64,
+        max_longitude: f64,
+        resolution: f64,
+    ) -> Result<Self> {
+        if !(resolution > 0.0 && resolution.is_finite()) {
+            return Err(ProcessingError::Config(format!(
+                "Grid resolution must be a positive number of degrees, got {}",
+                resolution
+            )));
+        }
+        if !(-90.0..=90.0).contains(&min_latitude)
+            || !(-90.0..=90.0).contains(&max_latitude)
+            || !(-180.0..=180.0).contains(&min_longitude)
+            || !(-180.0..=180.0).contains(&max_longitude)
+            || min_latitude >= max_latitude
+            || min_longitude >= max_longitude
+        {
+            return Err(ProcessingError::Config(format!(
+                "Invalid grid bounds: latitude {} to {}, longitude {} to {}",
+                min_latitude, max_latitude, min_longitude, max_longitude
+            )));
+        }
+
+        // Tolerate bounds that are a whole number of cells up to rounding
+        let cells = |span: f64| ((span / resolution) - 1e-9).ceil().max(1.0) as usize;
+        let rows = cells(max_latitude - min_latitude);
+        let cols = cells(max_longitude - min_longitude);
+        if rows.saturating_mul(cols) > MAX_CELLS {
+            return Err(ProcessingError::Config(format!(
+                "Grid of {} x {} cells is too large; use a coarser resolution",
+                rows, cols
+            )));
+        }
+
+        Ok(Self {
+            min_latitude,
+            max_latitude,
+            min_longitude,
+            max_longitude,
+            resolution,
+            rows,
+            cols,
+        })
+    }
+
+    /// Grid covering a station-selection bounding box
+    pub fn from_bbox(bbox: &BoundingBox, resolution: f64) -> Result<Self> {
+        Self::new(
+            bbox.min_lat,
+            bbox.max_lat,
+            bbox.min_lon,
+            bbox.max_lon,
+            resolution,
+        )
+    }
+
+    /// Smallest grid aligned to multiples of `resolution` that covers every
+    /// station in the records
+    pub fn covering(records: &[WeatherRecord], resolution: f64) -> Result<Self> {
+        if records.is_empty() {
+            return Err(ProcessingError::Config(
+                "No stations to derive a grid from".to_string(),
+            ));
+        }
+        let (mut min_lat, mut max_lat) = (f64::MAX, f64::MIN);
+        let (mut min_lon, mut max_lon) = (f64::MAX, f64::MIN);
+        for record in records {
+            min_lat = min_lat.min(record.latitude);
+            max_lat = max_lat.max(record.latitude);
+            min_lon = min_lon.min(record.longitude);
+            max_lon = max_lon.max(record.longitude);
+        }
+        let floor = |v: f64| (v / resolution).floor() * resolution;
+        let ceil = |v: f64| ((v / resolution).floor() + 1.0) * resolution;
+        Self::new(
+            floor(min_lat).max(-90.0),
+            ceil(max_lat).min(90.0),
+            floor(min_lon).max(-180.0),
+            ceil(max_lon).min(180.0),
+            resolution,
+        )
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    /// Latitude and longitude of a cell centre, cells numbered row by row
+    /// from the south-west corner
+    pub fn centre(&self, cell: usize) -> (f64, f64) {
+        let (row, col) = (cell / self.cols, cell % self.cols);
+        (
+            self.min_latitude + (row as f64 + 0.5) * self.resolution,
+            self.min_longitude + (col as f64 + 0.5) * self.resolution,
+        )
+    }
+
+    /// Cell containing a point, if it lies inside the grid
+    pub fn cell_at(&self, latitude: f64, longitude: f64) -> Option<usize> {
+        if !(self.min_latitude..=self.max_latitude).contains(&latitude)
+            || !(self.min_longitude..=self.max_longitude).contains(&longitude)
+        {
+            return None;
+        }
+        let index = |offset: f64, count: usize| {
+            ((offset / self.resolution).floor() as usize).min(count - 1)
+        };
+        let row = index(latitude - self.min_latitude, self.rows);
+        let col = index(longitude - self.min_longitude, self.cols);
+        Some(row * self.cols + col)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ElevationRow {
+    latitude: f64,
+    longitude: f64,
+    elevation: f64,
+}
+
+/// Surface elevation of each grid cell in metres, for lapse-rate adjustment
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellElevations(Vec<Option<f64>>);
+
+impl CellElevations {
+    /// Assign `(latitude, longitude, elevation)` points to the cells that
+    /// contain them; later points replace earlier ones
+    pub fn from_points(grid: &GridSpec, points: impl IntoIterator<Item = (f64, f64, f64)>) -> Self {
+        let mut elevations = vec![None; grid.cell_count()];
+        for (latitude, longitude, elevation) in points {
+            if let Some(cell) = grid.cell_at(latitude, longitude) {
+                elevations[cell] = Some(elevation);
+            }
+        }
+        Self(elevations)
+    }
+
+    /// Read a CSV with `latitude`, `longitude` and `elevation` columns,
+    /// typically one row per cell centre sampled from a DEM
+    pub fn from_csv(path: &Path, grid: &GridSpec) -> Result<Self> {
+        let mut reader = csv::Reader::from_path(path)?;
+        let points = reader
+            .deserialize::<ElevationRow>()
+            .map(|row| row.map(|r| (r.latitude, r.longitude, r.elevation)))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(Self::from_points(grid, points))
+    }
+
+    pub fn get(&self, cell: usize) -> Option<f64> {
+        self.0.get(cell).copied().flatten()
+    }
+
+    /// Cells with a known elevation
+    pub fn known(&self) -> usize {
+        self.0.iter().filter(|e| e.is_some()).count()
+    }
+}
+
+/// One gridded value
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridValue {
+    pub date: NaiveDate,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub metric: &'static str,
+    pub value: f64,
+    /// Stations whose values were weighted into the cell
+    pub station_count: u32,
+}
+
+/// Counts from a gridding run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GridSummary {
+    pub days: usize,
+    pub cells: usize,
+    pub stations: usize,
+    pub values: usize,
+    /// Cells with no station within the search radius
+    pub cells_out_of_range: usize,
+}
+
+impl GridSummary {
+    pub fn summary(&self) -> String {
+        format!(
+            "Gridded {} values over {} days from {} stations onto {} cells ({} beyond reach of any station)",
+            self.values, self.days, self.stations, self.cells, self.cells_out_of_range
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GridStation {
+    elevation: Option<f64>,
+}
+
+/// Interpolates daily station values onto a regular grid by inverse-distance
+/// weighting of the nearest stations that reported.
+///
+/// Only values with Valid quality that were observed rather than infilled are
+/// used. With a lapse rate, station temperatures are reduced to sea level
+/// before weighting and raised to each cell's elevation afterwards.
+pub struct Gridder {
+    grid: GridSpec,
+    metrics: Vec<&'static str>,
+    power: f64,
+    max_distance_km: f64,
+    max_stations: usize,
+    min_stations: usize,
+    lapse_rate: Option<(f64, CellElevations)>,
+}
+
+impl Gridder {
+    pub fn new(grid: GridSpec) -> Self {
+        Self {
+            grid,
+            metrics: vec!["temp_avg"],
+            power: 2.0,
+            max_distance_km: 100.0,
+            max_stations: 8,
+            min_stations: 1,
+            lapse_rate: None,
+        }
+    }
+
+    /// Parse a comma-separated list of value columns to grid
+    pub fn parse_metrics(list: &str) -> Result<Vec<&'static str>> {
+        let mut metrics = Vec::new();
+        for metric in list.split(',').map(str::trim).filter(|m| !m.is_empty()) {
+            let name = GRIDDABLE_METRICS
+                .iter()
+                .find(|m| m.eq_ignore_ascii_case(metric))
+                .ok_or_else(|| {
+                    ProcessingError::Config(format!(
+                        "Cannot grid '{}' (expected one of: {})",
+                        metric,
+                        GRIDDABLE_METRICS.join(", ")
+                    ))
+                })?;
+            if !metrics.contains(name) {
+                metrics.push(*name);
+            }
+        }
+        Ok(metrics)
+    }
+
+    /// Value columns to grid, from [`Gridder::parse_metrics`]
+    pub fn with_metrics(mut self, metrics: Vec<&'static str>) -> Result<Self> {
+        if metrics.is_empty() {
+            return Err(ProcessingError::Config(
+                "At least one metric is required for gridding".to_string(),
+            ));
+        }
+        self.metrics = metrics;
+        Ok(self)
+    }
+
+    /// Distance exponent of the inverse-distance weights
+    pub fn with_power(mut self, power: f64) -> Result<Self> {
+        if !(power > 0.0 && power.is_finite()) {
+            return Err(ProcessingError::Config(format!(
+                "IDW power must be positive, got {}",
+                power
+            )));
+        }
+        self.power = power;
+        Ok(self)
+    }
+
+    pub fn with_max_distance_km(mut self, max_distance_km: f64) -> Self {
+        self.max_distance_km = max_distance_km;
+        self
+    }
+
+    /// Nearest reporting stations weighted into each cell
+    pub fn with_max_stations(mut self, max_stations: usize) -> Self {
+        self.max_stations = max_stations.max(1);
+        self
+    }
+
+    /// Reporting stations a cell needs before it gets a value
+    pub fn with_min_stations(mut self, min_stations: usize) -> Self {
+        self.min_stations = min_stations.max(1);
+        self
+    }
+
+    /// Adjust temperatures for elevation at `rate` °C per km, e.g. the
+    /// standard atmosphere's 6.5
+    pub fn with_lapse_rate(mut self, rate: f64, elevations: CellElevations) -> Result<Self> {
+        if elevations.0.len() != self.grid.cell_count() {
+            return Err(ProcessingError::Config(
+                "Cell elevations were loaded for a different grid".to_string(),
+            ));
+        }
+        self.lapse_rate = Some((rate, elevations));
+        Ok(self)
+    }
+
+    /// Grid every day in the records, passing each day's values to `sink`
+    /// in date order
+    pub fn grid<F>(&self, records: &[WeatherRecord], mut sink: F) -> Result<GridSummary>
+    where
+        F: FnMut(&[GridValue]) -> Result<()>,
+    {
+        let mut index: HashMap<u32, usize> = HashMap::new();
+        let mut stations: Vec<GridStation> = Vec::new();
+        let mut days: BTreeMap<NaiveDate, Vec<&WeatherRecord>> = BTreeMap::new();
+        for record in records {
+            index.entry(record.station_id).or_insert_with(|| {
+                stations.push(GridStation {
+                    elevation: record.elevation.map(f64::from),
+                });
+                stations.len() - 1
+            });
+            days.entry(record.date).or_default().push(record);
+        }
+        let positions: HashMap<usize, (f64, f64)> = records
+            .iter()
+            .map(|r| (index[&r.station_id], (r.latitude, r.longitude)))
+            .collect();
+
+        // Stations within reach of each cell, nearest first
+        let reach: Vec<Vec<(usize, f64)>> = (0..self.grid.cell_count())
+            .map(|cell| {
+                let (lat, lon) = self.grid.centre(cell);
+                let mut near: Vec<(usize, f64)> = positions
+                    .iter()
+                    .map(|(&s, &(s_lat, s_lon))| (s, haversine_distance(lat, lon, s_lat, s_lon)))
+                    .filter(|(_, d)| *d <= self.max_distance_km)
+                    .collect();
+                near.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+                near
+            })
+            .collect();
+
+        let mut summary = GridSummary {
+            days: days.len(),
+            cells: self.grid.cell_count(),
+            stations: stations.len(),
+            values: 0,
+            cells_out_of_range: reach.iter().filter(|r| r.is_empty()).count(),
+        };
+
+        let mut values = vec![None; stations.len()];
+        let mut output = Vec::new();
+        for (date, day_records) in days {
+            output.clear();
+            for &metric in &self.metrics {
+                values.iter_mut().for_each(|v| *v = None);
+                for record in &day_records {
+                    values[index[&record.station_id]] = gridding_value(record, metric);
+                }
+
+                let lapse = self
+                    .lapse_rate
+                    .as_ref()
+                    .filter(|_| metric.starts_with("temp_"));
+                for (cell, near) in reach.iter().enumerate() {
+                    let cell_elevation = match lapse {
+                        Some((_, elevations)) => match elevations.get(cell) {
+                            Some(elevation) => Some(elevation),
+                            None => continue,
+                        },
+                        None => None,
+                    };
+
+                    let (mut weighted, mut weights, mut count) = (0.0, 0.0, 0);
+                    for &(station, distance) in near {
+                        let Some(value) = values[station] else {
+                            continue;
+                        };
+                        let value = match lapse {
+                            Some((rate, _)) => match stations[station].elevation {
+                                Some(elevation) => value + rate * elevation / 1000.0,
+                                None => continue,
+                            },
+                            None => value,
+                        };
+                        let weight = distance.max(MIN_DISTANCE_KM).powf(-self.power);
+                        weighted += weight * value;
+                        weights += weight;
+                        count += 1;
+                        if count == self.max_stations {
+                            break;
+                        }
+                    }
+                    if count < self.min_stations {
+                        continue;
+                    }
+
+                    let mut value = weighted / weights;
+                    if let (Some((rate, _)), Some(elevation)) = (lapse, cell_elevation) {
+                        value -= rate * elevation / 1000.0;
+                    }
+                    let (latitude, longitude) = self.grid.centre(cell);
+                    output.push(GridValue {
+                        date,
+                        latitude,
+                        longitude,
+                        metric,
+                        value,
+                        station_count: count as u32,
+                    });
+                }
+            }
+            summary.values += output.len();
+            sink(&output)?;
+        }
+
+        Ok(summary)
+    }
+}
+
+/// A metric's value if it is of Valid quality and was not infilled
+fn gridding_value(record: &WeatherRecord, metric: &str) -> Option<f64> {
+    let (value, quality) = match metric {
+        "temp_min" | "temp_max" | "temp_avg" => {
+            let (value, infilled) = match metric {
+                "temp_min" => (record.temp_min, record.temp_min_infilled),
+                "temp_max" => (record.temp_max, record.temp_max_infilled),
+                _ => (record.temp_avg, record.temp_avg_infilled),
+            };
+            (
+                value.filter(|_| infilled.is_none()),
+                record.assess_temperature_quality(),
+            )
+        }
+        "precipitation" => (
+            record
+                .precipitation
+                .filter(|_| record.precip_infilled.is_none()),
+            record.assess_precipitation_quality(),
+        ),
+        "wind_speed" => (record.wind_speed, record.assess_wind_quality()),
+        _ => record
+            .supplementary_elements()
+            .into_iter()
+            .find(|(m, ..)| m.column_name() == metric)
+            .map(|(_, value, quality, validation)| {
+                (
+                    value,
+                    WeatherRecord::assess_element_quality(quality, validation),
+                )
+            })?,
+    };
+    value
+        .filter(|_| quality == DataQuality::Valid)
+        .map(f64::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(station_id: u32, lat: f64, lon: f64, elevation: i32, tg: f32) -> WeatherRecord {
+        let mut record = WeatherRecord::new(
+            station_id,
+            format!("Station {}", station_id),
+            NaiveDate::from_ymd_opt(2020, 7, 1).unwrap(),
+            lat,
+            lon,
+            None,
+            None,
+            Some(tg),
+            Some(2.0),
+            None,
+            Some("0".to_string()),
+            Some("0".to_string()),
+            None,
+        );
+        record.elevation = Some(elevation);
+        record
+    }
+
+    fn grid_all(gridder: &Gridder, records: &[WeatherRecord]) -> (Vec<GridValue>, GridSummary) {
+        let mut values = Vec::new();
+        let summary = gridder
+            .grid(records, |day| {
+                values.extend_from_slice(day);
+                Ok(())
+            })
+            .unwrap();
+        (values, summary)
+    }
+
+    #[test]
+    fn test_grid_spec_cells() {
+        let bbox: BoundingBox = "50,-2,52,1".parse().unwrap();
+        let grid = GridSpec::from_bbox(&bbox, 0.5).unwrap();
+        assert_eq!((grid.rows(), grid.cols()), (4, 6));
+        assert_eq!(grid.centre(0), (50.25, -1.75));
+        assert_eq!(grid.cell_at(51.6, 0.9), Some(3 * 6 + 5));
+        assert_eq!(grid.cell_at(52.0, 1.0), Some(23));
+        assert_eq!(grid.cell_at(49.9, 0.0), None);
+
+        let covering = GridSpec::covering(&[record(1, 51.2, -0.3, 0, 15.0)], 0.5).unwrap();
+        assert_eq!((covering.min_latitude, covering.max_latitude), (51.0, 51.5));
+        assert_eq!(covering.cell_count(), 1);
+
+        assert!(GridSpec::new(52.0, 50.0, -2.0, 1.0, 0.5).is_err());
+        assert!(GridSpec::from_bbox(&bbox, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_idw_and_lapse_rate() {
+        // Two cells 0.1° apart with stations at their centres and one
+        // upland station between them
+        let grid = GridSpec::new(51.0, 51.1, 0.0, 0.2, 0.1).unwrap();
+        let records = vec![
+            record(1, 51.05, 0.05, 0, 16.0),
+            record(2, 51.05, 0.15, 1000, 9.5),
+        ];
+
+        let gridder = Gridder::new(grid).with_max_distance_km(20.0);
+        let (values, summary) = grid_all(&gridder, &records);
+        assert_eq!(summary.values, 2);
+        assert_eq!(values[0].station_count, 2);
+        // A station at the centre dominates its cell
+        assert!((values[0].value - 16.0).abs() < 0.01);
+        assert!((values[1].value - 9.5).abs() < 0.01);
+
+        let gridder = Gridder::new(grid).with_max_stations(1);
+        let (values, _) = grid_all(&gridder, &records);
+        assert!(values.iter().all(|v| v.station_count == 1));
+
+        // Both stations are 16°C at sea level; a lowland cell gets 16°C
+        // whichever station it is weighted towards
+        let elevations =
+            CellElevations::from_points(&grid, [(51.05, 0.05, 0.0), (51.05, 0.15, 500.0)]);
+        let gridder = Gridder::new(grid)
+            .with_metrics(Gridder::parse_metrics("temp_avg, precipitation").unwrap())
+            .unwrap()
+            .with_power(1.0)
+            .unwrap()
+            .with_lapse_rate(6.5, elevations)
+            .unwrap();
+        let (values, _) = grid_all(&gridder, &records);
+        let temps: Vec<f64> = values
+            .iter()
+            .filter(|v| v.metric == "temp_avg")
+            .map(|v| v.value)
+            .collect();
+        assert!((temps[0] - 16.0).abs() < 1e-9);
+        assert!((temps[1] - 12.75).abs() < 1e-9);
+        assert!(values
+            .iter()
+            .filter(|v| v.metric == "precipitation")
+            .all(|v| (v.value - 2.0).abs() < 1e-9));
+
+        assert!(Gridder::parse_metrics("temp_avg,wind_direction").is_err());
+        assert!(Gridder::new(grid).with_metrics(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_single_station() {
+        // A 2 x 2 grid of 0.5° cells with one station at the centre of the
+        // south-west cell; the south-east centre is 35 km away, the northern
+        // ones over 55 km
+        let grid = GridSpec::new(51.0, 52.0, 0.0, 1.0, 0.5).unwrap();
+        let records = vec![record(1, 51.25, 0.25, 0, 14.0)];
+
+        let gridder = Gridder::new(grid).with_max_distance_km(40.0);
+        let (values, summary) = grid_all(&gridder, &records);
+        assert_eq!(summary.stations, 1);
+        assert_eq!(summary.cells_out_of_range, 2);
+        assert_eq!(values.len(), 2);
+        // Every cell in reach takes the station's value, including the one
+        // it sits at the centre of
+        for value in &values {
+            assert_eq!(value.station_count, 1);
+            assert!((value.value - 14.0).abs() < 1e-9);
+        }
+
+        // A cell needing two stations gets no value at all
+        let gridder = Gridder::new(grid)
+            .with_max_distance_km(40.0)
+            .with_min_stations(2);
+        let (values, summary) = grid_all(&gridder, &records);
+        assert!(values.is_empty());
+        assert_eq!(summary.values, 0);
+        assert_eq!(summary.days, 1);
+    }
+}
@@ -0,0 +1,674 @@
+use crate::analyzers::{IndexPeriod, MinimumQuality, PeriodAggregate, TemporalAggregator};
+use crate::error::{ProcessingError, Result};
+use crate::models::{SourceMetadata, WeatherRecord};
+use chrono::{Duration, NaiveDate};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+/// Significance level the critical values correspond to
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// 95% critical values of the SNHT T0 statistic by series length
+/// (Khaliq & Ouarda, 2007)
+const SNHT_CRITICAL: [(usize, f64); 9] = [
+    (10, 5.70),
+    (20, 6.95),
+    (30, 7.65),
+    (40, 8.10),
+    (50, 8.45),
+    (70, 8.80),
+    (100, 9.15),
+    (150, 9.55),
+    (250, 10.00),
+];
+
+/// 95% critical values of the Buishand R/√n statistic by series length
+/// (Buishand, 1982)
+const BUISHAND_CRITICAL: [(usize, f64); 7] = [
+    (10, 1.28),
+    (20, 1.43),
+    (30, 1.50),
+    (40, 1.53),
+    (50, 1.55),
+    (100, 1.62),
+    (1000, 1.75),
+];
+
+/// Elements tested, with the ECA&D element prefix used in sources.txt
+const ELEMENTS: [(&str, &str); 4] = [
+    ("temp_min", "TN"),
+    ("temp_max", "TX"),
+    ("temp_avg", "TG"),
+    ("precipitation", "RR"),
+];
+
+/// Start date, aggregate and value of one period in a station's series
+type SeriesPoint<'a> = (NaiveDate, &'a PeriodAggregate, f64);
+
+/// Tests for a single shift in the mean of a series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HomogeneityTest {
+    /// Standard Normal Homogeneity Test (Alexandersson, 1986)
+    Snht,
+    /// Pettitt's non-parametric rank test
+    Pettitt,
+    /// Buishand range test
+    Buishand,
+}
+
+impl HomogeneityTest {
+    pub fn all() -> [HomogeneityTest; 3] {
+        [
+            HomogeneityTest::Snht,
+            HomogeneityTest::Pettitt,
+            HomogeneityTest::Buishand,
+        ]
+    }
+
+    /// Parse a comma-separated list of test names
+    pub fn parse_list(s: &str) -> Result<Vec<HomogeneityTest>> {
+        let mut tests = s
+            .split(',')
+            .filter(|t| !t.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<HomogeneityTest>>>()?;
+        tests.sort();
+        tests.dedup();
+        if tests.is_empty() {
+            return Err(ProcessingError::Config(
+                "At least one homogeneity test is required".to_string(),
+            ));
+        }
+        Ok(tests)
+    }
+
+    fn run(&self, series: &[f64]) -> Option<TestResult> {
+        match self {
+            HomogeneityTest::Snht => snht(series),
+            HomogeneityTest::Pettitt => pettitt(series),
+            HomogeneityTest::Buishand => buishand(series),
+        }
+    }
+}
+
+impl FromStr for HomogeneityTest {
+    type Err = ProcessingError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "snht" => Ok(HomogeneityTest::Snht),
+            "pettitt" => Ok(HomogeneityTest::Pettitt),
+            "buishand" | "buishand-range" => Ok(HomogeneityTest::Buishand),
+            other => Err(ProcessingError::Config(format!(
+                "Unsupported homogeneity test: {} (expected snht, pettitt or buishand)",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for HomogeneityTest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HomogeneityTest::Snht => write!(f, "snht"),
+            HomogeneityTest::Pettitt => write!(f, "pettitt"),
+            HomogeneityTest::Buishand => write!(f, "buishand"),
+        }
+    }
+}
+
+/// Most likely break found by one test in one station's series
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breakpoint {
+    pub station_id: u32,
+    pub station_name: String,
+    pub element: &'static str,
+    pub test: HomogeneityTest,
+    pub period: IndexPeriod,
+    pub series_length: u32,
+    /// First period after the break
+    pub break_year: i32,
+    pub break_period: String,
+    pub break_date: NaiveDate,
+    pub statistic: f64,
+    /// Value the statistic must exceed at the 5% level
+    pub critical_value: f64,
+    /// Approximate p-value, where the test has one (Pettitt)
+    pub p_value: Option<f64>,
+    pub significant: bool,
+    /// Series means either side of the break. Monthly and seasonal series
+    /// are anomalies from each calendar period's mean.
+    pub mean_before: f64,
+    pub mean_after: f64,
+    /// Nearest change of source series in the records' `*_souid` column
+    pub souid_change_date: Option<NaiveDate>,
+    pub souid_before: Option<u32>,
+    pub souid_after: Option<u32>,
+    /// Nearest start or end of a source series in sources.txt
+    pub source_change_date: Option<NaiveDate>,
+}
+
+impl Breakpoint {
+    pub fn shift(&self) -> f64 {
+        self.mean_after - self.mean_before
+    }
+}
+
+/// Outcome of a test: the statistic and the index of the first value after
+/// the most likely break
+#[derive(Debug, Clone, Copy)]
+struct TestResult {
+    statistic: f64,
+    critical_value: f64,
+    p_value: Option<f64>,
+    split: usize,
+}
+
+/// A change of source series within a station's daily records
+#[derive(Debug, Clone, Copy)]
+struct SouidChange {
+    date: NaiveDate,
+    before: u32,
+    after: u32,
+}
+
+/// Runs homogeneity tests on annual, seasonal or monthly series per station
+/// and links detected breaks to source series changes
+pub struct BreakpointDetector {
+    period: IndexPeriod,
+    tests: Vec<HomogeneityTest>,
+    min_length: usize,
+    link_window_days: i64,
+    sources: Vec<SourceMetadata>,
+}
+
+impl BreakpointDetector {
+    pub fn new(period: IndexPeriod) -> Self {
+        Self {
+            period,
+            tests: HomogeneityTest::all().to_vec(),
+            min_length: 10,
+            link_window_days: 365,
+            sources: Vec::new(),
+        }
+    }
+
+    pub fn with_tests(mut self, tests: Vec<HomogeneityTest>) -> Self {
+        self.tests = tests;
+        self
+    }
+
+    /// Complete periods a series needs before it is tested
+    pub fn with_min_length(mut self, min_length: usize) -> Result<Self> {
+        if min_length < 10 {
+            return Err(ProcessingError::Config(format!(
+                "Homogeneity series need at least 10 values, got {}",
+                min_length
+            )));
+        }
+        self.min_length = min_length;
+        Ok(self)
+    }
+
+    /// How far from a break a source change may be and still be linked to it
+    pub fn with_link_window_days(mut self, days: i64) -> Self {
+        self.link_window_days = days;
+        self
+    }
+
+    /// Source series metadata (sources.txt) to link breaks to
+    pub fn with_sources(mut self, sources: Vec<SourceMetadata>) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    /// Test every station and element with a long enough series, returning
+    /// one breakpoint per test in station and element order.
+    ///
+    /// Only valid values count, and periods missing more days than the
+    /// ETCCDI rule allows are left out of the series.
+    pub fn detect(&self, records: &[WeatherRecord]) -> Vec<Breakpoint> {
+        let aggregates = TemporalAggregator::new(self.period)
+            .with_min_quality(MinimumQuality::Valid)
+            .aggregate(records);
+        let souid_changes = souid_changes(records);
+
+        let mut series: BTreeMap<(u32, usize), Vec<SeriesPoint>> = BTreeMap::new();
+        for aggregate in &aggregates {
+            let Some((start, _)) = self.period.date_range(aggregate.year, &aggregate.period) else {
+                continue;
+            };
+            for (element, _) in ELEMENTS.iter().enumerate() {
+                let (value, days) = element_value(aggregate, element);
+                let complete = aggregate.days_in_period.saturating_sub(days) as usize
+                    <= self.period.max_missing_days();
+                if let Some(value) = value.filter(|_| complete) {
+                    series
+                        .entry((aggregate.station_id, element))
+                        .or_default()
+                        .push((start, aggregate, value as f64));
+                }
+            }
+        }
+
+        let mut breakpoints = Vec::new();
+        for ((station_id, element), mut points) in series {
+            if points.len() < self.min_length {
+                continue;
+            }
+            points.sort_by_key(|(start, _, _)| *start);
+            let values = self.deseasonalise(&points);
+            let (element_name, prefix) = ELEMENTS[element];
+
+            for test in &self.tests {
+                let Some(result) = test.run(&values) else {
+                    continue;
+                };
+                let (break_date, first_after, _) = points[result.split];
+                let souid_change = souid_changes
+                    .get(&(station_id, element))
+                    .and_then(|changes| self.nearest(changes, break_date, |c| c.date))
+                    .copied();
+
+                breakpoints.push(Breakpoint {
+                    station_id,
+                    station_name: first_after.station_name.clone(),
+                    element: element_name,
+                    test: *test,
+                    period: self.period,
+                    series_length: values.len() as u32,
+                    break_year: first_after.year,
+                    break_period: first_after.period.clone(),
+                    break_date,
+                    statistic: result.statistic,
+                    critical_value: result.critical_value,
+                    p_value: result.p_value,
+                    significant: result.statistic > result.critical_value,
+                    mean_before: mean(&values[..result.split]),
+                    mean_after: mean(&values[result.split..]),
+                    souid_change_date: souid_change.map(|c| c.date),
+                    souid_before: souid_change.map(|c| c.before),
+                    souid_after: souid_change.map(|c| c.after),
+                    source_change_date: self.source_change(station_id, prefix, break_date),
+                });
+            }
+        }
+
+        breakpoints
+    }
+
+    /// Subtract each calendar period's mean so the seasonal cycle does not
+    /// mask a shift. Annual series have a single label, so only the overall
+    /// mean is removed.
+    fn deseasonalise(&self, points: &[SeriesPoint]) -> Vec<f64> {
+        let mut totals: HashMap<&str, (f64, u32)> = HashMap::new();
+        for (_, aggregate, value) in points {
+            let entry = totals.entry(aggregate.period.as_str()).or_default();
+            entry.0 += value;
+            entry.1 += 1;
+        }
+        points
+            .iter()
+            .map(|(_, aggregate, value)| {
+                let (sum, count) = totals[aggregate.period.as_str()];
+                value - sum / count as f64
+            })
+            .collect()
+    }
+
+    fn nearest<'a, T>(
+        &self,
+        candidates: &'a [T],
+        date: NaiveDate,
+        date_of: impl Fn(&T) -> NaiveDate,
+    ) -> Option<&'a T> {
+        candidates
+            .iter()
+            .map(|c| ((date_of(c) - date).num_days().abs(), c))
+            .filter(|(distance, _)| *distance <= self.link_window_days)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, c)| c)
+    }
+
+    /// Nearest date a source series for the element starts or ends, ignoring
+    /// the start and end of the station's whole record
+    fn source_change(&self, station_id: u32, prefix: &str, date: NaiveDate) -> Option<NaiveDate> {
+        let sources: Vec<&SourceMetadata> = self
+            .sources
+            .iter()
+            .filter(|s| s.staid == station_id && s.element_id.starts_with(prefix))
+            .collect();
+        let first = sources.iter().map(|s| s.start_date).min()?;
+        let last = sources.iter().map(|s| s.end_date).max()?;
+
+        let boundaries: Vec<NaiveDate> = sources
+            .iter()
+            .flat_map(|s| {
+                [
+                    (s.start_date != first).then_some(s.start_date),
+                    (s.end_date != last).then(|| s.end_date + Duration::days(1)),
+                ]
+            })
+            .flatten()
+            .collect();
+        self.nearest(&boundaries, date, |d| *d).copied()
+    }
+}
+
+/// Element value and valid day count of an aggregate, by `ELEMENTS` index
+fn element_value(aggregate: &PeriodAggregate, element: usize) -> (Option<f32>, u32) {
+    match element {
+        0 => (aggregate.temp_min_mean, aggregate.temp_min_days),
+        1 => (aggregate.temp_max_mean, aggregate.temp_max_days),
+        2 => (aggregate.temp_avg_mean, aggregate.temp_avg_days),
+        _ => (aggregate.precipitation_total, aggregate.precipitation_days),
+    }
+}
+
+/// Dates each station's source series changes, by station and `ELEMENTS` index
+fn souid_changes(records: &[WeatherRecord]) -> HashMap<(u32, usize), Vec<SouidChange>> {
+    let mut sorted: Vec<&WeatherRecord> = records.iter().collect();
+    sorted.sort_by_key(|r| (r.station_id, r.date));
+
+    let mut changes: HashMap<(u32, usize), Vec<SouidChange>> = HashMap::new();
+    let mut current: HashMap<(u32, usize), u32> = HashMap::new();
+    for record in sorted {
+        let souids = [
+            record.temp_min_souid,
+            record.temp_max_souid,
+            record.temp_avg_souid,
+            record.precip_souid,
+        ];
+        for (element, souid) in souids.into_iter().enumerate() {
+            let Some(souid) = souid else { continue };
+            let key = (record.station_id, element);
+            if let Some(before) = current.insert(key, souid).filter(|b| *b != souid) {
+                changes.entry(key).or_default().push(SouidChange {
+                    date: record.date,
+                    before,
+                    after: souid,
+                });
+            }
+        }
+    }
+    changes
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Linear interpolation in a critical value table, clamped at both ends
+fn critical_value(table: &[(usize, f64)], n: usize) -> f64 {
+    let (first_n, first_value) = table[0];
+    if n <= first_n {
+        return first_value;
+    }
+    for pair in table.windows(2) {
+        let ((n0, v0), (n1, v1)) = (pair[0], pair[1]);
+        if n <= n1 {
+            return v0 + (v1 - v0) * (n - n0) as f64 / (n1 - n0) as f64;
+        }
+    }
+    table[table.len() - 1].1
+}
+
+/// Standard Normal Homogeneity Test: T(k) = k·z̄₁² + (n−k)·z̄₂² on the
+/// standardised series, maximised over k
+fn snht(series: &[f64]) -> Option<TestResult> {
+    let n = series.len();
+    if n < 2 {
+        return None;
+    }
+    let mean = mean(series);
+    let sd = (series.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt();
+    if sd == 0.0 {
+        return None;
+    }
+
+    let z: Vec<f64> = series.iter().map(|x| (x - mean) / sd).collect();
+    let total: f64 = z.iter().sum();
+    let mut before = 0.0;
+    let mut best: Option<(f64, usize)> = None;
+    for k in 1..n {
+        before += z[k - 1];
+        let (k_f, rest) = (k as f64, (n - k) as f64);
+        let t = k_f * (before / k_f).powi(2) + rest * ((total - before) / rest).powi(2);
+        if best.map_or(true, |(max, _)| t > max) {
+            best = Some((t, k));
+        }
+    }
+
+    best.map(|(statistic, split)| TestResult {
+        statistic,
+        critical_value: critical_value(&SNHT_CRITICAL, n),
+        p_value: None,
+        split,
+    })
+}
+
+/// Pettitt's test: K = max |U_t| over the Mann-Whitney statistics of every
+/// split, with p ≈ 2·exp(−6K² / (n³ + n²))
+fn pettitt(series: &[f64]) -> Option<TestResult> {
+    let n = series.len();
+    if n < 2 {
+        return None;
+    }
+
+    // U_t = U_{t-1} + Σ_j sgn(x_t − x_j)
+    let mut u = 0i64;
+    let mut best: Option<(i64, usize)> = None;
+    for t in 0..n - 1 {
+        u += series
+            .iter()
+            .map(|x| match series[t].partial_cmp(x) {
+                Some(std::cmp::Ordering::Greater) => 1,
+                Some(std::cmp::Ordering::Less) => -1,
+                _ => 0,
+            })
+            .sum::<i64>();
+        if best.map_or(true, |(max, _)| u.abs() > max) {
+            best = Some((u.abs(), t + 1));
+        }
+    }
+
+    let (k, split) = best?;
+    if k == 0 {
+        return None;
+    }
+    let scale = (n as f64).powi(3) + (n as f64).powi(2);
+    let statistic = k as f64;
+    Some(TestResult {
+        statistic,
+        critical_value: (-(SIGNIFICANCE_LEVEL / 2.0).ln() * scale / 6.0).sqrt(),
+        p_value: Some((2.0 * (-6.0 * statistic.powi(2) / scale).exp()).min(1.0)),
+        split,
+    })
+}
+
+/// Buishand range test: the range of the cumulative deviations from the
+/// mean, rescaled by the standard deviation and √n
+fn buishand(series: &[f64]) -> Option<TestResult> {
+    let n = series.len();
+    if n < 2 {
+        return None;
+    }
+    let mean = mean(series);
+    let sd = (series.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n as f64).sqrt();
+    if sd == 0.0 {
+        return None;
+    }
+
+    let mut cumulative = 0.0;
+    let (mut max, mut min) = (0.0f64, 0.0f64);
+    let mut best: Option<(f64, usize)> = None;
+    for (k, x) in series.iter().enumerate().take(n - 1) {
+        cumulative += x - mean;
+        max = max.max(cumulative);
+        min = min.min(cumulative);
+        if best.map_or(true, |(largest, _)| cumulative.abs() > largest) {
+            best = Some((cumulative.abs(), k + 1));
+        }
+    }
+
+    let (_, split) = best?;
+    Some(TestResult {
+        statistic: (max - min) / sd / (n as f64).sqrt(),
+        critical_value: critical_value(&BUISHAND_CRITICAL, n),
+        p_value: None,
+        split,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    /// Daily records for 1981-2010 with a 2°C warm shift from 1996, when
+    /// the station moves from source series 100 to 101
+    fn shifted_station() -> Vec<WeatherRecord> {
+        NaiveDate::from_ymd_opt(1981, 1, 1)
+            .unwrap()
+            .iter_days()
+            .take_while(|d| d.year() <= 2010)
+            .map(|d| {
+                let shift = if d.year() >= 1996 { 2.0 } else { 0.0 };
+                // Small year-to-year variation so the series is not constant
+                let wobble = ((d.year() * 7) % 5) as f32 * 0.1;
+                let mut record = WeatherRecord::new(
+                    257,
+                    "Test Station".to_string(),
+                    d,
+                    52.4,
+                    -1.8,
+                    None,
+                    None,
+                    Some(10.0 + shift + wobble),
+                    Some(2.0 + wobble),
+                    None,
+                    Some("0".to_string()),
+                    Some("0".to_string()),
+                    None,
+                );
+                record.temp_avg_souid = Some(if d.year() >= 1996 { 101 } else { 100 });
+                record
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_annual_shift_detected_and_linked() {
+        let source = |souid, start: NaiveDate, end: NaiveDate| SourceMetadata {
+            staid: 257,
+            souid,
+            name: "Test Station".to_string(),
+            country: "GB".to_string(),
+            latitude: 52.4,
+            longitude: -1.8,
+            elevation: None,
+            element_id: "TG1".to_string(),
+            start_date: start,
+            end_date: end,
+            participant_id: None,
+            participant_name: String::new(),
+        };
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let sources = vec![
+            source(100, date(1981, 1, 1), date(1995, 12, 31)),
+            source(101, date(1996, 1, 1), date(2010, 12, 31)),
+        ];
+
+        let breakpoints = BreakpointDetector::new(IndexPeriod::Annual)
+            .with_sources(sources)
+            .detect(&shifted_station());
+
+        let tg: Vec<&Breakpoint> = breakpoints
+            .iter()
+            .filter(|b| b.element == "temp_avg")
+            .collect();
+        assert_eq!(tg.len(), 3);
+        for breakpoint in &tg {
+            assert!(
+                breakpoint.significant,
+                "{} missed the shift",
+                breakpoint.test
+            );
+            assert_eq!(breakpoint.break_year, 1996);
+            assert_eq!(breakpoint.series_length, 30);
+            assert!((breakpoint.shift() - 2.0).abs() < 0.3);
+            assert_eq!(breakpoint.souid_change_date, Some(date(1996, 1, 1)));
+            assert_eq!(breakpoint.souid_before, Some(100));
+            assert_eq!(breakpoint.souid_after, Some(101));
+            assert_eq!(breakpoint.source_change_date, Some(date(1996, 1, 1)));
+        }
+        let pettitt = tg
+            .iter()
+            .find(|b| b.test == HomogeneityTest::Pettitt)
+            .unwrap();
+        assert!(pettitt.p_value.unwrap() < SIGNIFICANCE_LEVEL);
+
+        // Precipitation has no shift
+        assert!(breakpoints
+            .iter()
+            .filter(|b| b.element == "precipitation")
+            .all(|b| !b.significant));
+    }
+
+    #[test]
+    fn test_statistics_and_options() {
+        // A homogeneous alternating series has no significant break
+        let flat: Vec<f64> = (0..40)
+            .map(|i| if i % 2 == 0 { 1.0 } else { -1.0 })
+            .collect();
+        for test in HomogeneityTest::all() {
+            let result = test.run(&flat).unwrap();
+            assert!(result.statistic < result.critical_value, "{}", test);
+        }
+
+        // A step in the middle is located exactly
+        let step: Vec<f64> = (0..20).map(|i| if i < 12 { 0.0 } else { 1.0 }).collect();
+        for test in HomogeneityTest::all() {
+            assert_eq!(test.run(&step).unwrap().split, 12, "{}", test);
+        }
+        assert!(snht(&[3.0; 20]).is_none());
+
+        assert!((critical_value(&SNHT_CRITICAL, 25) - 7.3).abs() < 1e-9);
+        assert_eq!(critical_value(&BUISHAND_CRITICAL, 5000), 1.75);
+
+        assert_eq!(
+            HomogeneityTest::parse_list("pettitt, snht,pettitt").unwrap(),
+            vec![HomogeneityTest::Snht, HomogeneityTest::Pettitt]
+        );
+        assert!(HomogeneityTest::parse_list("").is_err());
+        assert!(BreakpointDetector::new(IndexPeriod::Monthly)
+            .with_min_length(5)
+            .is_err());
+    }
+
+    #[test]
+    fn test_single_station_series_length_edges() {
+        let years = |count: i32| -> Vec<WeatherRecord> {
+            shifted_station()
+                .into_iter()
+                .filter(|r| r.date.year() < 1981 + count)
+                .collect()
+        };
+        let detector =
+            BreakpointDetector::new(IndexPeriod::Annual).with_tests(vec![HomogeneityTest::Snht]);
+
+        // Exactly the minimum ten years of TG and RR are tested, nine are not
+        let breakpoints = detector.detect(&years(10));
+        assert_eq!(breakpoints.len(), 2);
+        assert!(breakpoints.iter().all(|b| b.series_length == 10));
+        assert!(detector.detect(&years(9)).is_empty());
+
+        // The shortest series SNHT can split
+        assert!(snht(&[1.0]).is_none());
+        let result = snht(&[0.0, 1.0]).unwrap();
+        assert_eq!(result.split, 1);
+        assert!((result.statistic - 1.0).abs() < 1e-9);
+        assert!(result.statistic < result.critical_value);
+    }
+}
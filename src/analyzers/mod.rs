@@ -1,3 +1,13 @@
+pub mod aggregator;
+pub mod climate_indices;
+pub mod gridding;
+pub mod homogeneity;
+pub mod normals;
 pub mod weather_analyzer;
 
+pub use aggregator::{MinimumQuality, PeriodAggregate, TemporalAggregator};
+pub use climate_indices::{BasePeriod, ClimateIndexCalculator, IndexPeriod, IndexValue};
+pub use gridding::{CellElevations, GridSpec, GridSummary, GridValue, Gridder};
+pub use homogeneity::{Breakpoint, BreakpointDetector, HomogeneityTest};
+pub use normals::{Anomaly, ClimateNormal, NormalsCalculator, NormalsGranularity, NormalsTable};
 pub use weather_analyzer::WeatherAnalyzer;
@@ -0,0 +1,373 @@
+use crate::analyzers::climate_indices::calendar_day;
+use crate::analyzers::BasePeriod;
+use crate::error::{ProcessingError, Result};
+use crate::models::weather::DataQuality;
+use crate::models::WeatherRecord;
+use chrono::{Datelike, NaiveDate};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+/// WMO rule: a month with more missing days than this does not count
+/// towards a monthly normal
+const MAX_MISSING_DAYS_PER_MONTH: u32 = 10;
+
+/// Elements normals are computed for, with their units
+const ELEMENTS: [(&str, &str); 4] = [
+    ("temp_min", "°C"),
+    ("temp_max", "°C"),
+    ("temp_avg", "°C"),
+    ("precipitation", "mm/day"),
+];
+
+/// Calendar resolution of a normals table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NormalsGranularity {
+    #[default]
+    Month,
+    /// Day of a 365-day calendar, with 29 February folded onto the 28th
+    DayOfYear,
+}
+
+impl NormalsGranularity {
+    /// Month (1-12) or day of year (1-365) a date belongs to
+    pub fn key(&self, date: NaiveDate) -> u32 {
+        match self {
+            NormalsGranularity::Month => date.month(),
+            NormalsGranularity::DayOfYear => calendar_day(date) as u32 + 1,
+        }
+    }
+
+    /// Valid days a year needs in a period for it to count towards the normal
+    fn min_days(&self, year: i32, key: u32) -> u32 {
+        match self {
+            NormalsGranularity::Month => days_in_month(year, key)
+                .saturating_sub(MAX_MISSING_DAYS_PER_MONTH)
+                .max(1),
+            NormalsGranularity::DayOfYear => 1,
+        }
+    }
+}
+
+impl FromStr for NormalsGranularity {
+    type Err = ProcessingError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "month" | "monthly" => Ok(NormalsGranularity::Month),
+            "day-of-year" | "doy" | "daily" => Ok(NormalsGranularity::DayOfYear),
+            other => Err(ProcessingError::Config(format!(
+                "Unsupported normals granularity: {} (expected month or day-of-year)",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for NormalsGranularity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NormalsGranularity::Month => write!(f, "month"),
+            NormalsGranularity::DayOfYear => write!(f, "day-of-year"),
+        }
+    }
+}
+
+/// Normal of one element for a station and month or day of year
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClimateNormal {
+    pub station_id: u32,
+    pub station_name: String,
+    /// Month 1-12 or day of year 1-365, depending on the table granularity
+    pub period: u32,
+    pub element: &'static str,
+    /// None when fewer years than required have enough valid data
+    pub normal: Option<f64>,
+    /// Base-period years that contributed to the normal
+    pub years: u32,
+    pub units: &'static str,
+}
+
+/// Anomalies of one record against its station's normals
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Anomaly {
+    /// Mean temperature minus its normal, in °C
+    pub temp_avg_anomaly: Option<f32>,
+    /// Precipitation departure from its normal as a percentage of the normal
+    pub precip_anomaly_pct: Option<f32>,
+}
+
+/// Normals for every station, element and period of a dataset
+#[derive(Debug, Clone)]
+pub struct NormalsTable {
+    pub granularity: NormalsGranularity,
+    pub base_period: BasePeriod,
+    pub normals: Vec<ClimateNormal>,
+    lookup: HashMap<(u32, u32, &'static str), f64>,
+}
+
+impl NormalsTable {
+    fn new(
+        granularity: NormalsGranularity,
+        base_period: BasePeriod,
+        normals: Vec<ClimateNormal>,
+    ) -> Self {
+        let lookup = normals
+            .iter()
+            .filter_map(|n| {
+                n.normal
+                    .map(|value| ((n.station_id, n.period, n.element), value))
+            })
+            .collect();
+        Self {
+            granularity,
+            base_period,
+            normals,
+            lookup,
+        }
+    }
+
+    /// Normal of `element` for a station on a given date
+    pub fn normal(&self, station_id: u32, date: NaiveDate, element: &str) -> Option<f64> {
+        let key = self.granularity.key(date);
+        ELEMENTS
+            .iter()
+            .find(|(name, _)| *name == element)
+            .and_then(|(name, _)| self.lookup.get(&(station_id, key, *name)).copied())
+    }
+
+    pub fn anomaly(&self, record: &WeatherRecord) -> Anomaly {
+        let normal = |element| self.normal(record.station_id, record.date, element);
+
+        let temp_avg_anomaly = record
+            .temp_avg
+            .zip(normal("temp_avg"))
+            .map(|(value, normal)| (value as f64 - normal) as f32);
+        // A percentage of a dry normal is undefined
+        let precip_anomaly_pct = record
+            .precipitation
+            .zip(normal("precipitation").filter(|n| *n > 0.0))
+            .map(|(value, normal)| ((value as f64 - normal) / normal * 100.0) as f32);
+
+        Anomaly {
+            temp_avg_anomaly,
+            precip_anomaly_pct,
+        }
+    }
+
+    pub fn anomalies(&self, records: &[WeatherRecord]) -> Vec<Anomaly> {
+        records.iter().map(|r| self.anomaly(r)).collect()
+    }
+}
+
+type YearTotals = BTreeMap<i32, (f64, u32)>;
+
+/// Computes climatological normals from daily weather records
+pub struct NormalsCalculator {
+    base_period: BasePeriod,
+    granularity: NormalsGranularity,
+    min_years: Option<u32>,
+}
+
+impl NormalsCalculator {
+    pub fn new(base_period: BasePeriod) -> Self {
+        Self {
+            base_period,
+            granularity: NormalsGranularity::default(),
+            min_years: None,
+        }
+    }
+
+    pub fn with_granularity(mut self, granularity: NormalsGranularity) -> Self {
+        self.granularity = granularity;
+        self
+    }
+
+    /// Years of data a normal needs. Defaults to 80% of the base period,
+    /// e.g. 24 of 30 years.
+    pub fn with_min_years(mut self, min_years: u32) -> Self {
+        self.min_years = Some(min_years);
+        self
+    }
+
+    fn required_years(&self) -> u32 {
+        self.min_years
+            .unwrap_or_else(|| (self.base_period.years() as u32 * 4 + 4) / 5)
+    }
+
+    /// Compute normals from base-period records whose quality is Valid.
+    ///
+    /// Each year's mean for a period counts once, so normals are means of
+    /// yearly means rather than of all days.
+    pub fn compute(&self, records: &[WeatherRecord]) -> NormalsTable {
+        let mut totals: BTreeMap<(u32, u32, usize), YearTotals> = BTreeMap::new();
+        let mut names: BTreeMap<u32, &str> = BTreeMap::new();
+
+        for record in records.iter().filter(|r| self.base_period.contains(r.date)) {
+            names
+                .entry(record.station_id)
+                .or_insert(&record.station_name);
+            let key = self.granularity.key(record.date);
+
+            let temp_valid = record.assess_temperature_quality() == DataQuality::Valid;
+            let precip_valid = record.assess_precipitation_quality() == DataQuality::Valid;
+            let values = [
+                record.temp_min.filter(|_| temp_valid),
+                record.temp_max.filter(|_| temp_valid),
+                record.temp_avg.filter(|_| temp_valid),
+                record.precipitation.filter(|_| precip_valid),
+            ];
+
+            for (element, value) in values.into_iter().enumerate() {
+                let Some(value) = value else { continue };
+                let year = totals
+                    .entry((record.station_id, key, element))
+                    .or_default()
+                    .entry(record.date.year())
+                    .or_default();
+                year.0 += value as f64;
+                year.1 += 1;
+            }
+        }
+
+        let required_years = self.required_years();
+        let normals = totals
+            .into_iter()
+            .map(|((station_id, key, element), years)| {
+                let yearly_means: Vec<f64> = years
+                    .into_iter()
+                    .filter(|(year, (_, days))| *days >= self.granularity.min_days(*year, key))
+                    .map(|(_, (sum, days))| sum / days as f64)
+                    .collect();
+                let count = yearly_means.len() as u32;
+                let (name, units) = ELEMENTS[element];
+
+                ClimateNormal {
+                    station_id,
+                    station_name: names[&station_id].to_string(),
+                    period: key,
+                    element: name,
+                    normal: (count >= required_years.max(1))
+                        .then(|| yearly_means.iter().sum::<f64>() / count as f64),
+                    years: count,
+                    units,
+                }
+            })
+            .collect();
+
+        NormalsTable::new(self.granularity, self.base_period, normals)
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|next| NaiveDate::from_ymd_opt(year, month, 1).map(|first| next - first))
+        .map_or(0, |days| days.num_days() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(date: NaiveDate, tg: f32, rr: f32, temp_quality: &str) -> WeatherRecord {
+        WeatherRecord::new(
+            257,
+            "Test Station".to_string(),
+            date,
+            52.4,
+            -1.8,
+            None,
+            None,
+            Some(tg),
+            Some(rr),
+            None,
+            Some(temp_quality.to_string()),
+            Some("0".to_string()),
+            None,
+        )
+    }
+
+    /// Every day of 2001-2003 with mean temperature rising 1°C per year
+    fn three_years() -> Vec<WeatherRecord> {
+        NaiveDate::from_ymd_opt(2001, 1, 1)
+            .unwrap()
+            .iter_days()
+            .take_while(|d| d.year() <= 2003)
+            .map(|d| record(d, (d.year() - 2000) as f32, 2.0, "0"))
+            .collect()
+    }
+
+    #[test]
+    fn test_monthly_normals_and_anomalies() {
+        let records = three_years();
+        let table = NormalsCalculator::new(BasePeriod::new(2001, 2003).unwrap()).compute(&records);
+
+        let january = |element| {
+            table
+                .normals
+                .iter()
+                .find(|n| n.period == 1 && n.element == element)
+                .unwrap()
+        };
+        assert_eq!(january("temp_avg").normal, Some(2.0));
+        assert_eq!(january("temp_avg").years, 3);
+        assert_eq!(january("precipitation").normal, Some(2.0));
+        assert!(table.normals.iter().all(|n| n.element != "temp_min"));
+
+        let anomaly = table.anomaly(&record(
+            NaiveDate::from_ymd_opt(2010, 1, 5).unwrap(),
+            5.5,
+            3.0,
+            "0",
+        ));
+        assert_eq!(anomaly.temp_avg_anomaly, Some(3.5));
+        assert_eq!(anomaly.precip_anomaly_pct, Some(50.0));
+
+        // Three years fall short of the 80% rule for a 1991-2020 base period
+        let mut shifted = records.clone();
+        for r in &mut shifted {
+            r.date = r.date.with_year(r.date.year() - 10).unwrap();
+        }
+        let table = NormalsCalculator::new(BasePeriod::new(1991, 2020).unwrap()).compute(&shifted);
+        assert!(table.normals.iter().all(|n| n.normal.is_none()));
+        let table = NormalsCalculator::new(BasePeriod::new(1991, 2020).unwrap())
+            .with_min_years(3)
+            .compute(&shifted);
+        assert!(table.normals.iter().all(|n| n.normal.is_some()));
+    }
+
+    #[test]
+    fn test_only_valid_complete_years_count() {
+        let mut records = three_years();
+        // Flag all of 2003 as suspect and drop most of January 2002
+        for r in records.iter_mut().filter(|r| r.date.year() == 2003) {
+            r.temp_quality = Some("1".to_string());
+        }
+        records.retain(|r| !(r.date.year() == 2002 && r.date.month() == 1 && r.date.day() > 5));
+
+        let table = NormalsCalculator::new(BasePeriod::new(2001, 2003).unwrap())
+            .with_granularity(NormalsGranularity::DayOfYear)
+            .with_min_years(1)
+            .compute(&records);
+        let jan_1 = NaiveDate::from_ymd_opt(2001, 1, 1).unwrap();
+        assert_eq!(table.normal(257, jan_1, "temp_avg"), Some(1.5));
+        let jan_20 = NaiveDate::from_ymd_opt(2001, 1, 20).unwrap();
+        assert_eq!(table.normal(257, jan_20, "temp_avg"), Some(1.0));
+
+        let table = NormalsCalculator::new(BasePeriod::new(2001, 2003).unwrap())
+            .with_min_years(1)
+            .compute(&records);
+        assert_eq!(table.normal(257, jan_1, "temp_avg"), Some(1.0));
+
+        assert_eq!(
+            "doy".parse::<NormalsGranularity>().unwrap(),
+            NormalsGranularity::DayOfYear
+        );
+        assert!("weekly".parse::<NormalsGranularity>().is_err());
+    }
+}
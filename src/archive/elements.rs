@@ -0,0 +1,219 @@
+use crate::archive::{TemperatureType, WeatherMetric};
+use crate::error::{ProcessingError, Result};
+
+/// Value ECA&D writes for a missing observation, in every element file
+pub const ECAD_MISSING_VALUE: i32 = -9999;
+
+/// How an ECA&D element is stored in the source files and in our output.
+///
+/// Source files hold integers in `raw_unit`; multiplying by `scale_factor`
+/// gives the value in `unit`, which is what WeatherRecord and Parquet hold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementSpec {
+    pub metric: WeatherMetric,
+    /// File name prefix, e.g. `TG` in `TG_STAID000257.txt`
+    pub prefix: &'static str,
+    /// Parquet value column
+    pub column: &'static str,
+    pub display_name: &'static str,
+    pub raw_unit: &'static str,
+    pub scale_factor: f64,
+    pub unit: &'static str,
+    pub missing: i32,
+}
+
+impl ElementSpec {
+    /// Convert a raw value field to output units, or None if it is the
+    /// missing sentinel
+    pub fn to_physical(&self, raw: &str) -> Result<Option<f32>> {
+        let raw = raw.trim();
+        let value: i32 = raw.parse().map_err(|_| {
+            ProcessingError::InvalidFormat(format!("Invalid {} value: '{}'", self.prefix, raw))
+        })?;
+        if value == self.missing {
+            return Ok(None);
+        }
+        Ok(Some((value as f64 * self.scale_factor) as f32))
+    }
+
+    pub fn from_prefix(prefix: &str) -> Option<&'static ElementSpec> {
+        ELEMENTS.iter().find(|e| e.prefix == prefix)
+    }
+
+    pub fn from_column(column: &str) -> Option<&'static ElementSpec> {
+        ELEMENTS.iter().find(|e| e.column == column)
+    }
+}
+
+const fn element(
+    metric: WeatherMetric,
+    prefix: &'static str,
+    column: &'static str,
+    display_name: &'static str,
+    raw_unit: &'static str,
+    scale_factor: f64,
+    unit: &'static str,
+) -> ElementSpec {
+    ElementSpec {
+        metric,
+        prefix,
+        column,
+        display_name,
+        raw_unit,
+        scale_factor,
+        unit,
+        missing: ECAD_MISSING_VALUE,
+    }
+}
+
+/// Every ECA&D element the processor reads, in output column order
+pub static ELEMENTS: [ElementSpec; 13] = [
+    element(
+        WeatherMetric::Temperature(TemperatureType::Minimum),
+        "TN",
+        "temp_min",
+        "Temperature (Min)",
+        "0.1 °C",
+        0.1,
+        "°C",
+    ),
+    element(
+        WeatherMetric::Temperature(TemperatureType::Maximum),
+        "TX",
+        "temp_max",
+        "Temperature (Max)",
+        "0.1 °C",
+        0.1,
+        "°C",
+    ),
+    element(
+        WeatherMetric::Temperature(TemperatureType::Average),
+        "TG",
+        "temp_avg",
+        "Temperature (Avg)",
+        "0.1 °C",
+        0.1,
+        "°C",
+    ),
+    element(
+        WeatherMetric::Precipitation,
+        "RR",
+        "precipitation",
+        "Precipitation",
+        "0.1 mm",
+        0.1,
+        "mm",
+    ),
+    element(
+        WeatherMetric::WindSpeed,
+        "FG",
+        "wind_speed",
+        "Wind Speed",
+        "0.1 m/s",
+        0.1,
+        "m/s",
+    ),
+    element(
+        WeatherMetric::Sunshine,
+        "SS",
+        "sunshine",
+        "Sunshine",
+        "0.1 h",
+        0.1,
+        "h",
+    ),
+    element(
+        WeatherMetric::Humidity,
+        "HU",
+        "humidity",
+        "Humidity",
+        "%",
+        1.0,
+        "%",
+    ),
+    element(
+        WeatherMetric::Pressure,
+        "PP",
+        "pressure",
+        "Pressure (MSL)",
+        "0.1 hPa",
+        0.1,
+        "hPa",
+    ),
+    element(
+        WeatherMetric::CloudCover,
+        "CC",
+        "cloud_cover",
+        "Cloud Cover",
+        "oktas",
+        1.0,
+        "oktas",
+    ),
+    element(
+        WeatherMetric::SnowDepth,
+        "SD",
+        "snow_depth",
+        "Snow Depth",
+        "cm",
+        1.0,
+        "cm",
+    ),
+    element(
+        WeatherMetric::WindGust,
+        "FX",
+        "wind_gust",
+        "Wind Gust",
+        "0.1 m/s",
+        0.1,
+        "m/s",
+    ),
+    element(
+        WeatherMetric::WindDirection,
+        "DD",
+        "wind_direction",
+        "Wind Direction",
+        "degrees",
+        1.0,
+        "degrees",
+    ),
+    element(
+        WeatherMetric::GlobalRadiation,
+        "QQ",
+        "global_radiation",
+        "Global Radiation",
+        "W/m²",
+        1.0,
+        "W/m²",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_covers_every_metric() {
+        for spec in &ELEMENTS {
+            assert_eq!(spec.metric.spec(), spec);
+            assert_eq!(ElementSpec::from_prefix(spec.prefix), Some(spec));
+            assert_eq!(ElementSpec::from_column(spec.column), Some(spec));
+            assert_eq!(spec.missing, ECAD_MISSING_VALUE);
+        }
+        assert_eq!(ElementSpec::from_prefix("XX"), None);
+    }
+
+    #[test]
+    fn test_raw_values_converted_to_output_units() {
+        let tg = ElementSpec::from_prefix("TG").unwrap();
+        assert_eq!(tg.to_physical(" 125").unwrap(), Some(12.5));
+        assert_eq!(tg.to_physical("-3").unwrap(), Some(-0.3));
+        assert_eq!(tg.to_physical("-9999").unwrap(), None);
+        assert!(tg.to_physical("12.5").is_err());
+
+        let pp = ElementSpec::from_prefix("PP").unwrap();
+        assert_eq!(pp.to_physical("10132").unwrap(), Some(1013.2));
+        let hu = ElementSpec::from_prefix("HU").unwrap();
+        assert_eq!(hu.to_physical("87").unwrap(), Some(87.0));
+        assert_eq!((hu.raw_unit, hu.unit), ("%", "%"));
+    }
+}
@@ -1,11 +1,13 @@
 use crate::archive::{TemperatureType, WeatherMetric};
 use crate::error::{ProcessingError, Result};
+use crate::processors::{RunWarning, WarningCode};
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
+use tracing::{debug, instrument, warn};
 use zip::ZipArchive;
 
 type ScanResult = (
@@ -22,6 +24,9 @@ pub struct ArchiveMetadata {
     pub date_range: Option<(NaiveDate, NaiveDate)>,
     pub file_counts: HashMap<WeatherMetric, usize>,
     pub total_files: usize,
+    /// Warnings raised while inspecting; reported separately
+    #[serde(skip)]
+    pub warnings: Vec<RunWarning>,
 }
 
 impl ArchiveMetadata {
@@ -83,6 +88,7 @@ impl ArchiveMetadata {
 pub struct ArchiveInspector;
 
 impl ArchiveInspector {
+    #[instrument(skip_all, fields(path = %zip_path.display()))]
     pub fn inspect_zip(zip_path: &Path) -> Result<ArchiveMetadata> {
         let file = File::open(zip_path)?;
         let mut archive = ZipArchive::new(file)?;
@@ -97,17 +103,18 @@ impl ArchiveInspector {
         }
 
         // Step 2: Extract country from stations.txt
-        let country = Self::extract_country(&mut archive)?;
+        let mut warnings = Vec::new();
+        let country = Self::extract_country(&mut archive, &mut warnings)?;
 
         // Step 3: Validate metrics against elements.txt (optional)
         if let Ok(element_metrics) = Self::validate_with_elements(&mut archive) {
             // Cross-validate if elements.txt is available
             for metric in &metrics {
                 if !element_metrics.contains(metric) {
-                    println!(
-                        "Warning: Metric {} found in files but not in elements.txt",
-                        metric
-                    );
+                    let message =
+                        format!("Metric {} found in files but not in elements.txt", metric);
+                    warn!(metric = %metric, "Metric found in files but not in elements.txt");
+                    warnings.push(RunWarning::new(WarningCode::MetricNotInElements, message));
                 }
             }
         }
@@ -115,6 +122,19 @@ impl ArchiveInspector {
         // Step 4: Estimate date range (optional - requires parsing data files)
         let date_range = Self::estimate_date_range(&mut archive, &metrics).ok();
 
+        debug!(
+            country = %country,
+            metrics = metrics.len(),
+            stations = all_station_ids.len(),
+            "Inspected archive metadata"
+        );
+
+        let archive_path = zip_path.display().to_string();
+        let warnings = warnings
+            .into_iter()
+            .map(|w| w.with_path(archive_path.clone()))
+            .collect();
+
         Ok(ArchiveMetadata {
             country,
             metrics,
@@ -122,6 +142,7 @@ impl ArchiveInspector {
             date_range,
             file_counts,
             total_files: archive.len(),
+            warnings,
         })
     }
 
@@ -197,7 +218,10 @@ impl ArchiveInspector {
         }
     }
 
-    fn extract_country(archive: &mut ZipArchive<File>) -> Result<String> {
+    fn extract_country(
+        archive: &mut ZipArchive<File>,
+        warnings: &mut Vec<RunWarning>,
+    ) -> Result<String> {
         // Extract stations.txt to read country codes
         let mut stations_file = archive.by_name("stations.txt").map_err(|_| {
             ProcessingError::InvalidFormat("stations.txt not found in archive".to_string())
@@ -238,7 +262,9 @@ impl ArchiveInspector {
         }
 
         if countries.len() > 1 {
-            println!("Warning: Multiple countries found: {:?}", countries);
+            let message = format!("Multiple countries found: {:?}", countries);
+            warn!(countries = ?countries, "Multiple countries found in stations.txt");
+            warnings.push(RunWarning::new(WarningCode::MultipleCountries, message));
         }
 
         // Return the first (or most common) country
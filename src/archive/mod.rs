@@ -1,11 +1,13 @@
+pub mod elements;
 pub mod inspector;
 pub mod multi_processor;
 pub mod processor;
 pub mod temp_manager;
 
+pub use elements::{ElementSpec, ECAD_MISSING_VALUE, ELEMENTS};
 pub use inspector::{ArchiveInspector, ArchiveMetadata};
 pub use multi_processor::{ArchiveInfo, MultiArchiveProcessor};
-pub use processor::ArchiveProcessor;
+pub use processor::{ArchiveIndex, ArchiveProcessor};
 pub use temp_manager::TempFileManager;
 
 use serde::{Deserialize, Serialize};
@@ -15,6 +17,14 @@ pub enum WeatherMetric {
     Temperature(TemperatureType),
     Precipitation,
     WindSpeed,
+    Sunshine,        // SS
+    Humidity,        // HU
+    Pressure,        // PP
+    CloudCover,      // CC
+    SnowDepth,       // SD
+    WindGust,        // FX
+    WindDirection,   // DD
+    GlobalRadiation, // QQ
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -25,43 +35,58 @@ pub enum TemperatureType {
 }
 
 impl WeatherMetric {
+    /// Registry entry describing this element's units and storage
+    pub fn spec(&self) -> &'static ElementSpec {
+        ELEMENTS
+            .iter()
+            .find(|e| e.metric == *self)
+            .expect("every WeatherMetric is in the element registry")
+    }
+
     pub fn from_file_prefix(prefix: &str) -> Option<Self> {
-        match prefix {
-            "TN" => Some(WeatherMetric::Temperature(TemperatureType::Minimum)),
-            "TX" => Some(WeatherMetric::Temperature(TemperatureType::Maximum)),
-            "TG" => Some(WeatherMetric::Temperature(TemperatureType::Average)),
-            "RR" => Some(WeatherMetric::Precipitation),
-            "FG" => Some(WeatherMetric::WindSpeed),
-            _ => None,
-        }
+        ElementSpec::from_prefix(prefix).map(|e| e.metric.clone())
     }
 
     pub fn to_file_prefix(&self) -> &'static str {
-        match self {
-            WeatherMetric::Temperature(TemperatureType::Minimum) => "TN",
-            WeatherMetric::Temperature(TemperatureType::Maximum) => "TX",
-            WeatherMetric::Temperature(TemperatureType::Average) => "TG",
-            WeatherMetric::Precipitation => "RR",
-            WeatherMetric::WindSpeed => "FG",
-        }
+        self.spec().prefix
     }
 
     pub fn display_name(&self) -> &'static str {
-        match self {
-            WeatherMetric::Temperature(TemperatureType::Minimum) => "Temperature (Min)",
-            WeatherMetric::Temperature(TemperatureType::Maximum) => "Temperature (Max)",
-            WeatherMetric::Temperature(TemperatureType::Average) => "Temperature (Avg)",
-            WeatherMetric::Precipitation => "Precipitation",
-            WeatherMetric::WindSpeed => "Wind Speed",
-        }
+        self.spec().display_name
     }
 
+    /// Units of values in WeatherRecord and Parquet output
     pub fn units(&self) -> &'static str {
-        match self {
-            WeatherMetric::Temperature(_) => "0.1°C",
-            WeatherMetric::Precipitation => "0.1mm",
-            WeatherMetric::WindSpeed => "0.1 m/s",
-        }
+        self.spec().unit
+    }
+
+    /// Units of the integers in ECA&D source files
+    pub fn raw_units(&self) -> &'static str {
+        self.spec().raw_unit
+    }
+
+    /// Supplementary elements stored in their own WeatherRecord columns
+    pub fn supplementary() -> [WeatherMetric; 8] {
+        [
+            WeatherMetric::Sunshine,
+            WeatherMetric::Humidity,
+            WeatherMetric::Pressure,
+            WeatherMetric::CloudCover,
+            WeatherMetric::SnowDepth,
+            WeatherMetric::WindGust,
+            WeatherMetric::WindDirection,
+            WeatherMetric::GlobalRadiation,
+        ]
+    }
+
+    /// Parquet column name holding the value for this metric
+    pub fn column_name(&self) -> &'static str {
+        self.spec().column
+    }
+
+    /// Factor applied to raw ECA&D integers to obtain physical units
+    pub fn scale_factor(&self) -> f32 {
+        self.spec().scale_factor as f32
     }
 }
 
@@ -97,6 +122,14 @@ mod tests {
             WeatherMetric::from_file_prefix("FG"),
             Some(WeatherMetric::WindSpeed)
         );
+        assert_eq!(
+            WeatherMetric::from_file_prefix("SS"),
+            Some(WeatherMetric::Sunshine)
+        );
+        assert_eq!(
+            WeatherMetric::from_file_prefix("QQ"),
+            Some(WeatherMetric::GlobalRadiation)
+        );
         assert_eq!(WeatherMetric::from_file_prefix("XX"), None);
     }
 
@@ -118,6 +151,18 @@ mod tests {
         assert_eq!(WeatherMetric::WindSpeed.to_file_prefix(), "FG");
     }
 
+    #[test]
+    fn test_supplementary_prefix_round_trip() {
+        for metric in WeatherMetric::supplementary() {
+            assert_eq!(
+                WeatherMetric::from_file_prefix(metric.to_file_prefix()),
+                Some(metric.clone())
+            );
+        }
+        assert_eq!(WeatherMetric::Pressure.scale_factor(), 0.1);
+        assert_eq!(WeatherMetric::Humidity.scale_factor(), 1.0);
+    }
+
     #[test]
     fn test_weather_metric_display() {
         assert_eq!(
@@ -132,9 +177,10 @@ mod tests {
     fn test_weather_metric_units() {
         assert_eq!(
             WeatherMetric::Temperature(TemperatureType::Minimum).units(),
-            "0.1°C"
+            "°C"
         );
-        assert_eq!(WeatherMetric::Precipitation.units(), "0.1mm");
-        assert_eq!(WeatherMetric::WindSpeed.units(), "0.1 m/s");
+        assert_eq!(WeatherMetric::Precipitation.units(), "mm");
+        assert_eq!(WeatherMetric::Precipitation.raw_units(), "0.1 mm");
+        assert_eq!(WeatherMetric::WindSpeed.units(), "m/s");
     }
 }
@@ -1,12 +1,10 @@
-use crate::archive::{ArchiveInspector, ArchiveProcessor, WeatherMetric};
+use crate::archive::{ArchiveInspector, ArchiveMetadata, WeatherMetric};
 use crate::error::{ProcessingError, Result};
 use crate::models::WeatherRecord;
-use crate::processors::IntegrityReport;
-use chrono::NaiveDate;
-use std::collections::HashMap;
+use crate::processors::{RunWarning, WarningCode};
 use std::fs;
 use std::path::{Path, PathBuf};
-use tokio::task::JoinSet;
+use tracing::{info, warn};
 
 #[derive(Debug, Clone)]
 pub struct ArchiveInfo {
@@ -14,29 +12,21 @@ pub struct ArchiveInfo {
     pub metrics: Vec<WeatherMetric>,
     pub station_count: usize,
     pub file_count: usize,
+    pub metadata: ArchiveMetadata,
 }
 
-#[derive(Debug, Clone)]
-pub struct DatasetComposition {
-    pub total_records: usize,
-    pub records_with_temperature: usize,
-    pub records_with_precipitation: usize,
-    pub records_with_wind_speed: usize,
-    pub available_metrics: Vec<String>,
-}
-
+/// Archives found in a directory, in file name order.
+///
+/// Processing is done by `Pipeline`, which reads these archives one
+/// station at a time.
 pub struct MultiArchiveProcessor {
     archives: Vec<ArchiveInfo>,
-    max_workers: usize,
+    warnings: Vec<RunWarning>,
 }
 
 impl MultiArchiveProcessor {
-    /// Create a new processor by scanning a directory for zip files
-    pub async fn from_directory(
-        dir_path: &Path,
-        file_pattern: Option<&str>,
-        max_workers: usize,
-    ) -> Result<Self> {
+    /// Scan a directory for zip files, skipping any that can't be inspected
+    pub fn from_directory(dir_path: &Path, file_pattern: Option<&str>) -> Result<Self> {
         if !dir_path.is_dir() {
             return Err(ProcessingError::InvalidFormat(format!(
                 "Path is not a directory: {}",
@@ -45,6 +35,7 @@ impl MultiArchiveProcessor {
         }
 
         let mut archives = Vec::new();
+        let mut warnings = Vec::new();
 
         // Read directory entries
         let entries = fs::read_dir(dir_path)?;
@@ -71,29 +62,36 @@ impl MultiArchiveProcessor {
                 }
             }
 
-            println!("Inspecting archive: {}", path.display());
+            info!(path = %path.display(), "Inspecting archive");
 
             // Inspect the archive to get metadata
             match ArchiveInspector::inspect_zip(&path) {
                 Ok(metadata) => {
                     let archive_info = ArchiveInfo {
                         path: path.clone(),
-                        metrics: metadata.metrics,
+                        metrics: metadata.metrics.clone(),
                         station_count: metadata.station_count,
                         file_count: metadata.total_files,
+                        metadata,
                     };
 
-                    println!(
-                        "  → Found {} metrics across {} stations in {} files",
-                        archive_info.metrics.len(),
-                        archive_info.station_count,
-                        archive_info.file_count
+                    info!(
+                        path = %path.display(),
+                        metrics = archive_info.metrics.len(),
+                        stations = archive_info.station_count,
+                        files = archive_info.file_count,
+                        "Inspected archive"
                     );
 
                     archives.push(archive_info);
                 }
                 Err(e) => {
-                    println!("  → Warning: Failed to inspect {}: {}", path.display(), e);
+                    let message = format!("Failed to inspect {}: {}", path.display(), e);
+                    warn!(path = %path.display(), error = %e, "Failed to inspect archive");
+                    warnings.push(
+                        RunWarning::new(WarningCode::ArchiveInspectionFailed, message)
+                            .with_path(path.display().to_string()),
+                    );
                     continue;
                 }
             }
@@ -109,19 +107,9 @@ impl MultiArchiveProcessor {
         // Sort archives by filename for consistent processing order
         archives.sort_by(|a, b| a.path.file_name().cmp(&b.path.file_name()));
 
-        println!("\nFound {} archives to process:", archives.len());
-        for archive in &archives {
-            println!(
-                "  • {} ({} metrics)",
-                archive.path.file_name().unwrap().to_string_lossy(),
-                archive.metrics.len()
-            );
-        }
+        info!(archives = archives.len(), "Found archives to process");
 
-        Ok(Self {
-            archives,
-            max_workers,
-        })
+        Ok(Self { archives, warnings })
     }
 
     /// Get summary of all discovered archives
@@ -150,156 +138,11 @@ impl MultiArchiveProcessor {
         summary
     }
 
-    /// Process all archives and merge data into unified records
-    pub async fn process_unified_data(
-        mut self,
-        station_filter: Option<u32>,
-    ) -> Result<(Vec<WeatherRecord>, IntegrityReport, DatasetComposition)> {
-        println!(
-            "Processing {} archives with up to {} workers...",
-            self.archives.len(),
-            self.max_workers
-        );
-
-        // Process archives concurrently
-        let mut join_set = JoinSet::new();
-        let archives = std::mem::take(&mut self.archives);
-
-        for archive_info in archives {
-            join_set.spawn(async move {
-                println!("Starting processing: {}", archive_info.path.display());
-
-                let processor = ArchiveProcessor::from_zip(&archive_info.path).await?;
-                let (records, report) = processor.process_data(&archive_info.path).await?;
-
-                // Filter by station if specified
-                let filtered_records = if let Some(station_id) = station_filter {
-                    records
-                        .into_iter()
-                        .filter(|r| r.station_id == station_id)
-                        .collect()
-                } else {
-                    records
-                };
-
-                println!(
-                    "Completed processing: {} ({} records)",
-                    archive_info.path.file_name().unwrap().to_string_lossy(),
-                    filtered_records.len()
-                );
-
-                Ok::<(Vec<WeatherRecord>, IntegrityReport), ProcessingError>((
-                    filtered_records,
-                    report,
-                ))
-            });
-        }
-
-        // Collect all results
-        let mut all_records_by_archive = Vec::new();
-        let mut all_reports = Vec::new();
-
-        while let Some(result) = join_set.join_next().await {
-            match result {
-                Ok(Ok((records, report))) => {
-                    all_records_by_archive.push(records);
-                    all_reports.push(report);
-                }
-                Ok(Err(e)) => return Err(e),
-                Err(e) => return Err(ProcessingError::TaskJoin(e)),
-            }
-        }
-
-        println!("All archives processed. Merging unified records...");
-
-        // Merge records by station and date
-        let (unified_records, composition) = self.merge_records_by_key(all_records_by_archive)?;
-
-        println!("Created {} unified weather records", unified_records.len());
-
-        // Combine integrity reports
-        let combined_report = self.combine_integrity_reports(all_reports);
-
-        Ok((unified_records, combined_report, composition))
-    }
-
-    /// Merge records from multiple archives by (station_id, date) key
-    fn merge_records_by_key(
-        &self,
-        records_by_archive: Vec<Vec<WeatherRecord>>,
-    ) -> Result<(Vec<WeatherRecord>, DatasetComposition)> {
-        let mut record_map: HashMap<(u32, NaiveDate), WeatherRecord> = HashMap::new();
-
-        for archive_records in records_by_archive {
-            for record in archive_records {
-                let key = (record.station_id, record.date);
-
-                match record_map.get_mut(&key) {
-                    Some(existing) => {
-                        // Merge with existing record
-                        Self::merge_weather_records(existing, record)?;
-                    }
-                    None => {
-                        // Add new record
-                        record_map.insert(key, record);
-                    }
-                }
-            }
-        }
-
-        // Convert to vector and ensure all records have physical validation
-        let mut unified_records: Vec<_> = record_map.into_values().collect();
-
-        // Ensure all records have physical validation performed
-        for record in &mut unified_records {
-            record.perform_physical_validation();
-        }
-
-        unified_records.sort_by(|a, b| {
-            a.station_id
-                .cmp(&b.station_id)
-                .then_with(|| a.date.cmp(&b.date))
-        });
-
-        // Calculate dataset composition
-        let total_records = unified_records.len();
-        let records_with_temperature = unified_records
-            .iter()
-            .filter(|r| r.has_temperature_data())
-            .count();
-        let records_with_precipitation = unified_records
-            .iter()
-            .filter(|r| r.has_precipitation())
-            .count();
-        let records_with_wind_speed = unified_records
-            .iter()
-            .filter(|r| r.has_wind_speed())
-            .count();
-
-        let mut available_metrics = Vec::new();
-        if records_with_temperature > 0 {
-            available_metrics.push("temperature".to_string());
-        }
-        if records_with_precipitation > 0 {
-            available_metrics.push("precipitation".to_string());
-        }
-        if records_with_wind_speed > 0 {
-            available_metrics.push("wind_speed".to_string());
-        }
-
-        let composition = DatasetComposition {
-            total_records,
-            records_with_temperature,
-            records_with_precipitation,
-            records_with_wind_speed,
-            available_metrics,
-        };
-
-        Ok((unified_records, composition))
-    }
-
     /// Merge data from one weather record into another
-    fn merge_weather_records(target: &mut WeatherRecord, source: WeatherRecord) -> Result<()> {
+    pub(crate) fn merge_weather_records(
+        target: &mut WeatherRecord,
+        source: WeatherRecord,
+    ) -> Result<()> {
         // Verify records are for same station and date
         if target.station_id != source.station_id || target.date != source.date {
             return Err(ProcessingError::InvalidFormat(format!(
@@ -308,6 +151,13 @@ impl MultiArchiveProcessor {
             )));
         }
 
+        if source.country.is_some() {
+            target.country = source.country.clone();
+        }
+        if source.elevation.is_some() {
+            target.elevation = source.elevation;
+        }
+
         // Merge temperature data (prefer non-null values)
         if source.temp_min.is_some() {
             target.temp_min = source.temp_min;
@@ -329,6 +179,20 @@ impl MultiArchiveProcessor {
             target.wind_speed = source.wind_speed;
         }
 
+        // Merge source series IDs alongside the values they describe
+        for (column, souid) in source.source_ids() {
+            if souid.is_some() {
+                target.set_source_id(column, souid);
+            }
+        }
+
+        // Merge supplementary elements together with their quality flags
+        for (metric, value, quality, _) in source.supplementary_elements() {
+            if value.is_some() {
+                target.set_supplementary_element(&metric, value, quality.map(str::to_string));
+            }
+        }
+
         // Merge quality flags
         if source.temp_quality.is_some() {
             target.temp_quality = source.temp_quality;
@@ -340,43 +204,24 @@ impl MultiArchiveProcessor {
             target.wind_quality = source.wind_quality;
         }
 
+        // Keep reasons only for columns still missing after the merge
+        target.missing_reasons.extend(source.missing_reasons);
+        target.clear_filled_missing_reasons();
+
         // Re-run physical validation after merging data
         target.perform_physical_validation();
 
         Ok(())
     }
 
-    /// Combine multiple integrity reports into one
-    fn combine_integrity_reports(&self, reports: Vec<IntegrityReport>) -> IntegrityReport {
-        let mut combined = IntegrityReport {
-            total_records: 0,
-            valid_records: 0,
-            suspect_records: 0,
-            invalid_records: 0,
-            missing_data_records: 0,
-            temperature_violations: Vec::new(),
-            station_statistics: HashMap::new(),
-        };
-
-        for report in reports {
-            combined.total_records += report.total_records;
-            combined.valid_records += report.valid_records;
-            combined.suspect_records += report.suspect_records;
-            combined.invalid_records += report.invalid_records;
-            combined.missing_data_records += report.missing_data_records;
-
-            // Merge violations
-            combined
-                .temperature_violations
-                .extend(report.temperature_violations);
-
-            // Merge station statistics (basic approach - could be more sophisticated)
-            for (station_id, stats) in report.station_statistics {
-                combined.station_statistics.insert(station_id, stats);
-            }
-        }
+    /// Archives found in the directory, with their metadata
+    pub fn archives(&self) -> &[ArchiveInfo] {
+        &self.archives
+    }
 
-        combined
+    /// Warnings raised while scanning the directory, e.g. unreadable archives
+    pub fn warnings(&self) -> &[RunWarning] {
+        &self.warnings
     }
 
     /// Get list of archive paths
@@ -394,7 +239,6 @@ impl MultiArchiveProcessor {
 mod tests {
     use super::*;
     use std::fs::File;
-    use std::io::Write;
     use tempfile::TempDir;
 
     fn create_test_directory() -> Result<TempDir> {
@@ -410,13 +254,12 @@ mod tests {
         Ok(temp_dir)
     }
 
-    #[tokio::test]
-    async fn test_directory_scanning() {
+    #[test]
+    fn test_directory_scanning() {
         let temp_dir = create_test_directory().unwrap();
 
         // This will fail because the zip files are empty, but we can test the scanning logic
-        let result =
-            MultiArchiveProcessor::from_directory(temp_dir.path(), Some("UK_ALL_"), 4).await;
+        let result = MultiArchiveProcessor::from_directory(temp_dir.path(), Some("UK_ALL_"));
 
         // Should find 3 UK_ALL_ files and fail on inspection
         assert!(result.is_err());
@@ -1,19 +1,38 @@
 use crate::archive::{
-    ArchiveInspector, ArchiveMetadata, TempFileManager, TemperatureType, WeatherMetric,
+    ArchiveInspector, ArchiveMetadata, ElementSpec, TempFileManager, TemperatureType, WeatherMetric,
 };
 use crate::error::{ProcessingError, Result};
-use crate::models::{StationMetadata, WeatherRecord};
-use crate::processors::{IntegrityReport, StationStatistics, TemperatureViolation, ViolationType};
-use crate::readers::{StationReader, TemperatureReader};
+use crate::models::weather::{MissingObservation, MissingReason};
+use crate::models::{
+    DateFilter, SourceMetadata, StationMetadata, StationSelection, TemperatureRecord,
+    ValidationRules, WeatherRecord,
+};
+use crate::processors::{
+    IntegrityReport, RunWarning, StationStatistics, TemperatureViolation, ViolationType,
+    WarningCode,
+};
+use crate::readers::{SourceReader, StationReader, TemperatureReader};
+use crate::utils::constants::{DEFAULT_BUFFER_SIZE, SOURCES_FILE};
 use chrono::NaiveDate;
-use std::collections::HashMap;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use tracing::{debug, debug_span, info, instrument, warn, Span};
+use zip::ZipArchive;
 
 pub struct ArchiveProcessor {
     temp_manager: TempFileManager,
     archive_metadata: ArchiveMetadata,
+    thread_pool: Option<Arc<ThreadPool>>,
+    station_selection: Option<StationSelection>,
+    date_filter: DateFilter,
+    validation_rules: Arc<ValidationRules>,
+    keep_missing: bool,
+    metrics: Option<Vec<WeatherMetric>>,
 }
 
 impl ArchiveProcessor {
@@ -27,51 +46,97 @@ impl ArchiveProcessor {
         Ok(Self {
             temp_manager,
             archive_metadata,
+            thread_pool: None,
+            station_selection: None,
+            date_filter: DateFilter::default(),
+            validation_rules: Arc::new(ValidationRules::default()),
+            keep_missing: false,
+            metrics: None,
         })
     }
 
+    /// Parse data entries on a dedicated pool of `max_workers` threads
+    pub fn with_max_workers(self, max_workers: usize) -> Result<Self> {
+        Ok(self.with_thread_pool(build_thread_pool(max_workers)?))
+    }
+
+    /// Parse data entries on a shared thread pool
+    pub fn with_thread_pool(mut self, thread_pool: Arc<ThreadPool>) -> Self {
+        self.thread_pool = Some(thread_pool);
+        self
+    }
+
+    /// Only index and read the data of stations matching `selection`
+    pub fn with_station_selection(mut self, selection: StationSelection) -> Self {
+        self.station_selection = Some(selection);
+        self
+    }
+
+    /// Skip data lines dated outside `date_filter` while parsing
+    pub fn with_date_filter(mut self, date_filter: DateFilter) -> Self {
+        self.date_filter = date_filter;
+        self
+    }
+
+    /// Classify physical validity with `rules` instead of the built-in limits
+    pub fn with_validation_rules(mut self, rules: Arc<ValidationRules>) -> Self {
+        self.validation_rules = rules;
+        self
+    }
+
+    /// Keep observations the source reported without a value, and data
+    /// files of stations missing from stations.txt, as rows with null values
+    /// and a missing reason
+    pub fn with_keep_missing(mut self, keep_missing: bool) -> Self {
+        self.keep_missing = keep_missing;
+        self
+    }
+
+    /// Only read the data entries of `metrics`; other elements stay empty
+    pub fn with_metrics(mut self, metrics: Vec<WeatherMetric>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
     pub fn metadata(&self) -> &ArchiveMetadata {
         &self.archive_metadata
     }
 
+    /// Read source series metadata from the archive's sources.txt, if present
+    pub fn read_sources(&mut self, zip_path: &Path) -> Result<Vec<SourceMetadata>> {
+        match self.temp_manager.extract_file(zip_path, SOURCES_FILE) {
+            Ok(sources_path) => SourceReader::new().read_sources(&sources_path),
+            Err(_) => Ok(Vec::new()),
+        }
+    }
+
+    #[instrument(name = "archive", skip_all, fields(path = %zip_path.display()))]
     pub async fn process_data(
         mut self,
         zip_path: &Path,
     ) -> Result<(Vec<WeatherRecord>, IntegrityReport)> {
-        // Extract metadata files
-        let metadata_files = self.temp_manager.extract_metadata_files(zip_path)?;
-
-        // Read station metadata
-        let station_map = if let Some(stations_path) = metadata_files.get("stations.txt") {
-            let reader = StationReader::new();
-            reader.read_stations_map(stations_path)?
-        } else {
-            return Err(ProcessingError::InvalidFormat(
-                "stations.txt not found in archive".to_string(),
-            ));
-        };
-
-        println!("Loaded {} stations from metadata", station_map.len());
+        let index = self.index_archive(zip_path)?;
+
+        // Parse every data entry in parallel, straight from the archive
+        let entries: Vec<(u32, &WeatherMetric, &str)> = index
+            .station_files
+            .iter()
+            .flat_map(|(&station_id, files)| {
+                files
+                    .iter()
+                    .map(move |(metric, name)| (station_id, metric, name.as_str()))
+            })
+            .collect();
+        let parsed = self.read_entries(&index, &entries)?;
 
         // Group weather data by station and date
         let mut weather_data: HashMap<(u32, NaiveDate), WeatherRecord> = HashMap::new();
 
-        // Process each metric type
-        for metric in &self.archive_metadata.metrics {
-            let pattern = format!("{}_STAID", metric.to_file_prefix());
-            let data_files = self
-                .temp_manager
-                .extract_files_matching_pattern(zip_path, &pattern)?;
-
-            println!(
-                "Processing {} files for metric: {}",
-                data_files.len(),
-                metric
-            );
-
-            // Process files for this metric
-            self.process_metric_files(&data_files, metric, &station_map, &mut weather_data)
-                .await?;
+        for ((station_id, metric, _), entry) in entries.into_iter().zip(parsed) {
+            if let Some(station) = index.station_map.get(&station_id) {
+                let entry = index.checked_entry(station_id, entry);
+                self.merge_entry(station, metric, entry, &mut weather_data);
+            }
         }
 
         // Convert to vector and ensure all records have physical validation
@@ -79,10 +144,12 @@ impl ArchiveProcessor {
 
         // Ensure all records have physical validation performed after data population
         for record in &mut all_records {
-            record.perform_physical_validation();
+            record.clear_filled_missing_reasons();
+            record.perform_physical_validation_with(&self.validation_rules);
         }
 
-        let integrity_report = self.calculate_integrity_report(&all_records);
+        let mut integrity_report = Self::calculate_integrity_report(&all_records);
+        integrity_report.warnings = index.warnings;
 
         // Cleanup temporary files
         self.temp_manager.cleanup()?;
@@ -90,45 +157,148 @@ impl ArchiveProcessor {
         Ok((all_records, integrity_report))
     }
 
-    async fn process_metric_files(
-        &self,
-        file_paths: &[std::path::PathBuf],
-        metric: &WeatherMetric,
-        station_map: &HashMap<u32, StationMetadata>,
-        weather_data: &mut HashMap<(u32, NaiveDate), WeatherRecord>,
-    ) -> Result<()> {
-        for file_path in file_paths {
-            if let Some(file_name) = file_path.file_name().and_then(|n| n.to_str()) {
-                if let Some(station_id) = extract_station_id_from_filename(file_name) {
-                    // Get station metadata
-                    let station_metadata = station_map.get(&station_id);
-                    if station_metadata.is_none() {
-                        println!("Warning: Station {} not found in metadata", station_id);
+    /// Read station metadata and list the archive's data entries by station.
+    ///
+    /// Only the small metadata files are extracted; data entries are read
+    /// from the zip when a station is loaded.
+    pub fn index_archive(&mut self, zip_path: &Path) -> Result<ArchiveIndex> {
+        // Extract metadata files
+        let metadata_files = self.temp_manager.extract_metadata_files(zip_path)?;
+
+        // Read station metadata
+        let mut station_map = if let Some(stations_path) = metadata_files.get("stations.txt") {
+            let reader = StationReader::new();
+            reader.read_stations_map(stations_path)?
+        } else {
+            return Err(ProcessingError::InvalidFormat(
+                "stations.txt not found in archive".to_string(),
+            ));
+        };
+
+        info!(stations = station_map.len(), "Loaded station metadata");
+
+        // Drop unselected stations before any of their data entries are listed
+        let mut deselected = HashSet::new();
+        let selection = self.station_selection.as_ref().filter(|s| !s.is_empty());
+        if let Some(selection) = selection {
+            let total = station_map.len();
+            station_map.retain(|&station_id, station| {
+                let selected = selection.matches(station);
+                if !selected {
+                    deselected.insert(station_id);
+                }
+                selected
+            });
+            info!(
+                selected = station_map.len(),
+                total, "Applied station selection"
+            );
+        }
+
+        let archive = ZipArchive::new(File::open(zip_path)?)?;
+        let mut station_files: BTreeMap<u32, Vec<(WeatherMetric, String)>> = BTreeMap::new();
+        let mut unknown_stations = BTreeSet::new();
+        let mut warnings = Vec::new();
+
+        // List the entries for each selected metric type
+        let metrics = self.archive_metadata.metrics.iter().filter(|m| {
+            self.metrics
+                .as_ref()
+                .map_or(true, |selected| selected.contains(m))
+        });
+        for metric in metrics {
+            let pattern = format!("{}_STAID", metric.to_file_prefix());
+            let entry_names: Vec<&str> = archive
+                .file_names()
+                .filter(|name| name.contains(&pattern) && name.ends_with(".txt"))
+                .collect();
+
+            info!(
+                files = entry_names.len(),
+                metric = %metric,
+                "Listed data files for metric"
+            );
+
+            for entry_name in entry_names {
+                if let Some(station_id) = extract_station_id_from_filename(entry_name) {
+                    if deselected.contains(&station_id) {
                         continue;
                     }
-                    let station = station_metadata.unwrap();
-
-                    // Parse weather data based on metric type
-                    match metric {
-                        WeatherMetric::Temperature(temp_type) => {
-                            self.process_temperature_file(
-                                file_path,
-                                station,
-                                temp_type,
-                                weather_data,
-                            )?;
-                        }
-                        WeatherMetric::Precipitation => {
-                            self.process_precipitation_file(file_path, station, weather_data)?;
-                        }
-                        WeatherMetric::WindSpeed => {
-                            self.process_wind_speed_file(file_path, station, weather_data)?;
+                    if !station_map.contains_key(&station_id) {
+                        let message = format!("Station {} not found in metadata", station_id);
+                        warn!(
+                            station_id,
+                            entry = entry_name,
+                            "Station not found in metadata"
+                        );
+                        warnings.push(
+                            RunWarning::new(WarningCode::StationNotInMetadata, message)
+                                .with_station(station_id)
+                                .with_path(entry_name),
+                        );
+                        // Unlisted stations cannot be matched against a selection
+                        if !self.keep_missing || selection.is_some() {
+                            continue;
                         }
+                        unknown_stations.insert(station_id);
                     }
+                    station_files
+                        .entry(station_id)
+                        .or_default()
+                        .push((metric.clone(), entry_name.to_string()));
                 }
             }
         }
 
+        for &station_id in &unknown_stations {
+            station_map.insert(
+                station_id,
+                StationMetadata::new(
+                    station_id,
+                    UNKNOWN_STATION_NAME.to_string(),
+                    String::new(),
+                    0.0,
+                    0.0,
+                    None,
+                ),
+            );
+        }
+
+        Ok(ArchiveIndex {
+            zip_path: zip_path.to_path_buf(),
+            station_map,
+            station_files,
+            unknown_stations,
+            warnings,
+            handles: Mutex::new(vec![archive]),
+        })
+    }
+
+    /// Parse every data entry for one station into `weather_data`
+    pub fn load_station(
+        &self,
+        index: &ArchiveIndex,
+        station_id: u32,
+        weather_data: &mut HashMap<(u32, NaiveDate), WeatherRecord>,
+    ) -> Result<()> {
+        let (Some(station), Some(files)) = (
+            index.station_map.get(&station_id),
+            index.station_files.get(&station_id),
+        ) else {
+            return Ok(());
+        };
+
+        let entries: Vec<(u32, &WeatherMetric, &str)> = files
+            .iter()
+            .map(|(metric, name)| (station_id, metric, name.as_str()))
+            .collect();
+        let parsed = self.read_entries(index, &entries)?;
+
+        for ((_, metric, _), entry) in entries.into_iter().zip(parsed) {
+            let entry = index.checked_entry(station_id, entry);
+            self.merge_entry(station, metric, entry, weather_data);
+        }
+
         Ok(())
     }
 
@@ -140,57 +310,131 @@ impl ArchiveProcessor {
         self.temp_manager.cleanup()
     }
 
-    fn process_temperature_file(
+    /// Parse zip entries in parallel; each worker borrows a handle on the
+    /// archive from the index, opening one only when none is free. Results
+    /// are returned in `entries` order.
+    fn read_entries(
+        &self,
+        index: &ArchiveIndex,
+        entries: &[(u32, &WeatherMetric, &str)],
+    ) -> Result<Vec<ParsedEntry>> {
+        // Rayon workers don't inherit the caller's span, so parent explicitly
+        let parent = Span::current();
+        let parse = || {
+            entries
+                .par_iter()
+                .map_init(
+                    || ArchiveHandle::borrow(index),
+                    |archive, &(station_id, metric, name)| {
+                        let _span =
+                            debug_span!(parent: &parent, "station", station_id, entry = name)
+                                .entered();
+                        debug!(metric = %metric, "Reading entry");
+                        let entry = archive.get()?.by_name(name)?;
+
+                        match metric {
+                            WeatherMetric::Temperature(_) => {
+                                let reader =
+                                    TemperatureReader::new().with_date_filter(self.date_filter);
+                                if self.keep_missing {
+                                    reader
+                                        .read_temperatures_with_missing_from_reader(
+                                            entry, station_id,
+                                        )
+                                        .map(|(records, missing)| {
+                                            ParsedEntry::Temperature(records, missing)
+                                        })
+                                } else {
+                                    reader.read_temperatures_from_reader(entry, station_id).map(
+                                        |records| ParsedEntry::Temperature(records, Vec::new()),
+                                    )
+                                }
+                            }
+                            _ => parse_weather_values(
+                                entry,
+                                metric.spec(),
+                                &self.date_filter,
+                                self.keep_missing,
+                            )
+                            .map(|(values, missing)| ParsedEntry::Values(values, missing)),
+                        }
+                    },
+                )
+                .collect()
+        };
+
+        match &self.thread_pool {
+            Some(pool) => pool.install(parse),
+            None => parse(),
+        }
+    }
+
+    /// Merge one parsed entry into the station/date keyed records
+    fn merge_entry(
         &self,
-        file_path: &Path,
         station: &StationMetadata,
-        temp_type: &TemperatureType,
+        metric: &WeatherMetric,
+        entry: ParsedEntry,
         weather_data: &mut HashMap<(u32, NaiveDate), WeatherRecord>,
-    ) -> Result<()> {
-        let reader = TemperatureReader::new();
-        let temp_records = reader.read_temperatures_with_station_id(file_path, station.staid)?;
+    ) {
+        let missing = match (metric, entry) {
+            (WeatherMetric::Temperature(temp_type), ParsedEntry::Temperature(records, missing)) => {
+                self.merge_temperature_records(records, station, temp_type, weather_data);
+                missing
+            }
+            (WeatherMetric::Precipitation, ParsedEntry::Values(values, missing)) => {
+                self.merge_precipitation_values(values, station, weather_data);
+                missing
+            }
+            (WeatherMetric::WindSpeed, ParsedEntry::Values(values, missing)) => {
+                self.merge_wind_speed_values(values, station, weather_data);
+                missing
+            }
+            (_, ParsedEntry::Values(values, missing)) => {
+                self.merge_element_values(values, station, metric, weather_data);
+                missing
+            }
+            (_, ParsedEntry::Temperature(..)) => Vec::new(),
+        };
 
+        if self.keep_missing {
+            for observation in missing {
+                weather_data
+                    .entry((station.staid, observation.date))
+                    .or_insert_with(|| empty_station_record(station, observation.date))
+                    .set_missing_reason(metric.column_name(), observation.reason);
+            }
+        }
+    }
+
+    fn merge_temperature_records(
+        &self,
+        temp_records: Vec<TemperatureRecord>,
+        station: &StationMetadata,
+        temp_type: &TemperatureType,
+        weather_data: &mut HashMap<(u32, NaiveDate), WeatherRecord>,
+    ) {
         for temp_record in temp_records {
             let key = (temp_record.staid, temp_record.date);
 
             // Get or create weather record for this station/date
-            let weather_record = weather_data.entry(key).or_insert_with(|| {
-                WeatherRecord::builder()
-                    .station_id(station.staid)
-                    .station_name(station.name.clone())
-                    .date(temp_record.date)
-                    .coordinates(station.latitude, station.longitude)
-                    .build()
-                    .unwrap_or_else(|_| {
-                        // Fallback record if builder fails
-                        WeatherRecord::new(
-                            station.staid,
-                            station.name.clone(),
-                            temp_record.date,
-                            station.latitude,
-                            station.longitude,
-                            None,
-                            None,
-                            None,
-                            None,
-                            None,
-                            None,
-                            None,
-                            None,
-                        )
-                    })
-            });
+            let weather_record = weather_data
+                .entry(key)
+                .or_insert_with(|| empty_station_record(station, temp_record.date));
 
-            // Update temperature data based on type
+            // Update temperature data and its source series based on type
             match temp_type {
                 TemperatureType::Minimum => {
                     weather_record.temp_min = Some(temp_record.temperature);
+                    weather_record.temp_min_souid = Some(temp_record.souid);
                 }
                 TemperatureType::Maximum => {
                     weather_record.temp_max = Some(temp_record.temperature);
+                    weather_record.temp_max_souid = Some(temp_record.souid);
                 }
                 TemperatureType::Average => {
                     weather_record.temp_avg = Some(temp_record.temperature);
+                    weather_record.temp_avg_souid = Some(temp_record.souid);
                 }
             }
 
@@ -204,129 +448,70 @@ impl ArchiveProcessor {
                 weather_record.temp_quality = Some(quality_str);
             }
         }
-
-        Ok(())
     }
 
-    fn process_precipitation_file(
+    fn merge_precipitation_values(
         &self,
-        file_path: &Path,
+        precip_records: Vec<WeatherValue>,
         station: &StationMetadata,
         weather_data: &mut HashMap<(u32, NaiveDate), WeatherRecord>,
-    ) -> Result<()> {
-        let precip_records = self.parse_weather_file(file_path, station.staid)?;
-
-        for (date, value, quality) in precip_records {
+    ) {
+        for (souid, date, value, quality) in precip_records {
             let key = (station.staid, date);
 
-            let weather_record = weather_data.entry(key).or_insert_with(|| {
-                WeatherRecord::new(
-                    station.staid,
-                    station.name.clone(),
-                    date,
-                    station.latitude,
-                    station.longitude,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                )
-            });
+            let weather_record = weather_data
+                .entry(key)
+                .or_insert_with(|| empty_station_record(station, date));
 
-            weather_record.precipitation = Some(value / 10.0); // Convert from 0.1mm to mm
+            weather_record.precipitation = Some(value);
             weather_record.precip_quality = Some(quality.to_string());
+            weather_record.precip_souid = Some(souid);
         }
-
-        Ok(())
     }
 
-    fn process_wind_speed_file(
+    fn merge_wind_speed_values(
         &self,
-        file_path: &Path,
+        wind_records: Vec<WeatherValue>,
         station: &StationMetadata,
         weather_data: &mut HashMap<(u32, NaiveDate), WeatherRecord>,
-    ) -> Result<()> {
-        let wind_records = self.parse_weather_file(file_path, station.staid)?;
-
-        for (date, value, quality) in wind_records {
+    ) {
+        for (souid, date, value, quality) in wind_records {
             let key = (station.staid, date);
 
-            let weather_record = weather_data.entry(key).or_insert_with(|| {
-                WeatherRecord::new(
-                    station.staid,
-                    station.name.clone(),
-                    date,
-                    station.latitude,
-                    station.longitude,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                )
-            });
+            let weather_record = weather_data
+                .entry(key)
+                .or_insert_with(|| empty_station_record(station, date));
 
-            weather_record.wind_speed = Some(value / 10.0); // Convert from 0.1 m/s to m/s
+            weather_record.wind_speed = Some(value);
             weather_record.wind_quality = Some(quality.to_string());
+            weather_record.wind_souid = Some(souid);
         }
-
-        Ok(())
     }
 
-    fn parse_weather_file(
+    fn merge_element_values(
         &self,
-        file_path: &Path,
-        _station_id: u32,
-    ) -> Result<Vec<(NaiveDate, f32, u8)>> {
-        let file = File::open(file_path)?;
-        let reader = BufReader::new(file);
-        let mut records = Vec::new();
-        let mut line_count = 0;
-
-        for line_result in reader.lines() {
-            let line = line_result?;
-            line_count += 1;
-
-            // Skip empty lines
-            if line.trim().is_empty() {
-                continue;
-            }
-
-            // Skip header lines (first 20 lines typically contain headers)
-            if line_count <= 20 {
-                continue;
-            }
+        element_records: Vec<WeatherValue>,
+        station: &StationMetadata,
+        metric: &WeatherMetric,
+        weather_data: &mut HashMap<(u32, NaiveDate), WeatherRecord>,
+    ) {
+        for (souid, date, value, quality) in element_records {
+            let key = (station.staid, date);
 
-            // Parse data line: SOUID, DATE, VALUE, Q_FLAG
-            let parts: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
-            if parts.len() < 4 {
-                continue; // Skip malformed lines
-            }
+            let weather_record = weather_data
+                .entry(key)
+                .or_insert_with(|| empty_station_record(station, date));
 
-            // Parse date (YYYYMMDD format)
-            if let Ok(date) = NaiveDate::parse_from_str(parts[1], "%Y%m%d") {
-                // Parse value (skip missing values)
-                if parts[2] != "-9999" {
-                    if let (Ok(value), Ok(quality)) =
-                        (parts[2].parse::<f32>(), parts[3].parse::<u8>())
-                    {
-                        records.push((date, value, quality));
-                    }
-                }
-            }
+            weather_record.set_supplementary_element(
+                metric,
+                Some(value),
+                Some(quality.to_string()),
+            );
+            weather_record.set_source_id(metric.column_name(), Some(souid));
         }
-
-        Ok(records)
     }
 
-    fn calculate_integrity_report(&self, records: &[WeatherRecord]) -> IntegrityReport {
+    pub fn calculate_integrity_report(records: &[WeatherRecord]) -> IntegrityReport {
         let mut valid_records = 0;
         let mut suspect_records = 0;
         let mut invalid_records = 0;
@@ -411,6 +596,8 @@ impl ArchiveProcessor {
             missing_data_records,
             temperature_violations,
             station_statistics,
+            temporal_qc: Default::default(),
+            warnings: Vec::new(),
         }
     }
 }
@@ -418,11 +605,216 @@ impl ArchiveProcessor {
 impl Drop for ArchiveProcessor {
     fn drop(&mut self) {
         if let Err(e) = self.temp_manager.cleanup() {
-            eprintln!("Warning: Failed to cleanup archive processor: {}", e);
+            warn!(error = %e, "Failed to clean up archive processor");
         }
     }
 }
 
+/// Station metadata plus the archive's data entry names, grouped by station
+pub struct ArchiveIndex {
+    pub zip_path: PathBuf,
+    pub station_map: HashMap<u32, StationMetadata>,
+    pub station_files: BTreeMap<u32, Vec<(WeatherMetric, String)>>,
+    /// Stations with data files but no stations.txt entry, only indexed when
+    /// keeping missing observations
+    pub unknown_stations: BTreeSet<u32>,
+    /// Warnings raised while listing the archive
+    pub warnings: Vec<RunWarning>,
+    /// Open handles on the zip, kept so its central directory is parsed
+    /// once per worker rather than once per station
+    handles: Mutex<Vec<ZipArchive<File>>>,
+}
+
+/// A zip handle borrowed from an [`ArchiveIndex`], returned to it on drop
+struct ArchiveHandle<'a> {
+    index: &'a ArchiveIndex,
+    archive: Option<ZipArchive<File>>,
+}
+
+impl<'a> ArchiveHandle<'a> {
+    fn borrow(index: &'a ArchiveIndex) -> Self {
+        let archive = index
+            .handles
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop();
+        Self { index, archive }
+    }
+
+    /// The borrowed handle, opening a new one if none was free
+    fn get(&mut self) -> Result<&mut ZipArchive<File>> {
+        if self.archive.is_none() {
+            self.archive = Some(ZipArchive::new(File::open(&self.index.zip_path)?)?);
+        }
+        Ok(self.archive.as_mut().unwrap())
+    }
+}
+
+impl Drop for ArchiveHandle<'_> {
+    fn drop(&mut self) {
+        if let Some(archive) = self.archive.take() {
+            self.index
+                .handles
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(archive);
+        }
+    }
+}
+
+impl ArchiveIndex {
+    /// Values of stations without metadata cannot be placed, so every line
+    /// of their files becomes a missing observation
+    fn checked_entry(&self, station_id: u32, entry: ParsedEntry) -> ParsedEntry {
+        if !self.unknown_stations.contains(&station_id) {
+            return entry;
+        }
+        let unknown = |souid, date| MissingObservation {
+            souid: Some(souid),
+            date,
+            reason: MissingReason::UnknownStation,
+        };
+        let missing = match entry {
+            ParsedEntry::Temperature(records, missing) => records
+                .into_iter()
+                .map(|r| (r.souid, r.date))
+                .chain(missing.into_iter().map(|m| (m.souid.unwrap_or(0), m.date)))
+                .map(|(souid, date)| unknown(souid, date))
+                .collect(),
+            ParsedEntry::Values(values, missing) => values
+                .into_iter()
+                .map(|(souid, date, ..)| (souid, date))
+                .chain(missing.into_iter().map(|m| (m.souid.unwrap_or(0), m.date)))
+                .map(|(souid, date)| unknown(souid, date))
+                .collect(),
+        };
+        ParsedEntry::Values(Vec::new(), missing)
+    }
+}
+
+/// Station name given to records of stations missing from stations.txt
+pub const UNKNOWN_STATION_NAME: &str = "UNKNOWN STATION";
+
+/// One daily value from a data file: (SOUID, date, value in output units, quality flag)
+type WeatherValue = (u32, NaiveDate, f32, u8);
+
+/// The parsed contents of a single zip entry
+enum ParsedEntry {
+    Temperature(Vec<TemperatureRecord>, Vec<MissingObservation>),
+    Values(Vec<WeatherValue>, Vec<MissingObservation>),
+}
+
+/// An empty record for a station/date, ready for metrics to be merged in
+fn empty_station_record(station: &StationMetadata, date: NaiveDate) -> WeatherRecord {
+    let mut record = WeatherRecord::new(
+        station.staid,
+        station.name.clone(),
+        date,
+        station.latitude,
+        station.longitude,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    record.country = Some(station.country.clone()).filter(|c| !c.is_empty());
+    record.elevation = station.elevation;
+    record
+}
+
+/// Build a rayon pool for parsing data entries
+pub fn build_thread_pool(max_workers: usize) -> Result<Arc<ThreadPool>> {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(max_workers)
+        .build()
+        .map_err(|e| ProcessingError::Config(e.to_string()))?;
+    Ok(Arc::new(pool))
+}
+
+/// Validate one station's merged records and sort them by date
+pub fn finalize_station_records(
+    weather_data: HashMap<(u32, NaiveDate), WeatherRecord>,
+    rules: &ValidationRules,
+) -> Vec<WeatherRecord> {
+    let mut records: Vec<WeatherRecord> = weather_data.into_values().collect();
+    for record in &mut records {
+        record.clear_filled_missing_reasons();
+        record.perform_physical_validation_with(rules);
+    }
+    records.sort_by_key(|r| r.date);
+    records
+}
+
+/// Parse an RR/FG-style data file (SOUID, DATE, VALUE, Q_FLAG) from any byte
+/// source, converting values to the element's output units. Lines without a
+/// value are only collected as missing observations when `keep_missing`.
+fn parse_weather_values<R: Read>(
+    source: R,
+    spec: &ElementSpec,
+    date_filter: &DateFilter,
+    keep_missing: bool,
+) -> Result<(Vec<WeatherValue>, Vec<MissingObservation>)> {
+    let reader = BufReader::with_capacity(DEFAULT_BUFFER_SIZE, source);
+    let mut records = Vec::new();
+    let mut missing = Vec::new();
+    let mut line_count = 0;
+
+    for line_result in reader.lines() {
+        let line = line_result?;
+        line_count += 1;
+
+        // Skip empty lines
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        // Skip header lines (first 20 lines typically contain headers)
+        if line_count <= 20 {
+            continue;
+        }
+
+        // Parse data line: SOUID, DATE, VALUE, Q_FLAG
+        let parts: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
+        let souid = parts[0].parse::<u32>().ok();
+        let Some(date) = parts
+            .get(1)
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y%m%d").ok())
+        else {
+            continue; // Skip lines without a date
+        };
+        // Skip dates outside the filter
+        if !date_filter.matches(date) {
+            continue;
+        }
+        let mut note_missing = |reason| {
+            if keep_missing {
+                missing.push(MissingObservation {
+                    souid,
+                    date,
+                    reason,
+                });
+            }
+        };
+
+        let (Some(souid), Some(raw), Some(flag)) = (souid, parts.get(2), parts.get(3)) else {
+            note_missing(MissingReason::MalformedLine);
+            continue;
+        };
+        match (spec.to_physical(raw), flag.parse::<u8>()) {
+            (Ok(Some(value)), Ok(quality)) => records.push((souid, date, value, quality)),
+            (Ok(None), Ok(9)) => note_missing(MissingReason::Flag9),
+            (Ok(None), _) => note_missing(MissingReason::Sentinel),
+            _ => note_missing(MissingReason::MalformedLine),
+        }
+    }
+
+    Ok((records, missing))
+}
+
 fn extract_station_id_from_filename(file_name: &str) -> Option<u32> {
     // Extract station ID from patterns like TX_STAID000257.txt
     if let Some(start) = file_name.find("STAID") {
@@ -442,6 +834,7 @@ fn extract_station_id_from_filename(file_name: &str) -> Option<u32> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Datelike;
 
     #[test]
     fn test_extract_station_id_from_filename() {
@@ -455,4 +848,80 @@ mod tests {
         );
         assert_eq!(extract_station_id_from_filename("invalid_file.txt"), None);
     }
+
+    #[test]
+    fn test_parse_weather_values_from_reader() -> Result<()> {
+        let mut content = "Header line\n".repeat(20);
+        content.push_str("  102,20230101,   25,    0\n  102,20230102,-9999,    9\n");
+        content.push_str("  102,20230103,-9999,    0\n  102,20230104,   1x\n");
+
+        let rr = WeatherMetric::Precipitation.spec();
+        let (values, missing) =
+            parse_weather_values(content.as_bytes(), rr, &DateFilter::default(), true)?;
+
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].0, 102);
+        assert_eq!(values[0].2, 2.5);
+        let reasons: Vec<_> = missing.iter().map(|m| (m.date.day(), m.reason)).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (2, MissingReason::Flag9),
+                (3, MissingReason::Sentinel),
+                (4, MissingReason::MalformedLine),
+            ]
+        );
+
+        let january_fourth = NaiveDate::from_ymd_opt(2023, 1, 4);
+        let filter = DateFilter::new()
+            .with_date_range(january_fourth, None)
+            .unwrap();
+        let (values, missing) = parse_weather_values(content.as_bytes(), rr, &filter, true)?;
+        assert!(values.is_empty());
+        assert_eq!(missing.len(), 1);
+
+        // Missing lines are not collected unless they will be kept
+        let (values, missing) =
+            parse_weather_values(content.as_bytes(), rr, &DateFilter::default(), false)?;
+        assert_eq!(values.len(), 1);
+        assert!(missing.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_zip_handles_reused_across_stations() -> Result<()> {
+        use std::io::Write;
+
+        let dir = tempfile::TempDir::new()?;
+        let path = dir.path().join("ECA_test.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&path)?);
+        let options = zip::write::FileOptions::default();
+        zip.start_file("stations.txt", options)?;
+        zip.write_all(
+            b"STAID, STANAME                                 , CN, LAT    , LON     , HGHT\n\
+              257, CET CENTRAL ENGLAND                     , GB,+52:25:12,-001:49:48,   78\n\
+              258, ARMAGH                                  , GB,+54:21:10,-006:38:57,   62\n",
+        )?;
+        for station in ["257", "258"] {
+            zip.start_file(format!("TG_STAID000{}.txt", station), options)?;
+            zip.write_all("Header line\n".repeat(20).as_bytes())?;
+            zip.write_all(b"  101,20230101,   55,    0\n")?;
+        }
+        zip.finish()?;
+
+        let mut processor = ArchiveProcessor::from_zip(&path)
+            .await?
+            .with_max_workers(1)?;
+        let index = processor.index_archive(&path)?;
+        let mut weather_data = HashMap::new();
+        for station_id in [257, 258, 257] {
+            processor.load_station(&index, station_id, &mut weather_data)?;
+        }
+
+        // The handle opened while indexing served every station
+        assert_eq!(weather_data.len(), 2);
+        assert_eq!(index.handles.lock().unwrap().len(), 1);
+        Ok(())
+    }
 }
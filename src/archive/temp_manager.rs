@@ -4,6 +4,7 @@ use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
+use tracing::warn;
 use zip::ZipArchive;
 
 pub struct TempFileManager {
@@ -166,7 +167,7 @@ impl TempFileManager {
 impl Drop for TempFileManager {
     fn drop(&mut self) {
         if let Err(e) = self.cleanup() {
-            eprintln!("Warning: Failed to cleanup temporary files: {}", e);
+            warn!(error = %e, "Failed to clean up temporary files");
         }
     }
 }
@@ -1,5 +1,11 @@
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use crate::error::{ProcessingError, Result};
+use crate::models::{DateFilter, StationSelection};
+use crate::processors::{ExitCondition, SpatialQualityControl, TemporalQualityControl};
+use crate::utils::constants::DEFAULT_ROW_GROUP_SIZE;
+use crate::utils::generate_report_filename;
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand};
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "ecad-processor")]
@@ -12,8 +18,20 @@ pub struct Cli {
     #[arg(short, long, global = true, help = "Enable verbose logging")]
     pub verbose: bool,
 
-    #[arg(long, global = true, help = "Log file path")]
+    #[arg(
+        long,
+        global = true,
+        help = "Append log lines to this file instead of stderr"
+    )]
     pub log_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        default_value = "text",
+        help = "Log line format: text or json"
+    )]
+    pub log_format: String,
 }
 
 #[derive(Subcommand)]
@@ -30,20 +48,82 @@ pub enum Commands {
         )]
         output_file: Option<PathBuf>,
 
-        #[arg(short, long, default_value = "snappy")]
+        #[arg(
+            short,
+            long,
+            default_value = "snappy",
+            help = "Compression: snappy, gzip[:level], lz4, zstd[:level] or none"
+        )]
         compression: String,
 
-        #[arg(short, long)]
-        station_id: Option<u32>,
+        #[command(flatten)]
+        selection: StationSelectionArgs,
+
+        #[command(flatten)]
+        dates: DateFilterArgs,
+
+        #[command(flatten)]
+        spatial_qc: SpatialQcArgs,
+
+        #[command(flatten)]
+        temporal_qc: TemporalQcArgs,
+
+        #[command(flatten)]
+        run_report: RunReportArgs,
+
+        #[arg(
+            long,
+            help = "Physical validity limits file (TOML, YAML or JSON) [default: built-in UK/Ireland limits]"
+        )]
+        qc_rules: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Keep missing and rejected observations as rows with null values and a missing_reason"
+        )]
+        keep_missing: bool,
 
         #[arg(long, default_value = "false")]
         validate_only: bool,
 
-        #[arg(long, default_value_t = num_cpus::get())]
+        #[arg(
+            long,
+            default_value_t = num_cpus::get(),
+            help = "Worker threads for parsing archive entries"
+        )]
         max_workers: usize,
 
-        #[arg(long, default_value = "1000")]
+        #[arg(
+            long,
+            default_value_t = DEFAULT_ROW_GROUP_SIZE,
+            help = "Records per write batch and Parquet row group"
+        )]
         chunk_size: usize,
+
+        #[arg(
+            long,
+            hide = true,
+            help = "Deprecated: has no effect, processing always streams station by station"
+        )]
+        streaming: bool,
+
+        #[arg(
+            long,
+            help = "Write a Hive-partitioned dataset directory keyed on e.g. 'country,year' or 'station'"
+        )]
+        partition_by: Option<String>,
+
+        #[arg(
+            long,
+            help = "Write a per-station, per-metric completeness report (.json or .parquet)"
+        )]
+        completeness_report: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Drop stations whose most complete metric is below this percentage"
+        )]
+        min_completeness: Option<f64>,
     },
 
     /// Process all zip files in directory and combine into unified dataset
@@ -58,21 +138,83 @@ pub enum Commands {
         )]
         output_file: Option<PathBuf>,
 
-        #[arg(short, long, default_value = "snappy")]
+        #[arg(
+            short,
+            long,
+            default_value = "snappy",
+            help = "Compression: snappy, gzip[:level], lz4, zstd[:level] or none"
+        )]
         compression: String,
 
-        #[arg(short, long)]
-        station_id: Option<u32>,
+        #[command(flatten)]
+        selection: StationSelectionArgs,
+
+        #[command(flatten)]
+        dates: DateFilterArgs,
+
+        #[command(flatten)]
+        spatial_qc: SpatialQcArgs,
+
+        #[command(flatten)]
+        temporal_qc: TemporalQcArgs,
+
+        #[command(flatten)]
+        run_report: RunReportArgs,
+
+        #[arg(
+            long,
+            help = "Physical validity limits file (TOML, YAML or JSON) [default: built-in UK/Ireland limits]"
+        )]
+        qc_rules: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Keep missing and rejected observations as rows with null values and a missing_reason"
+        )]
+        keep_missing: bool,
 
         #[arg(long, default_value = "false")]
         validate_only: bool,
 
-        #[arg(long, default_value_t = num_cpus::get())]
+        #[arg(
+            long,
+            default_value_t = num_cpus::get(),
+            help = "Worker threads for parsing archive entries"
+        )]
         max_workers: usize,
 
-        #[arg(long, default_value = "1000")]
+        #[arg(
+            long,
+            default_value_t = DEFAULT_ROW_GROUP_SIZE,
+            help = "Records per write batch and Parquet row group"
+        )]
         chunk_size: usize,
 
+        #[arg(
+            long,
+            hide = true,
+            help = "Deprecated: has no effect, processing always streams station by station"
+        )]
+        streaming: bool,
+
+        #[arg(
+            long,
+            help = "Write a Hive-partitioned dataset directory keyed on e.g. 'country,year' or 'station'"
+        )]
+        partition_by: Option<String>,
+
+        #[arg(
+            long,
+            help = "Write a per-station, per-metric completeness report (.json or .parquet)"
+        )]
+        completeness_report: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Drop stations whose most complete metric is below this percentage"
+        )]
+        min_completeness: Option<f64>,
+
         #[arg(
             long,
             help = "Filter to specific file pattern (e.g., 'UK_ALL_')",
@@ -81,6 +223,414 @@ pub enum Commands {
         file_pattern: String,
     },
 
+    /// Apply a newer ECA&D release to an existing Parquet dataset
+    Update {
+        #[arg(short, long, help = "Existing weather Parquet dataset")]
+        dataset: PathBuf,
+
+        #[arg(short, long, help = "Newer zip archive release")]
+        input_archive: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            help = "Output Parquet file path [default: overwrite the existing dataset]"
+        )]
+        output_file: Option<PathBuf>,
+
+        #[arg(long, help = "Changelog JSON path [default: {output}_changelog.json]")]
+        changelog: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Physical validity limits file (TOML, YAML or JSON) [default: built-in UK/Ireland limits]"
+        )]
+        qc_rules: Option<PathBuf>,
+
+        #[arg(
+            short,
+            long,
+            default_value = "snappy",
+            help = "Compression: snappy, gzip[:level], lz4, zstd[:level] or none"
+        )]
+        compression: String,
+
+        #[arg(
+            long,
+            default_value_t = num_cpus::get(),
+            help = "Worker threads for parsing archive entries"
+        )]
+        max_workers: usize,
+
+        #[arg(
+            long,
+            default_value_t = DEFAULT_ROW_GROUP_SIZE,
+            help = "Records per write batch and Parquet row group"
+        )]
+        chunk_size: usize,
+    },
+
+    /// Compute ETCCDI climate extremes indices from a weather dataset
+    Indices {
+        #[arg(
+            short,
+            long,
+            help = "Weather Parquet file or partitioned dataset directory"
+        )]
+        input: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            help = "Output Parquet file path [default: {input}_indices.parquet]"
+        )]
+        output_file: Option<PathBuf>,
+
+        #[arg(
+            short,
+            long,
+            default_value = "annual",
+            help = "Aggregation period: annual, monthly or seasonal"
+        )]
+        period: String,
+
+        #[arg(
+            long,
+            default_value = "1961-1990",
+            help = "Base period for percentile thresholds (TX90p, TN10p, R95p)"
+        )]
+        base_period: String,
+
+        #[arg(long, help = "Only compute indices for this station")]
+        station_id: Option<u32>,
+
+        #[arg(
+            short,
+            long,
+            default_value = "snappy",
+            help = "Compression: snappy, gzip[:level], lz4, zstd[:level] or none"
+        )]
+        compression: String,
+    },
+
+    /// Aggregate daily weather records to monthly, seasonal or annual values
+    Aggregate {
+        #[arg(
+            short,
+            long,
+            help = "Weather Parquet file or partitioned dataset directory"
+        )]
+        input: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            help = "Output Parquet file path [default: {input}_{period}.parquet]"
+        )]
+        output_file: Option<PathBuf>,
+
+        #[arg(
+            short,
+            long,
+            default_value = "monthly",
+            help = "Aggregation period: monthly, seasonal or annual"
+        )]
+        period: String,
+
+        #[arg(
+            long,
+            default_value_t = 1,
+            help = "Valid days an element needs in a period for its aggregate to be reported"
+        )]
+        min_valid_days: u32,
+
+        #[arg(
+            long,
+            default_value = "suspect",
+            help = "Lowest quality counted: valid, suspect (drops invalid) or any"
+        )]
+        min_quality: String,
+
+        #[arg(long, help = "Only aggregate this station")]
+        station_id: Option<u32>,
+
+        #[arg(
+            short,
+            long,
+            default_value = "snappy",
+            help = "Compression: snappy, gzip[:level], lz4, zstd[:level] or none"
+        )]
+        compression: String,
+    },
+
+    /// Compute climatological normals and optional anomalies from a weather dataset
+    Normals {
+        #[arg(
+            short,
+            long,
+            help = "Weather Parquet file or partitioned dataset directory"
+        )]
+        input: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            help = "Output normals Parquet path [default: {input}_normals.parquet]"
+        )]
+        output_file: Option<PathBuf>,
+
+        #[arg(
+            long,
+            default_value = "1991-2020",
+            help = "Base period, e.g. the WMO 1961-1990 or 1991-2020 normals"
+        )]
+        base_period: String,
+
+        #[arg(
+            long,
+            default_value = "month",
+            help = "Normals resolution: month or day-of-year"
+        )]
+        granularity: String,
+
+        #[arg(
+            long,
+            help = "Years of valid data a normal needs [default: 80% of the base period]"
+        )]
+        min_years: Option<u32>,
+
+        #[arg(long, help = "Only compute normals for this station")]
+        station_id: Option<u32>,
+
+        #[arg(
+            long,
+            help = "Also write the weather records with temp_avg_anomaly and precip_anomaly_pct columns"
+        )]
+        anomalies_output: Option<PathBuf>,
+
+        #[arg(
+            short,
+            long,
+            default_value = "snappy",
+            help = "Compression: snappy, gzip[:level], lz4, zstd[:level] or none"
+        )]
+        compression: String,
+    },
+
+    /// Fill short gaps in station series, marking each estimate with its method
+    Infill {
+        #[arg(
+            short,
+            long,
+            help = "Weather Parquet file or partitioned dataset directory"
+        )]
+        input: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            help = "Output Parquet path [default: {input}_infilled.parquet]"
+        )]
+        output_file: Option<PathBuf>,
+
+        #[arg(
+            long,
+            default_value = "interpolation,neighbour,climatology",
+            help = "Comma-separated methods in order of preference: interpolation, neighbour, climatology"
+        )]
+        methods: String,
+
+        #[arg(
+            long,
+            default_value_t = 3,
+            help = "Longest temperature gap bridged by linear interpolation (days)"
+        )]
+        max_interpolation_days: usize,
+
+        #[arg(
+            long,
+            default_value_t = 10,
+            help = "Longest gap filled by any method (days)"
+        )]
+        max_gap_days: usize,
+
+        #[arg(
+            long,
+            default_value_t = 5,
+            help = "Nearest stations considered for neighbour regression"
+        )]
+        neighbours: usize,
+
+        #[arg(
+            long,
+            default_value_t = 150.0,
+            help = "Maximum neighbour distance (km)"
+        )]
+        max_distance: f64,
+
+        #[arg(
+            long,
+            default_value_t = 0.7,
+            help = "Weakest daily correlation a neighbour regression may have"
+        )]
+        min_correlation: f64,
+
+        #[arg(
+            short,
+            long,
+            default_value = "snappy",
+            help = "Compression: snappy, gzip[:level], lz4, zstd[:level] or none"
+        )]
+        compression: String,
+    },
+
+    /// Interpolate station values onto a regular latitude/longitude grid
+    Grid {
+        #[arg(
+            short,
+            long,
+            help = "Weather Parquet file or partitioned dataset directory"
+        )]
+        input: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            help = "Output Parquet path [default: {input}_grid.parquet]"
+        )]
+        output_file: Option<PathBuf>,
+
+        #[arg(
+            long,
+            default_value = "temp_avg",
+            help = "Comma-separated value columns to grid, e.g. 'temp_min,temp_max,precipitation'"
+        )]
+        metrics: String,
+
+        #[arg(
+            long,
+            allow_hyphen_values = true,
+            help = "Grid extent as MIN_LAT,MIN_LON,MAX_LAT,MAX_LON [default: covers all stations]"
+        )]
+        bbox: Option<String>,
+
+        #[arg(long, default_value_t = 0.25, help = "Cell size (degrees)")]
+        resolution: f64,
+
+        #[arg(long, default_value_t = 2.0, help = "Inverse-distance weighting power")]
+        power: f64,
+
+        #[arg(
+            long,
+            default_value_t = 100.0,
+            help = "Furthest station weighted into a cell (km)"
+        )]
+        max_distance: f64,
+
+        #[arg(
+            long,
+            default_value_t = 8,
+            help = "Nearest reporting stations weighted into a cell"
+        )]
+        max_stations: usize,
+
+        #[arg(
+            long,
+            default_value_t = 1,
+            help = "Reporting stations a cell needs before it gets a value"
+        )]
+        min_stations: usize,
+
+        #[arg(
+            long,
+            requires = "elevation_file",
+            help = "Adjust temperatures for elevation at this lapse rate (°C/km, e.g. 6.5)"
+        )]
+        lapse_rate: Option<f64>,
+
+        #[arg(
+            long,
+            help = "CSV of latitude,longitude,elevation (m) giving each cell's surface elevation"
+        )]
+        elevation_file: Option<PathBuf>,
+
+        #[command(flatten)]
+        dates: DateFilterArgs,
+
+        #[arg(
+            short,
+            long,
+            default_value = "snappy",
+            help = "Compression: snappy, gzip[:level], lz4, zstd[:level] or none"
+        )]
+        compression: String,
+    },
+
+    /// Detect breakpoints in station series with homogeneity tests
+    Homogeneity {
+        #[arg(
+            short,
+            long,
+            help = "Weather Parquet file or partitioned dataset directory"
+        )]
+        input: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            help = "Output breakpoint Parquet path [default: {input}_breakpoints.parquet]"
+        )]
+        output_file: Option<PathBuf>,
+
+        #[arg(
+            long,
+            default_value = "annual",
+            help = "Series to test: annual, seasonal or monthly"
+        )]
+        period: String,
+
+        #[arg(
+            long,
+            default_value = "snht,pettitt,buishand",
+            help = "Comma-separated tests: snht, pettitt, buishand"
+        )]
+        tests: String,
+
+        #[arg(
+            long,
+            default_value_t = 10,
+            help = "Complete periods a series needs before it is tested"
+        )]
+        min_length: usize,
+
+        #[arg(
+            long,
+            help = "Sources Parquet table to link breaks to [default: {input}_sources.parquet when present]"
+        )]
+        sources: Option<PathBuf>,
+
+        #[arg(
+            long,
+            default_value_t = 365,
+            help = "Days from a break within which a source change is linked to it"
+        )]
+        link_window_days: i64,
+
+        #[arg(long, help = "Only write breakpoints significant at the 5% level")]
+        significant_only: bool,
+
+        #[arg(long, help = "Only test this station")]
+        station_id: Option<u32>,
+
+        #[arg(
+            short,
+            long,
+            default_value = "snappy",
+            help = "Compression: snappy, gzip[:level], lz4, zstd[:level] or none"
+        )]
+        compression: String,
+    },
+
     /// Validate archive data without processing
     Validate {
         #[arg(short, long, help = "Input zip archive file")]
@@ -90,9 +640,9 @@ pub enum Commands {
         max_workers: usize,
     },
 
-    /// Display information about a Parquet file
+    /// Display information about a Parquet file or partitioned dataset
     Info {
-        #[arg(short, long)]
+        #[arg(short, long, help = "Parquet file or partitioned dataset directory")]
         file: PathBuf,
 
         #[arg(short, long, default_value = "10")]
@@ -106,3 +656,246 @@ pub enum Commands {
         analysis_limit: usize,
     },
 }
+
+/// Station filters applied to `stations.txt` before any data files are read
+#[derive(Args, Debug, Clone, Default)]
+pub struct StationSelectionArgs {
+    #[arg(
+        short = 's',
+        long = "station-id",
+        value_delimiter = ',',
+        help = "Only process these station IDs (comma-separated)"
+    )]
+    pub station_ids: Vec<u32>,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Only process stations in these country codes (comma-separated)"
+    )]
+    pub country: Vec<String>,
+
+    #[arg(
+        long,
+        allow_hyphen_values = true,
+        help = "Only process stations inside MIN_LAT,MIN_LON,MAX_LAT,MAX_LON"
+    )]
+    pub bbox: Option<String>,
+
+    #[arg(
+        long,
+        allow_hyphen_values = true,
+        help = "Only process stations within KM of a point: LAT,LON,KM"
+    )]
+    pub radius: Option<String>,
+
+    #[arg(
+        long,
+        allow_negative_numbers = true,
+        help = "Minimum station elevation in metres"
+    )]
+    pub min_elevation: Option<i32>,
+
+    #[arg(
+        long,
+        allow_negative_numbers = true,
+        help = "Maximum station elevation in metres"
+    )]
+    pub max_elevation: Option<i32>,
+
+    #[arg(long, help = "Only process stations whose name matches this regex")]
+    pub name_pattern: Option<String>,
+}
+
+impl StationSelectionArgs {
+    pub fn to_selection(&self) -> Result<StationSelection> {
+        let mut selection =
+            StationSelection::new().with_elevation_range(self.min_elevation, self.max_elevation)?;
+        if !self.station_ids.is_empty() {
+            selection = selection.with_station_ids(self.station_ids.iter().copied());
+        }
+        if !self.country.is_empty() {
+            selection = selection.with_countries(&self.country);
+        }
+        if let Some(bbox) = &self.bbox {
+            selection = selection.with_bounding_box(bbox.parse()?);
+        }
+        if let Some(radius) = &self.radius {
+            selection = selection.with_radius(radius.parse()?);
+        }
+        if let Some(pattern) = &self.name_pattern {
+            selection = selection.with_name_pattern(pattern)?;
+        }
+        Ok(selection)
+    }
+}
+
+/// Date window applied to data lines before records are created
+#[derive(Args, Debug, Clone, Default)]
+pub struct DateFilterArgs {
+    #[arg(
+        long,
+        help = "Only read observations on or after this date (YYYY-MM-DD)"
+    )]
+    pub start_date: Option<NaiveDate>,
+
+    #[arg(
+        long,
+        help = "Only read observations on or before this date (YYYY-MM-DD)"
+    )]
+    pub end_date: Option<NaiveDate>,
+
+    #[arg(
+        long,
+        help = "Only read these months, e.g. '6,7,8', '6-8', 'JJA' or 'DJF,MAM'"
+    )]
+    pub months: Option<String>,
+
+    #[arg(
+        long,
+        help = "Only read this day-of-year range, e.g. '152-243' or '335-59'"
+    )]
+    pub day_of_year: Option<String>,
+}
+
+impl DateFilterArgs {
+    pub fn to_filter(&self) -> Result<DateFilter> {
+        let mut filter = DateFilter::new().with_date_range(self.start_date, self.end_date)?;
+        if let Some(months) = &self.months {
+            filter = filter.with_months(months.parse()?);
+        }
+        if let Some(day_of_year) = &self.day_of_year {
+            let invalid =
+                || ProcessingError::Config(format!("Invalid day-of-year range: {}", day_of_year));
+            let (first, last) = day_of_year.split_once('-').ok_or_else(invalid)?;
+            filter = filter.with_day_of_year(
+                first.trim().parse().map_err(|_| invalid())?,
+                last.trim().parse().map_err(|_| invalid())?,
+            )?;
+        }
+        Ok(filter)
+    }
+}
+
+/// Machine-readable run report and the outcomes that fail the run
+#[derive(Args, Debug, Clone, Default)]
+pub struct RunReportArgs {
+    #[arg(
+        long,
+        num_args = 0..=1,
+        value_name = "PATH",
+        help = "Write a JSON run report [default path: {output}_report.json]"
+    )]
+    pub report: Option<Option<PathBuf>>,
+
+    #[arg(
+        long,
+        value_name = "CONDITIONS",
+        help = "Exit with status 2 on any of: warnings, invalid-records, temperature-violations, no-records"
+    )]
+    pub fail_on: Option<String>,
+}
+
+impl RunReportArgs {
+    pub fn conditions(&self) -> Result<Vec<ExitCondition>> {
+        self.fail_on
+            .as_deref()
+            .map_or(Ok(Vec::new()), ExitCondition::parse_list)
+    }
+
+    /// Where to write the report for `output_file`, if one was requested
+    pub fn path(&self, output_file: &Path) -> Option<PathBuf> {
+        self.report.as_ref().map(|path| {
+            path.clone()
+                .unwrap_or_else(|| generate_report_filename(output_file))
+        })
+    }
+}
+
+/// Neighbour-based spatial quality control run before writing
+#[derive(Args, Debug, Clone)]
+pub struct SpatialQcArgs {
+    #[arg(
+        long,
+        help = "Flag temperature and precipitation values that disagree with neighbouring stations"
+    )]
+    pub spatial_qc: bool,
+
+    #[arg(
+        long,
+        default_value_t = 5,
+        help = "Nearest stations used for the spatial estimate"
+    )]
+    pub qc_neighbours: usize,
+
+    #[arg(
+        long,
+        default_value_t = 150.0,
+        help = "Maximum neighbour distance in km"
+    )]
+    pub qc_max_distance: f64,
+
+    #[arg(
+        long,
+        default_value_t = 500,
+        help = "Maximum neighbour elevation difference in metres"
+    )]
+    pub qc_max_elevation_diff: i32,
+}
+
+impl SpatialQcArgs {
+    /// The configured checker, or None when spatial QC is off
+    pub fn to_checker(&self) -> Option<SpatialQualityControl> {
+        self.spatial_qc.then(|| {
+            SpatialQualityControl::new()
+                .with_neighbours(self.qc_neighbours)
+                .with_max_distance_km(self.qc_max_distance)
+                .with_max_elevation_difference(self.qc_max_elevation_diff)
+        })
+    }
+}
+
+/// Per-station time-series quality control
+#[derive(Args, Debug, Clone)]
+pub struct TemporalQcArgs {
+    #[arg(
+        long,
+        help = "Flag spikes, stuck sensors, implausible precipitation runs and Tmax < Tmin"
+    )]
+    pub temporal_qc: bool,
+
+    #[arg(
+        long,
+        default_value_t = 4.0,
+        help = "Spike threshold in standard deviations of the month's day-to-day changes"
+    )]
+    pub qc_spike_factor: f64,
+
+    #[arg(
+        long,
+        default_value_t = 5,
+        help = "Consecutive identical values that mark a stuck sensor"
+    )]
+    pub qc_stuck_days: usize,
+
+    #[arg(
+        long,
+        default_value_t = 60,
+        help = "Longest plausible run of consecutive wet days"
+    )]
+    pub qc_max_wet_run: usize,
+}
+
+impl TemporalQcArgs {
+    /// The configured checker, or None when temporal QC is off
+    pub fn to_checker(&self) -> Result<Option<TemporalQualityControl>> {
+        if !self.temporal_qc {
+            return Ok(None);
+        }
+        let checker = TemporalQualityControl::new()
+            .with_spike_factor(self.qc_spike_factor)?
+            .with_stuck_days(self.qc_stuck_days)?
+            .with_max_wet_run_days(self.qc_max_wet_run);
+        Ok(Some(checker))
+    }
+}
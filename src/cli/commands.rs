@@ -1,227 +1,657 @@
-use crate::analyzers::WeatherAnalyzer;
-use crate::archive::{ArchiveProcessor, MultiArchiveProcessor};
-use crate::cli::args::{Cli, Commands};
-use crate::error::Result;
-use crate::processors::IntegrityChecker;
+use crate::analyzers::{
+    BasePeriod, BreakpointDetector, CellElevations, ClimateIndexCalculator, GridSpec, Gridder,
+    HomogeneityTest, IndexPeriod, MinimumQuality, NormalsCalculator, NormalsGranularity,
+    TemporalAggregator, WeatherAnalyzer,
+};
+use crate::archive::ArchiveProcessor;
+use crate::cli::args::{
+    Cli, Commands, DateFilterArgs, RunReportArgs, SpatialQcArgs, StationSelectionArgs,
+    TemporalQcArgs,
+};
+use crate::error::{ProcessingError, Result};
+use crate::models::weather::InfillMethod;
+use crate::models::{BoundingBox, SourceMetadata, ValidationRules};
+use crate::pipeline::{Pipeline, PipelineProgress};
+use crate::processors::{
+    ArchiveReport, CompletenessReport, DatasetUpdater, ExitCondition, Infiller, IntegrityChecker,
+    RunReport, WarningCode,
+};
 use crate::utils::progress::ProgressReporter;
-use crate::utils::{generate_default_parquet_filename, generate_default_unified_parquet_filename};
-use crate::writers::{ParquetWriter, SchemaType};
+use crate::utils::{
+    format_memory, generate_aggregate_filename, generate_breakpoints_filename,
+    generate_changelog_filename, generate_default_parquet_filename,
+    generate_default_unified_parquet_filename, generate_grid_filename, generate_indices_filename,
+    generate_infilled_filename, generate_normals_filename, generate_sources_filename, init_logging,
+    peak_memory_bytes, LogFormat,
+};
+use crate::writers::{
+    write_breakpoints, write_climate_indices, write_climate_normals, write_completeness_report,
+    write_period_aggregates, write_weather_records_with_anomalies, GridStreamWriter, ParquetWriter,
+    PartitionKey, SchemaType,
+};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{error, warn};
 
 pub async fn run(cli: Cli) -> Result<()> {
-    // Initialize logging if verbose
-    if cli.verbose {
-        println!("Verbose logging enabled");
-    }
+    let log_format: LogFormat = cli.log_format.parse()?;
+    init_logging(cli.verbose, log_format, cli.log_file.as_deref())?;
 
     match cli.command {
         Commands::Process {
             input_archive,
             output_file,
-            compression: _,
-            station_id,
+            compression,
+            selection,
+            dates,
+            spatial_qc,
+            temporal_qc,
+            qc_rules,
+            keep_missing,
             validate_only,
             max_workers,
             chunk_size,
+            streaming,
+            partition_by,
+            completeness_report,
+            min_completeness,
+            run_report,
         } => {
             println!("Processing weather data from archive...");
             println!("Input archive: {}", input_archive.display());
 
-            // Use default filename if not specified
-            let output_file = output_file.unwrap_or_else(generate_default_parquet_filename);
-
-            println!("Output file: {}", output_file.display());
-            println!("Workers: {}, Chunk size: {}", max_workers, chunk_size);
-
-            let progress = ProgressReporter::new_spinner("Inspecting archive...", false);
-
-            // Create archive processor
-            let processor = ArchiveProcessor::from_zip(&input_archive).await?;
+            let options = ProcessOptions {
+                // Use default filename if not specified
+                output_file: output_file.unwrap_or_else(generate_default_parquet_filename),
+                compression,
+                selection,
+                dates,
+                spatial_qc,
+                temporal_qc,
+                qc_rules,
+                keep_missing,
+                validate_only,
+                max_workers,
+                chunk_size,
+                streaming,
+                partition_by,
+                completeness_report,
+                min_completeness,
+                run_report,
+            };
+            process_archives(
+                "process",
+                Pipeline::new().with_archive(input_archive),
+                options,
+            )
+            .await?;
+        }
 
-            // Display archive metadata
-            println!("\n{}", processor.metadata().display_summary());
+        Commands::ProcessDirectory {
+            input_dir,
+            output_file,
+            compression,
+            selection,
+            dates,
+            spatial_qc,
+            temporal_qc,
+            qc_rules,
+            keep_missing,
+            validate_only,
+            max_workers,
+            chunk_size,
+            streaming,
+            partition_by,
+            completeness_report,
+            min_completeness,
+            file_pattern,
+            run_report,
+        } => {
+            println!("Processing weather data from directory...");
+            println!("Input directory: {}", input_dir.display());
+            let pattern = if file_pattern.is_empty() {
+                None
+            } else {
+                println!("File pattern filter: '{}'", file_pattern);
+                Some(file_pattern.as_str())
+            };
 
-            progress.set_message("Processing data...");
+            let options = ProcessOptions {
+                // Use default unified filename if not specified
+                output_file: output_file.unwrap_or_else(generate_default_unified_parquet_filename),
+                compression,
+                selection,
+                dates,
+                spatial_qc,
+                temporal_qc,
+                qc_rules,
+                keep_missing,
+                validate_only,
+                max_workers,
+                chunk_size,
+                streaming,
+                partition_by,
+                completeness_report,
+                min_completeness,
+                run_report,
+            };
+            let pipeline = Pipeline::new().with_archive_directory(&input_dir, pattern);
+            process_archives("process-directory", pipeline, options).await?;
+        }
 
-            // Process data
-            let (records, integrity_report) = processor.process_data(&input_archive).await?;
+        Commands::Update {
+            dataset,
+            input_archive,
+            output_file,
+            changelog,
+            qc_rules,
+            compression,
+            max_workers,
+            chunk_size,
+        } => {
+            println!("Updating weather dataset from newer release...");
+            println!("Existing dataset: {}", dataset.display());
+            println!("Input archive: {}", input_archive.display());
 
-            progress.finish_with_message(&format!("Processed {} records", records.len()));
+            // Default to replacing the existing dataset in place
+            let output_file = output_file.unwrap_or_else(|| dataset.clone());
+            let changelog_file =
+                changelog.unwrap_or_else(|| generate_changelog_filename(&output_file));
 
-            // Print integrity report
-            let checker = IntegrityChecker::new();
-            println!("\n{}", checker.generate_summary(&integrity_report));
+            println!("Output file: {}", output_file.display());
 
-            if validate_only {
-                println!("Validation complete - no output file written");
-                return Ok(());
+            // The update is written as one file, which cannot replace a directory
+            if output_file.is_dir() {
+                return Err(ProcessingError::Config(format!(
+                    "Cannot write the update over partitioned dataset directory {}; pass --output-file to write a single Parquet file",
+                    output_file.display()
+                )));
             }
 
-            // Filter by station if specified
-            let filtered_records = if let Some(id) = station_id {
-                records.into_iter().filter(|r| r.station_id == id).collect()
+            // Fail on a bad compression setting or rules file before doing any work
+            let writer = create_writer(&compression, chunk_size)?;
+            let validation_rules = load_validation_rules(qc_rules.as_deref())?;
+
+            let progress = ProgressReporter::new_spinner("Reading existing dataset...", false);
+            let existing = writer.read_weather_records(&dataset)?;
+            let existing_sources_file = generate_sources_filename(&dataset);
+            let existing_sources = if existing_sources_file.exists() {
+                writer.read_sources(&existing_sources_file)?
             } else {
-                records
+                Vec::new()
             };
 
-            if filtered_records.is_empty() {
-                println!("No records to write");
-                return Ok(());
+            progress.set_message("Processing new release...");
+            let mut processor = ArchiveProcessor::from_zip(&input_archive)
+                .await?
+                .with_max_workers(max_workers)?
+                .with_validation_rules(Arc::clone(&validation_rules));
+            println!("\n{}", processor.metadata().display_summary());
+            let release_sources = processor.read_sources(&input_archive)?;
+            let (incoming, _) = processor.process_data(&input_archive).await?;
+
+            progress.set_message("Comparing records...");
+            let (mut records, changelog) = DatasetUpdater::new().apply(existing, incoming)?;
+            // Merged records are reclassified with the same limits as the release
+            for record in &mut records {
+                record.perform_physical_validation_with(&validation_rules);
             }
 
-            // Write to Parquet
-            println!(
-                "Writing {} records to Parquet file...",
-                filtered_records.len()
-            );
+            progress.finish_with_message(&format!("Compared {} records", records.len()));
+            println!("\n{}", changelog.summary());
 
-            // Create parent directory if it doesn't exist
+            // Write next to the target and rename, so a failed write never
+            // leaves the existing dataset truncated
             if let Some(parent) = output_file.parent() {
                 std::fs::create_dir_all(parent)?;
             }
-
-            let writer = ParquetWriter::new();
-            writer.write_weather_records_batched(&filtered_records, &output_file, 10000)?;
+            let staging_file = output_file.with_extension("parquet.tmp");
+            writer.write_weather_records_batched(&records, &staging_file, chunk_size)?;
+            std::fs::rename(&staging_file, &output_file)?;
 
             println!(
                 "Successfully wrote {} weather records to {}",
-                filtered_records.len(),
+                records.len(),
                 output_file.display()
             );
 
-            if !filtered_records.is_empty() {
-                let sample_record = &filtered_records[0];
-                println!(
-                    "Sample record: Station {} on {}",
-                    sample_record.station_id, sample_record.date
-                );
-                println!("Available metrics: {:?}", sample_record.available_metrics());
-            }
+            // Carried-over records still need their series; the release's
+            // metadata replaces that of the same SOUID
+            let mut sources: BTreeMap<u32, SourceMetadata> = existing_sources
+                .into_iter()
+                .map(|source| (source.souid, source))
+                .collect();
+            sources.extend(release_sources.into_iter().map(|s| (s.souid, s)));
+            let sources: Vec<SourceMetadata> = sources.into_values().collect();
+            write_sources_table(&writer, &sources, &output_file)?;
+
+            changelog.write_json(&changelog_file)?;
+            println!(
+                "Wrote {} revisions to {}",
+                changelog.revisions.len(),
+                changelog_file.display()
+            );
 
-            println!("Processing complete!");
+            report_peak_memory();
+            println!("Update complete!");
         }
 
-        Commands::ProcessDirectory {
-            input_dir,
+        Commands::Indices {
+            input,
             output_file,
-            compression: _compression,
+            period,
+            base_period,
             station_id,
-            validate_only,
-            max_workers: _max_workers,
-            chunk_size: _chunk_size,
-            file_pattern,
+            compression,
         } => {
-            println!("Processing weather data from directory...");
-            println!("Input directory: {}", input_dir.display());
+            // Fail on bad options before reading the dataset
+            let period: IndexPeriod = period.parse()?;
+            let base_period: BasePeriod = base_period.parse()?;
+            let writer = ParquetWriter::new().with_compression(&compression)?;
+            let output_file = output_file.unwrap_or_else(|| generate_indices_filename(&input));
+
+            println!("Computing {} climate indices...", period);
+            println!("Input: {}", input.display());
+            println!(
+                "Base period: {}-{}",
+                base_period.start_year, base_period.end_year
+            );
 
-            // Use default unified filename if not specified
-            let output_file = output_file.unwrap_or_else(generate_default_unified_parquet_filename);
+            let progress = ProgressReporter::new_spinner("Reading weather records...", false);
+            let mut records = writer.read_weather_records(&input)?;
+            if let Some(station_id) = station_id {
+                records.retain(|r| r.station_id == station_id);
+            }
+            if records.is_empty() {
+                return Err(ProcessingError::Config(
+                    "No weather records to compute indices from".to_string(),
+                ));
+            }
 
-            println!("Output file: {}", output_file.display());
+            progress.set_message("Computing indices...");
+            let values = ClimateIndexCalculator::new(period)
+                .with_base_period(base_period)
+                .compute(&records);
+            progress.finish_with_message(&format!(
+                "Computed {} index values from {} records",
+                values.len(),
+                records.len()
+            ));
 
-            if !file_pattern.is_empty() {
-                println!("File pattern filter: '{}'", file_pattern);
+            if let Some(parent) = output_file.parent() {
+                std::fs::create_dir_all(parent)?;
             }
+            write_climate_indices(&writer, &values, &output_file)?;
+            println!(
+                "Successfully wrote {} index values to {}",
+                values.len(),
+                output_file.display()
+            );
+        }
 
-            let progress =
-                ProgressReporter::new_spinner("Scanning directory for archives...", false);
-
-            // Create multi-archive processor
-            let pattern = if file_pattern.is_empty() {
-                None
-            } else {
-                Some(file_pattern.as_str())
-            };
-            let processor = MultiArchiveProcessor::from_directory(&input_dir, pattern, 4).await?;
+        Commands::Aggregate {
+            input,
+            output_file,
+            period,
+            min_valid_days,
+            min_quality,
+            station_id,
+            compression,
+        } => {
+            // Fail on bad options before reading the dataset
+            let period: IndexPeriod = period.parse()?;
+            let min_quality: MinimumQuality = min_quality.parse()?;
+            let writer = ParquetWriter::new().with_compression(&compression)?;
+            let output_file = output_file
+                .unwrap_or_else(|| generate_aggregate_filename(&input, &period.to_string()));
+
+            println!("Aggregating weather records to {} values...", period);
+            println!("Input: {}", input.display());
+            println!(
+                "Minimum valid days: {}, Minimum quality: {}",
+                min_valid_days, min_quality
+            );
 
-            // Display archive summary
-            println!("\n{}", processor.get_summary());
+            let progress = ProgressReporter::new_spinner("Reading weather records...", false);
+            let mut records = writer.read_weather_records(&input)?;
+            if let Some(station_id) = station_id {
+                records.retain(|r| r.station_id == station_id);
+            }
+            if records.is_empty() {
+                return Err(ProcessingError::Config(
+                    "No weather records to aggregate".to_string(),
+                ));
+            }
 
-            progress.set_message("Processing all archives...");
+            progress.set_message("Aggregating...");
+            let aggregates = TemporalAggregator::new(period)
+                .with_min_valid_days(min_valid_days)
+                .with_min_quality(min_quality)
+                .aggregate(&records);
+            progress.finish_with_message(&format!(
+                "Aggregated {} records into {} periods",
+                records.len(),
+                aggregates.len()
+            ));
 
-            // Process unified data
-            let (records, integrity_report, composition) =
-                processor.process_unified_data(station_id).await?;
+            if let Some(parent) = output_file.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            write_period_aggregates(&writer, &aggregates, &output_file)?;
+            println!(
+                "Successfully wrote {} {} aggregates to {}",
+                aggregates.len(),
+                period,
+                output_file.display()
+            );
+        }
 
-            progress.finish_with_message(&format!("Processed {} unified records", records.len()));
+        Commands::Normals {
+            input,
+            output_file,
+            base_period,
+            granularity,
+            min_years,
+            station_id,
+            anomalies_output,
+            compression,
+        } => {
+            // Fail on bad options before reading the dataset
+            let base_period: BasePeriod = base_period.parse()?;
+            let granularity: NormalsGranularity = granularity.parse()?;
+            let writer = ParquetWriter::new().with_compression(&compression)?;
+            let output_file = output_file.unwrap_or_else(|| generate_normals_filename(&input));
+
+            println!("Computing {} climate normals...", granularity);
+            println!("Input: {}", input.display());
+            println!("Base period: {}", base_period);
+
+            let progress = ProgressReporter::new_spinner("Reading weather records...", false);
+            let mut records = writer.read_weather_records(&input)?;
+            if let Some(station_id) = station_id {
+                records.retain(|r| r.station_id == station_id);
+            }
+            if records.is_empty() {
+                return Err(ProcessingError::Config(
+                    "No weather records to compute normals from".to_string(),
+                ));
+            }
 
-            // Print integrity report
-            let checker = IntegrityChecker::new();
-            println!("\n{}", checker.generate_summary(&integrity_report));
+            progress.set_message("Computing normals...");
+            let mut calculator = NormalsCalculator::new(base_period).with_granularity(granularity);
+            if let Some(min_years) = min_years {
+                calculator = calculator.with_min_years(min_years);
+            }
+            let table = calculator.compute(&records);
+            let available = table.normals.iter().filter(|n| n.normal.is_some()).count();
+            progress.finish_with_message(&format!(
+                "Computed {} of {} normals from {} records",
+                available,
+                table.normals.len(),
+                records.len()
+            ));
+            if available == 0 {
+                println!(
+                    "Warning: no station has enough valid years in {} for a normal",
+                    base_period
+                );
+            }
 
-            if validate_only {
-                println!("Validation complete - no output file written");
-                return Ok(());
+            if let Some(parent) = output_file.parent() {
+                std::fs::create_dir_all(parent)?;
             }
+            write_climate_normals(&writer, &table, &output_file)?;
+            println!(
+                "Successfully wrote {} normals to {}",
+                table.normals.len(),
+                output_file.display()
+            );
 
-            // Filter records if needed (already done in processor, but for consistency)
-            let filtered_records = records;
+            if let Some(anomalies_output) = anomalies_output {
+                let anomalies = table.anomalies(&records);
+                if let Some(parent) = anomalies_output.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                write_weather_records_with_anomalies(
+                    &writer,
+                    &records,
+                    &anomalies,
+                    &anomalies_output,
+                )?;
+                println!(
+                    "Wrote {} records with anomaly columns to {}",
+                    records.len(),
+                    anomalies_output.display()
+                );
+            }
+        }
 
-            if filtered_records.is_empty() {
-                println!("No records to write");
-                return Ok(());
+        Commands::Infill {
+            input,
+            output_file,
+            methods,
+            max_interpolation_days,
+            max_gap_days,
+            neighbours,
+            max_distance,
+            min_correlation,
+            compression,
+        } => {
+            // Fail on bad options before reading the dataset
+            let methods = methods
+                .split(',')
+                .filter(|m| !m.trim().is_empty())
+                .map(str::parse)
+                .collect::<Result<Vec<InfillMethod>>>()?;
+            let infiller = Infiller::new()
+                .with_methods(methods)?
+                .with_max_interpolation_days(max_interpolation_days)
+                .with_max_gap_days(max_gap_days)
+                .with_neighbours(neighbours)
+                .with_max_distance_km(max_distance)
+                .with_min_correlation(min_correlation)?;
+            let writer = ParquetWriter::new().with_compression(&compression)?;
+            let output_file = output_file.unwrap_or_else(|| generate_infilled_filename(&input));
+
+            println!("Infilling gaps of up to {} days...", max_gap_days);
+            println!("Input: {}", input.display());
+
+            let progress = ProgressReporter::new_spinner("Reading weather records...", false);
+            let records = writer.read_weather_records(&input)?;
+            if records.is_empty() {
+                return Err(ProcessingError::Config(
+                    "No weather records to infill".to_string(),
+                ));
             }
 
-            // Write to Parquet
-            println!(
-                "Writing {} unified records to Parquet file...",
-                filtered_records.len()
-            );
+            progress.set_message("Filling gaps...");
+            let (records, summary) = infiller.infill(records);
+            progress.finish_with_message(&summary.summary());
 
-            // Create parent directory if it doesn't exist
             if let Some(parent) = output_file.parent() {
                 std::fs::create_dir_all(parent)?;
             }
+            writer.write_weather_records(&records, &output_file)?;
+            println!(
+                "Successfully wrote {} records to {}",
+                records.len(),
+                output_file.display()
+            );
+        }
 
-            let writer = ParquetWriter::new();
-            writer.write_weather_records_batched(&filtered_records, &output_file, 10000)?;
+        Commands::Grid {
+            input,
+            output_file,
+            metrics,
+            bbox,
+            resolution,
+            power,
+            max_distance,
+            max_stations,
+            min_stations,
+            lapse_rate,
+            elevation_file,
+            dates,
+            compression,
+        } => {
+            // Fail on bad options before reading the dataset
+            let metrics = Gridder::parse_metrics(&metrics)?;
+            let grid = bbox
+                .as_deref()
+                .map(|bbox| GridSpec::from_bbox(&bbox.parse::<BoundingBox>()?, resolution))
+                .transpose()?;
+            let date_filter = dates.to_filter()?;
+            let writer = ParquetWriter::new().with_compression(&compression)?;
+            let output_file = output_file.unwrap_or_else(|| generate_grid_filename(&input));
 
             println!(
-                "Successfully wrote {} unified weather records to {}",
-                filtered_records.len(),
-                output_file.display()
+                "Gridding {} at {}° resolution...",
+                metrics.join(", "),
+                resolution
             );
+            println!("Input: {}", input.display());
+
+            let progress = ProgressReporter::new_spinner("Reading weather records...", false);
+            let mut records = writer.read_weather_records(&input)?;
+            records.retain(|r| date_filter.matches(r.date));
+            if records.is_empty() {
+                return Err(ProcessingError::Config(
+                    "No weather records to grid".to_string(),
+                ));
+            }
 
-            // Display dataset composition based on actual data
-            println!("Dataset Composition:");
-            println!("  Metrics in Parquet: {:?}", composition.available_metrics);
-            println!("  Total records: {}", composition.total_records);
+            let grid = match grid {
+                Some(grid) => grid,
+                None => GridSpec::covering(&records, resolution)?,
+            };
+            println!(
+                "Grid: {} x {} cells, latitude {} to {}, longitude {} to {}",
+                grid.rows(),
+                grid.cols(),
+                grid.min_latitude,
+                grid.max_latitude,
+                grid.min_longitude,
+                grid.max_longitude
+            );
 
-            println!("Metric Coverage:");
-            if composition.records_with_temperature > 0 {
+            let mut gridder = Gridder::new(grid)
+                .with_metrics(metrics)?
+                .with_power(power)?
+                .with_max_distance_km(max_distance)
+                .with_max_stations(max_stations)
+                .with_min_stations(min_stations);
+            if let (Some(rate), Some(path)) = (lapse_rate, elevation_file) {
+                let elevations = CellElevations::from_csv(&path, &grid)?;
                 println!(
-                    "  Temperature: {}/{} ({:.1}%)",
-                    composition.records_with_temperature,
-                    composition.total_records,
-                    composition.records_with_temperature as f32 / composition.total_records as f32
-                        * 100.0
+                    "Lapse rate {} °C/km, {} of {} cells with a known elevation",
+                    rate,
+                    elevations.known(),
+                    grid.cell_count()
                 );
+                gridder = gridder.with_lapse_rate(rate, elevations)?;
             }
-            if composition.records_with_precipitation > 0 {
-                println!(
-                    "  Precipitation: {}/{} ({:.1}%)",
-                    composition.records_with_precipitation,
-                    composition.total_records,
-                    composition.records_with_precipitation as f32
-                        / composition.total_records as f32
-                        * 100.0
-                );
+
+            if let Some(parent) = output_file.parent() {
+                std::fs::create_dir_all(parent)?;
             }
-            if composition.records_with_wind_speed > 0 {
-                println!(
-                    "  Wind Speed: {}/{} ({:.1}%)",
-                    composition.records_with_wind_speed,
-                    composition.total_records,
-                    composition.records_with_wind_speed as f32 / composition.total_records as f32
-                        * 100.0
-                );
+            progress.set_message("Interpolating...");
+            let mut stream = GridStreamWriter::create(&writer, &output_file)?;
+            let summary = gridder.grid(&records, |values| stream.write_values(values))?;
+            let written = stream.finish()?;
+            progress.finish_with_message(&summary.summary());
+
+            println!(
+                "Successfully wrote {} gridded values to {}",
+                written,
+                output_file.display()
+            );
+        }
+
+        Commands::Homogeneity {
+            input,
+            output_file,
+            period,
+            tests,
+            min_length,
+            sources,
+            link_window_days,
+            significant_only,
+            station_id,
+            compression,
+        } => {
+            // Fail on bad options before reading the dataset
+            let period: IndexPeriod = period.parse()?;
+            let tests = HomogeneityTest::parse_list(&tests)?;
+            let writer = ParquetWriter::new().with_compression(&compression)?;
+            let output_file = output_file.unwrap_or_else(|| generate_breakpoints_filename(&input));
+            let sources_file = sources.or_else(|| {
+                let companion = generate_sources_filename(&input);
+                companion.exists().then_some(companion)
+            });
+
+            println!("Testing {} series for breakpoints...", period);
+            println!("Input: {}", input.display());
+            let test_names: Vec<String> = tests.iter().map(|t| t.to_string()).collect();
+            println!("Tests: {}", test_names.join(", "));
+
+            let progress = ProgressReporter::new_spinner("Reading weather records...", false);
+            let mut records = writer.read_weather_records(&input)?;
+            if let Some(station_id) = station_id {
+                records.retain(|r| r.station_id == station_id);
             }
+            if records.is_empty() {
+                return Err(ProcessingError::Config(
+                    "No weather records to test for breakpoints".to_string(),
+                ));
+            }
+            let source_metadata = match &sources_file {
+                Some(path) => writer.read_sources(path)?,
+                None => Vec::new(),
+            };
 
-            println!("Unified processing complete!");
+            progress.set_message("Running homogeneity tests...");
+            let detector = BreakpointDetector::new(period)
+                .with_tests(tests)
+                .with_min_length(min_length)?
+                .with_link_window_days(link_window_days)
+                .with_sources(source_metadata);
+            let mut breakpoints = detector.detect(&records);
+            let significant = breakpoints.iter().filter(|b| b.significant).count();
+            let linked = breakpoints
+                .iter()
+                .filter(|b| {
+                    b.significant
+                        && (b.souid_change_date.is_some() || b.source_change_date.is_some())
+                })
+                .count();
+            progress.finish_with_message(&format!(
+                "{} of {} test results significant, {} linked to a source change",
+                significant,
+                breakpoints.len(),
+                linked
+            ));
+            if sources_file.is_none() {
+                println!("No sources table found; breaks are linked to SOUID changes only");
+            }
+
+            if significant_only {
+                breakpoints.retain(|b| b.significant);
+            }
+            if let Some(parent) = output_file.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            write_breakpoints(&writer, &breakpoints, &output_file)?;
+            println!(
+                "Successfully wrote {} breakpoints to {}",
+                breakpoints.len(),
+                output_file.display()
+            );
         }
 
         Commands::Validate {
             input_archive,
-            max_workers: _,
+            max_workers,
         } => {
             println!("Validating weather data from archive...");
             println!("Input archive: {}", input_archive.display());
@@ -229,7 +659,9 @@ pub async fn run(cli: Cli) -> Result<()> {
             let progress = ProgressReporter::new_spinner("Inspecting archive...", false);
 
             // Create archive processor
-            let processor = ArchiveProcessor::from_zip(&input_archive).await?;
+            let processor = ArchiveProcessor::from_zip(&input_archive)
+                .await?
+                .with_max_workers(max_workers)?;
 
             // Display archive metadata
             println!("\n{}", processor.metadata().display_summary());
@@ -258,7 +690,11 @@ pub async fn run(cli: Cli) -> Result<()> {
             sample,
             analysis_limit,
         } => {
-            println!("Analyzing Parquet file: {}", file.display());
+            if file.is_dir() {
+                println!("Analyzing Parquet dataset: {}", file.display());
+            } else {
+                println!("Analyzing Parquet file: {}", file.display());
+            }
 
             // Get basic file info
             let writer = ParquetWriter::new();
@@ -299,7 +735,7 @@ pub async fn run(cli: Cli) -> Result<()> {
                                     );
                                 }
                             }
-                            Err(e) => println!("Error reading sample data: {}", e),
+                            Err(e) => error!(error = %e, "Failed to read sample data"),
                         }
                     }
                 }
@@ -310,7 +746,7 @@ pub async fn run(cli: Cli) -> Result<()> {
                             println!("{}", dataset_summary.display_comprehensive_summary());
                         }
                         Err(e) => {
-                            println!("Error analyzing weather dataset: {}", e);
+                            warn!(error = %e, "Failed to analyze weather dataset");
 
                             // Fallback to basic sample display
                             if sample > 0 {
@@ -348,6 +784,18 @@ pub async fn run(cli: Cli) -> Result<()> {
                                                 metrics.push(format!("wind={:.1}m/s", wind));
                                             }
 
+                                            for (metric, value, _, _) in
+                                                record.supplementary_elements()
+                                            {
+                                                if let Some(value) = value {
+                                                    metrics.push(format!(
+                                                        "{}={:.1}",
+                                                        metric.column_name(),
+                                                        value
+                                                    ));
+                                                }
+                                            }
+
                                             let metrics_str = if metrics.is_empty() {
                                                 "no data".to_string()
                                             } else {
@@ -363,7 +811,7 @@ pub async fn run(cli: Cli) -> Result<()> {
                                             );
                                         }
                                     }
-                                    Err(e) => println!("Error reading sample data: {}", e),
+                                    Err(e) => error!(error = %e, "Failed to read sample data"),
                                 }
                             }
                         }
@@ -378,3 +826,308 @@ pub async fn run(cli: Cli) -> Result<()> {
 
     Ok(())
 }
+
+/// Write source series metadata next to the main output file
+fn write_sources_table(
+    writer: &ParquetWriter,
+    sources: &[SourceMetadata],
+    output_file: &Path,
+) -> Result<()> {
+    if sources.is_empty() {
+        return Ok(());
+    }
+
+    let sources_file = generate_sources_filename(output_file);
+    writer.write_sources(sources, &sources_file)?;
+    println!(
+        "Wrote {} source series to {}",
+        sources.len(),
+        sources_file.display()
+    );
+    Ok(())
+}
+
+/// Parse `--partition-by`; a partitioned dataset is written to a directory
+/// named after the output file without its extension
+fn resolve_partitioning(
+    output_file: PathBuf,
+    partition_by: Option<&str>,
+) -> Result<(PathBuf, Vec<PartitionKey>)> {
+    let Some(partition_by) = partition_by else {
+        return Ok((output_file, Vec::new()));
+    };
+
+    let keys = PartitionKey::parse_list(partition_by)?;
+    let dataset_dir = output_file.with_extension("");
+    println!(
+        "Partitioning by {} into {}",
+        partition_by,
+        dataset_dir.display()
+    );
+
+    Ok((dataset_dir, keys))
+}
+
+/// Options shared by `process` and `process-directory`
+struct ProcessOptions {
+    output_file: PathBuf,
+    compression: String,
+    selection: StationSelectionArgs,
+    dates: DateFilterArgs,
+    spatial_qc: SpatialQcArgs,
+    temporal_qc: TemporalQcArgs,
+    qc_rules: Option<PathBuf>,
+    keep_missing: bool,
+    validate_only: bool,
+    max_workers: usize,
+    chunk_size: usize,
+    streaming: bool,
+    partition_by: Option<String>,
+    completeness_report: Option<PathBuf>,
+    min_completeness: Option<f64>,
+    run_report: RunReportArgs,
+}
+
+/// Configure `pipeline`, which already has its input archives, from the
+/// command line options, then run it and report what it did
+async fn process_archives(
+    command: &str,
+    pipeline: Pipeline,
+    options: ProcessOptions,
+) -> Result<()> {
+    if options.streaming {
+        warn!("--streaming is deprecated and has no effect");
+    }
+    let (output_file, partition_keys) =
+        resolve_partitioning(options.output_file, options.partition_by.as_deref())?;
+
+    println!("Output file: {}", output_file.display());
+    println!(
+        "Workers: {}, Chunk size: {}",
+        options.max_workers, options.chunk_size
+    );
+
+    // Fail on bad settings before doing any work
+    let writer = create_writer(&options.compression, options.chunk_size)?;
+    let mut pipeline = pipeline
+        .with_station_selection(options.selection.to_selection()?)
+        .with_date_filter(options.dates.to_filter()?)
+        .with_validation_rules(load_validation_rules(options.qc_rules.as_deref())?)
+        .with_keep_missing(options.keep_missing)
+        .with_max_workers(options.max_workers)?
+        .with_batch_size(options.chunk_size)?;
+    if let Some(temporal_qc) = options.temporal_qc.to_checker()? {
+        pipeline = pipeline.with_temporal_qc(temporal_qc);
+    }
+    if let Some(spatial_qc) = options.spatial_qc.to_checker() {
+        pipeline = pipeline.with_spatial_qc(spatial_qc);
+    }
+    if let Some(min_percent) = options.min_completeness {
+        pipeline = pipeline.with_min_completeness(min_percent)?;
+    }
+    if options.completeness_report.is_some() {
+        pipeline = pipeline.with_completeness_report();
+    }
+    if !options.validate_only {
+        pipeline = if partition_keys.is_empty() {
+            pipeline.with_parquet_output(&output_file, writer.clone())
+        } else {
+            pipeline.with_partitioned_output(&output_file, partition_keys, writer.clone())
+        };
+    }
+
+    let progress = Arc::new(ProgressReporter::new_spinner(
+        "Inspecting archives...",
+        false,
+    ));
+    let pipeline = pipeline.with_progress({
+        let progress = Arc::clone(&progress);
+        move |event| match event {
+            PipelineProgress::ArchiveIndexed { path, .. } => {
+                progress.set_message(&format!("Indexed {}", path.display()))
+            }
+            PipelineProgress::StationProcessed {
+                completed, total, ..
+            } => progress.set_message(&format!("Processing station {}/{}", completed, total)),
+            PipelineProgress::OutputWritten { .. } => {}
+        }
+    });
+
+    let conditions = options.run_report.conditions()?;
+    let report_path = options.run_report.path(&output_file);
+    let mut report = RunReport::new(command).with_checksums(report_path.is_some());
+
+    let outcome = async {
+        if let Some(qc_rules) = &options.qc_rules {
+            report.add_input(qc_rules)?;
+        }
+
+        let output = match pipeline.run().await {
+            Ok(output) => output,
+            Err(e) => {
+                progress.finish_with_message("Processing failed");
+                return Err(e);
+            }
+        };
+        progress.finish_with_message(&format!(
+            "Processed {} records",
+            output.counts.total_records
+        ));
+        for path in &output.outputs {
+            report.add_output(path);
+        }
+
+        for (path, metadata) in &output.archives {
+            report.add_input(path)?;
+            report.archives.push(ArchiveReport::new(path, metadata));
+            println!("\n{}", metadata.display_summary());
+        }
+        report.integrity = Some(output.integrity.clone());
+        report.warnings.extend(output.warnings.iter().cloned());
+        report.timings.extend(output.timings.iter().cloned());
+        report.counts = output.counts.clone();
+
+        println!(
+            "\n{}",
+            IntegrityChecker::new().generate_summary(&output.integrity)
+        );
+        if let Some(summary) = &output.spatial_qc {
+            println!("{}", summary.summary());
+        }
+        if let Some(completeness) = &output.completeness {
+            println!("\n{}", completeness.summary());
+            if let Some(path) = &options.completeness_report {
+                write_completeness_file(&writer, completeness, path)?;
+            }
+        }
+        for warning in &output.warnings {
+            if warning.code == WarningCode::StationsBelowCompleteness {
+                println!("{}", warning.message);
+            }
+        }
+
+        if options.validate_only {
+            println!("Validation complete - no output file written");
+            report_peak_memory();
+            return Ok(());
+        }
+        if output.outputs.is_empty() {
+            println!("No records to write");
+            report_peak_memory();
+            return Ok(());
+        }
+
+        let counts = &output.counts;
+        println!(
+            "Successfully wrote {} weather records to {}",
+            counts.total_records,
+            output_file.display()
+        );
+        println!("Metric Coverage:");
+        for (column, count) in &counts.metrics {
+            println!(
+                "  {}: {}/{} ({:.1}%)",
+                column,
+                count,
+                counts.total_records,
+                *count as f32 / counts.total_records as f32 * 100.0
+            );
+        }
+
+        report_peak_memory();
+        println!("Processing complete!");
+        Ok::<(), ProcessingError>(())
+    }
+    .await;
+
+    if let Some(path) = &options.completeness_report {
+        report.add_output(path);
+    }
+    finish_run_report(report, outcome, report_path.as_deref(), &conditions)
+}
+
+/// Write the `--completeness-report` file, as JSON or Parquet by extension
+fn write_completeness_file(
+    writer: &ParquetWriter,
+    report: &CompletenessReport,
+    path: &Path,
+) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if path.extension().is_some_and(|ext| ext == "json") {
+        report.write_json(path)?;
+    } else {
+        write_completeness_report(writer, report, path)?;
+    }
+    println!(
+        "Wrote completeness of {} series to {}",
+        report.series.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Close the run report and write it, if requested, then turn the run's
+/// outcome and any `--fail-on` conditions it met into the command's result
+fn finish_run_report(
+    mut report: RunReport,
+    outcome: Result<()>,
+    path: Option<&Path>,
+    conditions: &[ExitCondition],
+) -> Result<()> {
+    report.finish(&outcome, conditions);
+
+    if let Some(path) = path {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        report.write_json(path)?;
+        println!("Wrote run report to {}", path.display());
+    }
+
+    outcome?;
+    if report.failed_conditions.is_empty() {
+        return Ok(());
+    }
+    let met: Vec<String> = report
+        .failed_conditions
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    Err(ProcessingError::ExitConditions(met.join(", ")))
+}
+
+/// Load the `--qc-rules` file, or the built-in limits when none is given
+fn load_validation_rules(path: Option<&Path>) -> Result<Arc<ValidationRules>> {
+    let Some(path) = path else {
+        return Ok(Arc::new(ValidationRules::default()));
+    };
+    let rules = ValidationRules::from_file(path)?;
+    println!(
+        "QC rules: {} ({} regions)",
+        path.display(),
+        rules.regions().len()
+    );
+    Ok(Arc::new(rules))
+}
+
+/// Build the Parquet writer from the CLI compression and chunk size settings
+fn create_writer(compression: &str, chunk_size: usize) -> Result<ParquetWriter> {
+    if chunk_size == 0 {
+        return Err(ProcessingError::Config(
+            "Chunk size must be at least 1".to_string(),
+        ));
+    }
+
+    Ok(ParquetWriter::new()
+        .with_compression(compression)?
+        .with_row_group_size(chunk_size))
+}
+
+/// Print the process's peak resident memory, where the platform reports it
+fn report_peak_memory() {
+    if let Some(peak) = peak_memory_bytes() {
+        println!("Peak memory: {}", format_memory(peak));
+    }
+}
@@ -49,6 +49,9 @@ pub enum ProcessingError {
     #[error("Processing cancelled by user")]
     Cancelled,
 
+    #[error("Run met exit conditions: {0}")]
+    ExitConditions(String),
+
     #[error("Async task error: {0}")]
     TaskJoin(#[from] tokio::task::JoinError),
 
@@ -3,9 +3,11 @@ pub mod archive;
 pub mod cli;
 pub mod error;
 pub mod models;
+pub mod pipeline;
 pub mod processors;
 pub mod readers;
 pub mod utils;
 pub mod writers;
 
 pub use error::{ProcessingError, Result};
+pub use pipeline::{CancellationToken, Pipeline, PipelineOutput, PipelineProgress};
@@ -1,9 +1,17 @@
 use clap::Parser;
 use ecad_processor::cli::{run, Cli};
-use ecad_processor::error::Result;
+use ecad_processor::error::{ProcessingError, Result};
+use ecad_processor::processors::EXIT_CONDITION_CODE;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
-    run(cli).await
+    match run(cli).await {
+        // A completed run that met a --fail-on condition has its own status
+        Err(e @ ProcessingError::ExitConditions(_)) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(EXIT_CONDITION_CODE);
+        }
+        result => result,
+    }
 }
@@ -0,0 +1,187 @@
+use crate::error::{ProcessingError, Result};
+use chrono::{Datelike, NaiveDate};
+use std::str::FromStr;
+
+/// Calendar window applied to data lines as they are parsed, so lines
+/// outside it never become records
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateFilter {
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    months: Option<MonthSet>,
+    day_of_year: Option<(u32, u32)>,
+}
+
+impl DateFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep dates from `start` to `end`, inclusive; either bound may be open
+    pub fn with_date_range(
+        mut self,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+    ) -> Result<Self> {
+        if let (Some(start), Some(end)) = (start, end) {
+            if start > end {
+                return Err(ProcessingError::Config(format!(
+                    "Start date {} is after end date {}",
+                    start, end
+                )));
+            }
+        }
+        self.start = start;
+        self.end = end;
+        Ok(self)
+    }
+
+    pub fn with_months(mut self, months: MonthSet) -> Self {
+        self.months = Some(months);
+        self
+    }
+
+    /// Keep days of the year from `first` to `last` (1-366), inclusive.
+    /// A range such as 335-59 wraps over the new year.
+    pub fn with_day_of_year(mut self, first: u32, last: u32) -> Result<Self> {
+        if !(1..=366).contains(&first) || !(1..=366).contains(&last) {
+            return Err(ProcessingError::Config(format!(
+                "Day of year must be between 1 and 366, got {}-{}",
+                first, last
+            )));
+        }
+        self.day_of_year = Some((first, last));
+        Ok(self)
+    }
+
+    /// Whether no criteria are set, so every date matches
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn matches(&self, date: NaiveDate) -> bool {
+        if self.start.is_some_and(|start| date < start) || self.end.is_some_and(|end| date > end) {
+            return false;
+        }
+        if self
+            .months
+            .is_some_and(|months| !months.contains(date.month()))
+        {
+            return false;
+        }
+        if let Some((first, last)) = self.day_of_year {
+            let day = date.ordinal();
+            let inside = if first <= last {
+                (first..=last).contains(&day)
+            } else {
+                day >= first || day <= last
+            };
+            if !inside {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// A set of calendar months
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonthSet(u16);
+
+impl MonthSet {
+    pub fn new(months: impl IntoIterator<Item = u32>) -> Result<Self> {
+        let mut bits = 0;
+        for month in months {
+            if !(1..=12).contains(&month) {
+                return Err(ProcessingError::Config(format!(
+                    "Month must be between 1 and 12, got {}",
+                    month
+                )));
+            }
+            bits |= 1 << (month - 1);
+        }
+        Ok(Self(bits))
+    }
+
+    pub fn contains(&self, month: u32) -> bool {
+        (1..=12).contains(&month) && self.0 & (1 << (month - 1)) != 0
+    }
+}
+
+impl FromStr for MonthSet {
+    type Err = ProcessingError;
+
+    /// Parse comma-separated months, month ranges and season codes,
+    /// e.g. `6,7,8`, `6-8`, `JJA` or `DJF,MAM`
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || ProcessingError::Config(format!("Invalid month set: {}", s));
+        let mut months = Vec::new();
+
+        for part in s.split(',').map(str::trim) {
+            match part.to_uppercase().as_str() {
+                "DJF" => months.extend([12, 1, 2]),
+                "MAM" => months.extend([3, 4, 5]),
+                "JJA" => months.extend([6, 7, 8]),
+                "SON" => months.extend([9, 10, 11]),
+                _ => match part.split_once('-') {
+                    Some((first, last)) => {
+                        let first: u32 = first.trim().parse().map_err(|_| invalid())?;
+                        let last: u32 = last.trim().parse().map_err(|_| invalid())?;
+                        if first > last {
+                            return Err(invalid());
+                        }
+                        months.extend(first..=last);
+                    }
+                    None => months.push(part.parse().map_err(|_| invalid())?),
+                },
+            }
+        }
+
+        Self::new(months)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_date_filter() {
+        let filter = DateFilter::new()
+            .with_date_range(Some(date("1961-01-01")), Some(date("1990-12-31")))
+            .unwrap()
+            .with_months("JJA".parse().unwrap());
+
+        assert!(filter.matches(date("1975-07-14")));
+        assert!(!filter.matches(date("1975-09-01")));
+        assert!(!filter.matches(date("1991-07-14")));
+        assert!(!filter.is_empty());
+        assert!(DateFilter::new().matches(date("1850-01-01")));
+        assert!(DateFilter::new().is_empty());
+
+        // Winter window wrapping over the new year
+        let winter = DateFilter::new().with_day_of_year(335, 59).unwrap();
+        assert!(winter.matches(date("2001-12-25")));
+        assert!(winter.matches(date("2001-02-01")));
+        assert!(!winter.matches(date("2001-06-01")));
+    }
+
+    #[test]
+    fn test_month_set_parsing() {
+        let months: MonthSet = "DJF, 6-7".parse().unwrap();
+        assert!([12, 1, 2, 6, 7].iter().all(|m| months.contains(*m)));
+        assert!(!months.contains(8));
+
+        assert!("13".parse::<MonthSet>().is_err());
+        assert!("8-6".parse::<MonthSet>().is_err());
+        assert!("summer".parse::<MonthSet>().is_err());
+        assert!(DateFilter::new().with_day_of_year(0, 10).is_err());
+        assert!(DateFilter::new()
+            .with_date_range(Some(date("1990-01-01")), Some(date("1961-01-01")))
+            .is_err());
+    }
+}
@@ -1,9 +1,17 @@
 pub mod consolidated;
+pub mod date_filter;
+pub mod source;
 pub mod station;
+pub mod station_selection;
 pub mod temperature;
+pub mod validation_rules;
 pub mod weather;
 
 pub use consolidated::{ConsolidatedRecord, ConsolidatedRecordBuilder};
+pub use date_filter::{DateFilter, MonthSet};
+pub use source::SourceMetadata;
 pub use station::StationMetadata;
+pub use station_selection::{BoundingBox, RadiusFilter, StationSelection};
 pub use temperature::{QualityFlag, TemperatureRecord, TemperatureSet};
+pub use validation_rules::{Limits, RegionRule, StationLimits, ValidationRules};
 pub use weather::{WeatherRecord, WeatherRecordBuilder};
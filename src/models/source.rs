@@ -0,0 +1,66 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Metadata for a single ECA&D source series (one line of sources.txt)
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SourceMetadata {
+    pub staid: u32,
+    pub souid: u32,
+
+    #[validate(length(min = 1))]
+    pub name: String,
+
+    pub country: String,
+
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub latitude: f64,
+
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub longitude: f64,
+
+    pub elevation: Option<i32>,
+
+    /// ECA&D element identifier, e.g. "TG1" or "RR2"
+    pub element_id: String,
+
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+
+    pub participant_id: Option<u32>,
+    pub participant_name: String,
+}
+
+impl SourceMetadata {
+    /// Whether this source series covers the given date
+    pub fn covers(&self, date: NaiveDate) -> bool {
+        date >= self.start_date && date <= self.end_date
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_covers_date() {
+        let source = SourceMetadata {
+            staid: 1,
+            souid: 100,
+            name: "VAEXJOE".to_string(),
+            country: "SE".to_string(),
+            latitude: 56.8667,
+            longitude: 14.8,
+            elevation: Some(166),
+            element_id: "TG1".to_string(),
+            start_date: NaiveDate::from_ymd_opt(1860, 1, 1).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2006, 12, 31).unwrap(),
+            participant_id: Some(1),
+            participant_name: "SMHI".to_string(),
+        };
+
+        assert!(source.validate().is_ok());
+        assert!(source.covers(NaiveDate::from_ymd_opt(1950, 6, 1).unwrap()));
+        assert!(!source.covers(NaiveDate::from_ymd_opt(2007, 1, 1).unwrap()));
+    }
+}
@@ -0,0 +1,298 @@
+use crate::error::{ProcessingError, Result};
+use crate::models::StationMetadata;
+use crate::utils::coordinates::haversine_distance;
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+/// Latitude/longitude rectangle, inclusive on all edges
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+}
+
+impl BoundingBox {
+    pub fn new(min_lat: f64, min_lon: f64, max_lat: f64, max_lon: f64) -> Result<Self> {
+        if !(-90.0..=90.0).contains(&min_lat) || !(-90.0..=90.0).contains(&max_lat) {
+            return Err(ProcessingError::Config(
+                "Bounding box latitudes must be between -90 and 90".to_string(),
+            ));
+        }
+        if !(-180.0..=180.0).contains(&min_lon) || !(-180.0..=180.0).contains(&max_lon) {
+            return Err(ProcessingError::Config(
+                "Bounding box longitudes must be between -180 and 180".to_string(),
+            ));
+        }
+        if min_lat > max_lat || min_lon > max_lon {
+            return Err(ProcessingError::Config(
+                "Bounding box minimum exceeds maximum".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            min_lat,
+            min_lon,
+            max_lat,
+            max_lon,
+        })
+    }
+
+    pub fn contains(&self, latitude: f64, longitude: f64) -> bool {
+        (self.min_lat..=self.max_lat).contains(&latitude)
+            && (self.min_lon..=self.max_lon).contains(&longitude)
+    }
+}
+
+impl FromStr for BoundingBox {
+    type Err = ProcessingError;
+
+    /// Parse `min_lat,min_lon,max_lat,max_lon`
+    fn from_str(s: &str) -> Result<Self> {
+        match parse_numbers(s, "bounding box")?[..] {
+            [min_lat, min_lon, max_lat, max_lon] => Self::new(min_lat, min_lon, max_lat, max_lon),
+            _ => Err(ProcessingError::Config(format!(
+                "Invalid bounding box: {} (expected min_lat,min_lon,max_lat,max_lon)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Great-circle radius around a point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RadiusFilter {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_km: f64,
+}
+
+impl RadiusFilter {
+    pub fn new(latitude: f64, longitude: f64, radius_km: f64) -> Result<Self> {
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return Err(ProcessingError::Config(format!(
+                "Invalid radius centre: {}, {}",
+                latitude, longitude
+            )));
+        }
+        if radius_km.is_nan() || radius_km < 0.0 {
+            return Err(ProcessingError::Config(
+                "Radius must not be negative".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            latitude,
+            longitude,
+            radius_km,
+        })
+    }
+
+    pub fn contains(&self, latitude: f64, longitude: f64) -> bool {
+        haversine_distance(self.latitude, self.longitude, latitude, longitude) <= self.radius_km
+    }
+}
+
+impl FromStr for RadiusFilter {
+    type Err = ProcessingError;
+
+    /// Parse `lat,lon,km`
+    fn from_str(s: &str) -> Result<Self> {
+        match parse_numbers(s, "radius")?[..] {
+            [latitude, longitude, radius_km] => Self::new(latitude, longitude, radius_km),
+            _ => Err(ProcessingError::Config(format!(
+                "Invalid radius: {} (expected lat,lon,km)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Criteria for choosing stations from `stations.txt` before any data files
+/// are read. Every criterion that is set must match.
+#[derive(Debug, Clone, Default)]
+pub struct StationSelection {
+    station_ids: Option<BTreeSet<u32>>,
+    countries: Option<BTreeSet<String>>,
+    bounding_box: Option<BoundingBox>,
+    radius: Option<RadiusFilter>,
+    min_elevation: Option<i32>,
+    max_elevation: Option<i32>,
+    name_pattern: Option<Regex>,
+}
+
+impl StationSelection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_station_ids(mut self, station_ids: impl IntoIterator<Item = u32>) -> Self {
+        self.station_ids = Some(station_ids.into_iter().collect());
+        self
+    }
+
+    /// Match ISO country codes as listed in `stations.txt`, ignoring case
+    pub fn with_countries<S: AsRef<str>>(mut self, countries: impl IntoIterator<Item = S>) -> Self {
+        self.countries = Some(
+            countries
+                .into_iter()
+                .map(|c| c.as_ref().trim().to_uppercase())
+                .collect(),
+        );
+        self
+    }
+
+    pub fn with_bounding_box(mut self, bounding_box: BoundingBox) -> Self {
+        self.bounding_box = Some(bounding_box);
+        self
+    }
+
+    pub fn with_radius(mut self, radius: RadiusFilter) -> Self {
+        self.radius = Some(radius);
+        self
+    }
+
+    /// Keep stations within an elevation range in metres. Stations without a
+    /// recorded elevation never match a range.
+    pub fn with_elevation_range(mut self, min: Option<i32>, max: Option<i32>) -> Result<Self> {
+        if let (Some(min), Some(max)) = (min, max) {
+            if min > max {
+                return Err(ProcessingError::Config(format!(
+                    "Minimum elevation {} exceeds maximum {}",
+                    min, max
+                )));
+            }
+        }
+        self.min_elevation = min;
+        self.max_elevation = max;
+        Ok(self)
+    }
+
+    /// Keep stations whose name matches a regular expression
+    pub fn with_name_pattern(mut self, pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern)
+            .map_err(|e| ProcessingError::Config(format!("Invalid station name pattern: {}", e)))?;
+        self.name_pattern = Some(regex);
+        Ok(self)
+    }
+
+    /// Whether no criteria are set, so every station matches
+    pub fn is_empty(&self) -> bool {
+        self.station_ids.is_none()
+            && self.countries.is_none()
+            && self.bounding_box.is_none()
+            && self.radius.is_none()
+            && self.min_elevation.is_none()
+            && self.max_elevation.is_none()
+            && self.name_pattern.is_none()
+    }
+
+    pub fn matches(&self, station: &StationMetadata) -> bool {
+        if let Some(ids) = &self.station_ids {
+            if !ids.contains(&station.staid) {
+                return false;
+            }
+        }
+        if let Some(countries) = &self.countries {
+            if !countries.contains(&station.country.trim().to_uppercase()) {
+                return false;
+            }
+        }
+        if let Some(bounding_box) = &self.bounding_box {
+            if !bounding_box.contains(station.latitude, station.longitude) {
+                return false;
+            }
+        }
+        if let Some(radius) = &self.radius {
+            if !radius.contains(station.latitude, station.longitude) {
+                return false;
+            }
+        }
+        if self.min_elevation.is_some() || self.max_elevation.is_some() {
+            let Some(elevation) = station.elevation else {
+                return false;
+            };
+            if self.min_elevation.is_some_and(|min| elevation < min)
+                || self.max_elevation.is_some_and(|max| elevation > max)
+            {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.name_pattern {
+            if !pattern.is_match(station.name.trim()) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn parse_numbers(s: &str, what: &str) -> Result<Vec<f64>> {
+    s.split(',')
+        .map(|part| {
+            part.trim()
+                .parse::<f64>()
+                .map_err(|_| ProcessingError::Config(format!("Invalid {}: {}", what, s)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn station(staid: u32, name: &str, country: &str, lat: f64, lon: f64) -> StationMetadata {
+        StationMetadata::new(
+            staid,
+            name.to_string(),
+            country.to_string(),
+            lat,
+            lon,
+            Some(staid as i32),
+        )
+    }
+
+    #[test]
+    fn test_selection_criteria() {
+        let london = station(35, "LONDON HEATHROW", "GB", 51.48, -0.45);
+        let armagh = station(62, "ARMAGH", "GB", 54.35, -6.65);
+        let de_bilt = station(162, "DE BILT", "NL", 52.1, 5.18);
+
+        let uk = StationSelection::new().with_countries(["gb"]);
+        assert!(uk.matches(&london) && uk.matches(&armagh) && !uk.matches(&de_bilt));
+
+        let bbox = StationSelection::new().with_bounding_box("50,-2,53,6".parse().unwrap());
+        assert!(bbox.matches(&london) && bbox.matches(&de_bilt) && !bbox.matches(&armagh));
+
+        // De Bilt is about 400 km from Heathrow
+        let near_london = StationSelection::new().with_radius("51.5,-0.1,100".parse().unwrap());
+        assert!(near_london.matches(&london) && !near_london.matches(&de_bilt));
+
+        let high = StationSelection::new()
+            .with_elevation_range(Some(50), None)
+            .unwrap();
+        assert!(high.matches(&armagh) && !high.matches(&london));
+
+        let named = StationSelection::new()
+            .with_name_pattern("^(LONDON|DE) ")
+            .unwrap()
+            .with_station_ids([35, 62]);
+        assert!(named.matches(&london) && !named.matches(&armagh) && !named.matches(&de_bilt));
+
+        assert!(StationSelection::new().is_empty());
+        assert!(!named.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_selection() {
+        assert!("50,-2,53".parse::<BoundingBox>().is_err());
+        assert!("53,-2,50,6".parse::<BoundingBox>().is_err());
+        assert!("51.5,-0.1,-5".parse::<RadiusFilter>().is_err());
+        assert!(StationSelection::new().with_name_pattern("(").is_err());
+        assert!(StationSelection::new()
+            .with_elevation_range(Some(100), Some(10))
+            .is_err());
+    }
+}
@@ -0,0 +1,485 @@
+use crate::error::{ProcessingError, Result};
+use crate::models::weather::PhysicalValidity;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Elements with physical limits, in `StationLimits` order. `temperature`
+/// covers the minimum, maximum and mean.
+const ELEMENTS: [&str; 11] = [
+    "temperature",
+    "precipitation",
+    "wind_speed",
+    "sunshine",
+    "humidity",
+    "pressure",
+    "cloud_cover",
+    "snow_depth",
+    "wind_gust",
+    "wind_direction",
+    "global_radiation",
+];
+
+/// Physically possible range and unremarkable range of one element. Values
+/// outside `min..=max` are Invalid, values outside the suspect range Suspect.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    pub min: f32,
+    pub max: f32,
+    pub suspect_min: f32,
+    pub suspect_max: f32,
+}
+
+impl Limits {
+    const fn new(min: f32, max: f32, suspect_min: f32, suspect_max: f32) -> Self {
+        Self {
+            min,
+            max,
+            suspect_min,
+            suspect_max,
+        }
+    }
+
+    pub fn classify(&self, value: f32) -> PhysicalValidity {
+        if !(self.min..=self.max).contains(&value) {
+            PhysicalValidity::Invalid
+        } else if !(self.suspect_min..=self.suspect_max).contains(&value) {
+            PhysicalValidity::Suspect
+        } else {
+            PhysicalValidity::Valid
+        }
+    }
+
+    fn apply(&mut self, update: &LimitsUpdate) {
+        self.min = update.min.unwrap_or(self.min);
+        self.max = update.max.unwrap_or(self.max);
+        self.suspect_min = update.suspect_min.unwrap_or(self.suspect_min);
+        self.suspect_max = update.suspect_max.unwrap_or(self.suspect_max);
+    }
+}
+
+/// Limits in effect for one station, after region rules are applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StationLimits([Limits; 11]);
+
+impl StationLimits {
+    /// Limits for an element key such as `temperature` or `snow_depth`
+    pub fn get(&self, element: &str) -> Option<Limits> {
+        slot(element).map(|i| self.0[i])
+    }
+}
+
+/// Built-in limits, tuned for the UK and Ireland
+const BUILTIN: [Limits; 11] = [
+    // Beyond the world records of about -89°C and 57°C
+    Limits::new(-90.0, 60.0, -35.0, 45.0),
+    // Daily totals above 500 mm are extreme even for convective storms
+    Limits::new(0.0, 2000.0, 0.0, 500.0),
+    // Hurricane-force daily means are suspect
+    Limits::new(0.0, 120.0, 0.0, 50.0),
+    // Daily sunshine duration cannot exceed day length
+    Limits::new(0.0, 24.0, 0.0, 20.0),
+    // Relative humidity; very dry air is rare in Europe
+    Limits::new(0.0, 100.0, 5.0, 100.0),
+    // Sea level pressure; records are ~870 hPa and ~1084 hPa
+    Limits::new(850.0, 1100.0, 920.0, 1070.0),
+    // Oktas, with 9 meaning sky obscured
+    Limits::new(0.0, 9.0, 0.0, 9.0),
+    // World record snow depth is ~1180 cm
+    Limits::new(0.0, 2000.0, 0.0, 500.0),
+    // Strongest recorded gust is ~113 m/s
+    Limits::new(0.0, 120.0, 0.0, 60.0),
+    Limits::new(0.0, 360.0, 0.0, 360.0),
+    // Daily mean surface irradiance stays well below the solar constant
+    Limits::new(0.0, 500.0, 0.0, 400.0),
+];
+
+/// A partial limits table from a rules file
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LimitsUpdate {
+    min: Option<f32>,
+    max: Option<f32>,
+    suspect_min: Option<f32>,
+    suspect_max: Option<f32>,
+}
+
+/// Limits that replace the defaults for stations matching every condition
+/// given: a country code, a latitude band and an elevation band
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct RegionRule {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub countries: Vec<String>,
+    pub min_latitude: Option<f64>,
+    pub max_latitude: Option<f64>,
+    pub min_elevation: Option<i32>,
+    pub max_elevation: Option<i32>,
+    #[serde(flatten)]
+    limits: HashMap<String, LimitsUpdate>,
+}
+
+impl RegionRule {
+    pub fn matches(&self, country: Option<&str>, latitude: f64, elevation: Option<i32>) -> bool {
+        let country_ok = self.countries.is_empty()
+            || country.is_some_and(|c| self.countries.iter().any(|r| r.eq_ignore_ascii_case(c)));
+        let latitude_ok = self.min_latitude.map_or(true, |min| latitude >= min)
+            && self.max_latitude.map_or(true, |max| latitude <= max);
+        let elevation_ok = match (self.min_elevation, self.max_elevation) {
+            (None, None) => true,
+            (min, max) => elevation.is_some_and(|e| {
+                min.map_or(true, |min| e >= min) && max.map_or(true, |max| e <= max)
+            }),
+        };
+        country_ok && latitude_ok && elevation_ok
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default)]
+    defaults: HashMap<String, LimitsUpdate>,
+    #[serde(default)]
+    regions: Vec<RegionRule>,
+}
+
+/// Physical validity limits per element, optionally varying by country,
+/// latitude band or elevation.
+///
+/// Rules files are TOML, YAML or JSON. `[defaults.<element>]` tables adjust
+/// the built-in limits and each `[[regions]]` entry overrides them for the
+/// stations it matches; later regions win.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationRules {
+    defaults: [Limits; 11],
+    regions: Vec<RegionRule>,
+}
+
+impl Default for ValidationRules {
+    fn default() -> Self {
+        Self {
+            defaults: BUILTIN,
+            regions: Vec::new(),
+        }
+    }
+}
+
+impl ValidationRules {
+    /// Shared built-in rules
+    pub fn builtin() -> &'static ValidationRules {
+        static BUILTIN_RULES: OnceLock<ValidationRules> = OnceLock::new();
+        BUILTIN_RULES.get_or_init(ValidationRules::default)
+    }
+
+    /// Load rules from a file, choosing the format from its extension
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let source = config::File::from(path).required(true);
+        Self::load(source, &path.display().to_string())
+    }
+
+    pub fn from_toml_str(toml: &str) -> Result<Self> {
+        let source = config::File::from_str(toml, config::FileFormat::Toml);
+        Self::load(source, "inline rules")
+    }
+
+    fn load<S>(source: S, origin: &str) -> Result<Self>
+    where
+        S: config::Source + Send + Sync + 'static,
+    {
+        let invalid = |e: config::ConfigError| {
+            ProcessingError::Config(format!("Invalid QC rules in {}: {}", origin, e))
+        };
+        let file: RulesFile = config::Config::builder()
+            .add_source(source)
+            .build()
+            .map_err(invalid)?
+            .try_deserialize()
+            .map_err(invalid)?;
+
+        let mut rules = Self::default();
+        for (element, update) in &file.defaults {
+            let i = slot(element).ok_or_else(|| unknown_element(element, origin))?;
+            rules.defaults[i].apply(update);
+        }
+        for region in &file.regions {
+            if let Some(element) = region.limits.keys().find(|e| slot(e).is_none()) {
+                return Err(unknown_element(element, origin));
+            }
+        }
+        rules.regions = file.regions;
+
+        // Every set of regions a single station could match must leave
+        // every element's limits ordered once merged
+        rules.check_merged_order(0, &StationScope::default(), rules.defaults, &[], origin)?;
+
+        Ok(rules)
+    }
+
+    pub fn regions(&self) -> &[RegionRule] {
+        &self.regions
+    }
+
+    /// Limits for a station, applying every matching region in order
+    pub fn for_station(
+        &self,
+        country: Option<&str>,
+        latitude: f64,
+        elevation: Option<i32>,
+    ) -> StationLimits {
+        let mut limits = self.defaults;
+        for region in &self.regions {
+            if region.matches(country, latitude, elevation) {
+                self.apply_region(region, &mut limits);
+            }
+        }
+        StationLimits(limits)
+    }
+
+    /// Check `limits` and, recursively, every combination of the regions
+    /// from `next` onwards that a station within `scope` could also match.
+    /// Rules files hold a handful of regions, so the search stays small.
+    fn check_merged_order(
+        &self,
+        next: usize,
+        scope: &StationScope,
+        limits: [Limits; 11],
+        matched: &[&str],
+        origin: &str,
+    ) -> Result<()> {
+        for (element, l) in ELEMENTS.iter().zip(limits) {
+            if !(l.min <= l.suspect_min && l.suspect_min <= l.suspect_max && l.suspect_max <= l.max)
+            {
+                let stations = if matched.is_empty() {
+                    "by default".to_string()
+                } else {
+                    format!("for stations matching {}", matched.join(" + "))
+                };
+                return Err(ProcessingError::Config(format!(
+                    "QC rules in {} give {} limits out of order {}: min {} <= suspect_min {} <= suspect_max {} <= max {} is required",
+                    origin, element, stations, l.min, l.suspect_min, l.suspect_max, l.max
+                )));
+            }
+        }
+
+        for (offset, region) in self.regions[next..].iter().enumerate() {
+            let Some(narrowed) = scope.narrow(region) else {
+                continue;
+            };
+            let mut merged = limits;
+            self.apply_region(region, &mut merged);
+            let index = next + offset;
+            let name = if region.name.is_empty() {
+                format!("region {}", index + 1)
+            } else {
+                region.name.clone()
+            };
+            let mut names = matched.to_vec();
+            names.push(&name);
+            self.check_merged_order(index + 1, &narrowed, merged, &names, origin)?;
+        }
+        Ok(())
+    }
+
+    fn apply_region(&self, region: &RegionRule, limits: &mut [Limits; 11]) {
+        for (element, update) in &region.limits {
+            if let Some(i) = slot(element) {
+                limits[i].apply(update);
+            }
+        }
+    }
+}
+
+/// The stations that could match every region chosen so far
+#[derive(Debug, Clone)]
+struct StationScope {
+    /// Upper-cased country codes, or None for any country
+    countries: Option<Vec<String>>,
+    latitude: (f64, f64),
+    /// Elevation band, or None when no region so far needs an elevation
+    elevation: Option<(i32, i32)>,
+}
+
+impl Default for StationScope {
+    fn default() -> Self {
+        Self {
+            countries: None,
+            latitude: (f64::NEG_INFINITY, f64::INFINITY),
+            elevation: None,
+        }
+    }
+}
+
+impl StationScope {
+    /// Narrow to the stations that also match `region`, or None if none can
+    fn narrow(&self, region: &RegionRule) -> Option<Self> {
+        let countries = if region.countries.is_empty() {
+            self.countries.clone()
+        } else {
+            let wanted = region.countries.iter().map(|c| c.to_ascii_uppercase());
+            let kept: Vec<String> = match &self.countries {
+                Some(current) => wanted.filter(|c| current.contains(c)).collect(),
+                None => wanted.collect(),
+            };
+            if kept.is_empty() {
+                return None;
+            }
+            Some(kept)
+        };
+
+        let latitude = (
+            self.latitude
+                .0
+                .max(region.min_latitude.unwrap_or(f64::NEG_INFINITY)),
+            self.latitude
+                .1
+                .min(region.max_latitude.unwrap_or(f64::INFINITY)),
+        );
+        if latitude.0 > latitude.1 {
+            return None;
+        }
+
+        let elevation = match (region.min_elevation, region.max_elevation) {
+            (None, None) => self.elevation,
+            (min, max) => {
+                let (low, high) = self.elevation.unwrap_or((i32::MIN, i32::MAX));
+                let band = (
+                    low.max(min.unwrap_or(i32::MIN)),
+                    high.min(max.unwrap_or(i32::MAX)),
+                );
+                if band.0 > band.1 {
+                    return None;
+                }
+                Some(band)
+            }
+        };
+
+        Some(Self {
+            countries,
+            latitude,
+            elevation,
+        })
+    }
+}
+
+fn slot(element: &str) -> Option<usize> {
+    ELEMENTS.iter().position(|e| *e == element)
+}
+
+fn unknown_element(element: &str, origin: &str) -> ProcessingError {
+    ProcessingError::Config(format!(
+        "Unknown element '{}' in QC rules {} (expected one of: {})",
+        element,
+        origin,
+        ELEMENTS.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"
+        [defaults.precipitation]
+        suspect_max = 300
+
+        [[regions]]
+        name = "Iberia"
+        countries = ["ES", "PT"]
+        [regions.temperature]
+        suspect_max = 48
+
+        [[regions]]
+        name = "Fennoscandia"
+        min_latitude = 60.0
+        [regions.temperature]
+        suspect_min = -50
+
+        [[regions]]
+        name = "Alpine summits"
+        min_elevation = 2500
+        [regions.temperature]
+        suspect_min = -45
+        suspect_max = 30
+    "#;
+
+    #[test]
+    fn test_region_rules_applied_by_station() {
+        let rules = ValidationRules::from_toml_str(RULES).unwrap();
+        assert_eq!(rules.regions().len(), 3);
+
+        // Seville at 47°C is plausible, London at 47°C is not
+        let seville = rules.for_station(Some("ES"), 37.4, Some(34));
+        let london = rules.for_station(Some("GB"), 51.5, Some(25));
+        let temp = |limits: &StationLimits| limits.get("temperature").unwrap();
+        assert_eq!(temp(&seville).classify(47.0), PhysicalValidity::Valid);
+        assert_eq!(temp(&london).classify(47.0), PhysicalValidity::Suspect);
+        assert_eq!(temp(&london), ValidationRules::builtin().defaults[0]);
+
+        // Sodankylä at -45°C, and a summit with an unknown elevation
+        let sodankyla = rules.for_station(Some("FI"), 67.4, Some(179));
+        assert_eq!(temp(&sodankyla).classify(-45.0), PhysicalValidity::Valid);
+        let sonnblick = rules.for_station(Some("AT"), 47.1, Some(3106));
+        assert_eq!(temp(&sonnblick).classify(32.0), PhysicalValidity::Suspect);
+        let unknown = rules.for_station(Some("AT"), 47.1, None);
+        assert_eq!(temp(&unknown).classify(32.0), PhysicalValidity::Valid);
+
+        // Defaults apply everywhere, leaving unset limits alone
+        let precip = london.get("precipitation").unwrap();
+        assert_eq!((precip.suspect_max, precip.max), (300.0, 2000.0));
+        assert_eq!(precip.classify(-1.0), PhysicalValidity::Invalid);
+    }
+
+    #[test]
+    fn test_invalid_rules_rejected() {
+        let unknown = ValidationRules::from_toml_str("[defaults.temprature]\nmax = 50");
+        assert!(unknown.unwrap_err().to_string().contains("temprature"));
+
+        let disordered =
+            "[[regions]]\ncountries = [\"ES\"]\n[regions.wind_speed]\nsuspect_max = 150";
+        assert!(ValidationRules::from_toml_str(disordered).is_err());
+
+        let typo = "[defaults.temperature]\nsuspect_maximum = 50";
+        assert!(ValidationRules::from_toml_str(typo).is_err());
+    }
+
+    #[test]
+    fn test_overlapping_regions_checked_merged() {
+        // Each region is ordered alone, but a station in both is not
+        let overlapping = r#"
+            [[regions]]
+            name = "South"
+            max_latitude = 45.0
+            [regions.temperature]
+            suspect_max = 48
+
+            [[regions]]
+            name = "Lowlands"
+            max_elevation = 200
+            [regions.temperature]
+            max = 45
+        "#;
+        let err = ValidationRules::from_toml_str(overlapping).unwrap_err();
+        assert!(err.to_string().contains("South + Lowlands"));
+
+        // Regions no station can match together may disagree
+        let disjoint = overlapping.replace("max_elevation = 200", "min_latitude = 50.0");
+        assert!(ValidationRules::from_toml_str(&disjoint).is_ok());
+        let other_countries = r#"
+            [[regions]]
+            countries = ["ES"]
+            [regions.temperature]
+            suspect_max = 48
+
+            [[regions]]
+            countries = ["gb", "IE"]
+            [regions.temperature]
+            max = 45
+        "#;
+        assert!(ValidationRules::from_toml_str(other_countries).is_ok());
+        let same_country = other_countries.replace("\"gb\"", "\"es\"");
+        assert!(ValidationRules::from_toml_str(&same_country).is_err());
+    }
+}
@@ -1,8 +1,11 @@
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use validator::Validate;
 
+use crate::archive::WeatherMetric;
 use crate::error::{ProcessingError, Result};
+use crate::models::validation_rules::{StationLimits, ValidationRules};
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum PhysicalValidity {
@@ -22,6 +25,96 @@ impl PhysicalValidity {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SpatialConsistency {
+    Consistent, // Close to the neighbour-based estimate
+    Suspect,    // Unusually far from the neighbours
+    Outlier,    // Too far from the neighbours to be plausible
+}
+
+impl SpatialConsistency {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Consistent" => Some(SpatialConsistency::Consistent),
+            "Suspect" => Some(SpatialConsistency::Suspect),
+            "Outlier" => Some(SpatialConsistency::Outlier),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InfillMethod {
+    Interpolation, // Linear between the observations either side of a gap
+    Neighbour,     // Regression on the best-correlated nearby station
+    Climatology,   // The station's own mean for the calendar month
+}
+
+impl InfillMethod {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Interpolation" => Some(InfillMethod::Interpolation),
+            "Neighbour" => Some(InfillMethod::Neighbour),
+            "Climatology" => Some(InfillMethod::Climatology),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for InfillMethod {
+    type Err = ProcessingError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "interpolation" | "linear" => Ok(InfillMethod::Interpolation),
+            "neighbour" | "neighbor" | "regression" => Ok(InfillMethod::Neighbour),
+            "climatology" => Ok(InfillMethod::Climatology),
+            other => Err(ProcessingError::Config(format!(
+                "Unsupported infill method: {} (expected interpolation, neighbour or climatology)",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MissingReason {
+    Sentinel,       // Value was -9999 without a missing flag
+    Flag9,          // Value was -9999 with quality flag 9
+    MalformedLine,  // Line had a date but no usable value or flag
+    UnknownStation, // Station is not listed in stations.txt
+}
+
+impl MissingReason {
+    /// Name written to the `missing_reason` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MissingReason::Sentinel => "Sentinel",
+            MissingReason::Flag9 => "Flag9",
+            MissingReason::MalformedLine => "MalformedLine",
+            MissingReason::UnknownStation => "UnknownStation",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Sentinel" => Some(MissingReason::Sentinel),
+            "Flag9" => Some(MissingReason::Flag9),
+            "MalformedLine" => Some(MissingReason::MalformedLine),
+            "UnknownStation" => Some(MissingReason::UnknownStation),
+            _ => None,
+        }
+    }
+}
+
+/// A dated line of a data file that did not yield a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingObservation {
+    pub souid: Option<u32>,
+    pub date: NaiveDate,
+    pub reason: MissingReason,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum DataQuality {
     Valid,           // ECAD=0 AND physically valid
@@ -32,6 +125,20 @@ pub enum DataQuality {
     Missing,         // ECAD=9
 }
 
+/// View of a supplementary element: (metric, value, ECAD flag, physical validity)
+pub type ElementView<'a> = (
+    WeatherMetric,
+    Option<f32>,
+    Option<&'a str>,
+    Option<PhysicalValidity>,
+);
+
+type SupplementarySlots<'a> = (
+    &'a mut Option<f32>,
+    &'a mut Option<String>,
+    &'a mut Option<PhysicalValidity>,
+);
+
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct WeatherRecord {
     // Core station info (always present)
@@ -39,28 +146,30 @@ pub struct WeatherRecord {
     pub station_name: String,
     pub date: NaiveDate,
 
+    // Country code from station metadata
+    pub country: Option<String>,
+
+    // Station elevation in metres from station metadata
+    pub elevation: Option<i32>,
+
     #[validate(range(min = -90.0, max = 90.0))]
     pub latitude: f64,
 
     #[validate(range(min = -180.0, max = 180.0))]
     pub longitude: f64,
 
-    // Optional temperature metrics (0.1°C units)
-    #[validate(range(min = -50.0, max = 50.0))]
+    // Optional temperature metrics (°C). Physical limits for these and the
+    // other values come from `ValidationRules`, not validator ranges.
     pub temp_min: Option<f32>,
 
-    #[validate(range(min = -50.0, max = 50.0))]
     pub temp_max: Option<f32>,
 
-    #[validate(range(min = -50.0, max = 50.0))]
     pub temp_avg: Option<f32>,
 
-    // Optional precipitation (0.1mm units)
-    #[validate(range(min = 0.0, max = 1000.0))]
+    // Optional precipitation (mm)
     pub precipitation: Option<f32>,
 
-    // Optional wind speed (0.1 m/s units)
-    #[validate(range(min = 0.0, max = 100.0))]
+    // Optional wind speed (m/s)
     pub wind_speed: Option<f32>,
 
     // Quality flags per metric type (original ECAD flags)
@@ -72,6 +181,71 @@ pub struct WeatherRecord {
     pub temp_validation: Option<PhysicalValidity>,
     pub precip_validation: Option<PhysicalValidity>,
     pub wind_validation: Option<PhysicalValidity>,
+
+    // Supplementary ECA&D elements (converted to physical units)
+    pub sunshine: Option<f32>,         // hours
+    pub humidity: Option<f32>,         // %
+    pub pressure: Option<f32>,         // hPa (sea level)
+    pub cloud_cover: Option<f32>,      // oktas
+    pub snow_depth: Option<f32>,       // cm
+    pub wind_gust: Option<f32>,        // m/s
+    pub wind_direction: Option<f32>,   // degrees
+    pub global_radiation: Option<f32>, // W/m²
+
+    pub sunshine_quality: Option<String>,
+    pub humidity_quality: Option<String>,
+    pub pressure_quality: Option<String>,
+    pub cloud_cover_quality: Option<String>,
+    pub snow_depth_quality: Option<String>,
+    pub wind_gust_quality: Option<String>,
+    pub wind_direction_quality: Option<String>,
+    pub global_radiation_quality: Option<String>,
+
+    pub sunshine_validation: Option<PhysicalValidity>,
+    pub humidity_validation: Option<PhysicalValidity>,
+    pub pressure_validation: Option<PhysicalValidity>,
+    pub cloud_cover_validation: Option<PhysicalValidity>,
+    pub snow_depth_validation: Option<PhysicalValidity>,
+    pub wind_gust_validation: Option<PhysicalValidity>,
+    pub wind_direction_validation: Option<PhysicalValidity>,
+    pub global_radiation_validation: Option<PhysicalValidity>,
+
+    // Source series IDs (ECAD SOUID) each value was taken from
+    pub temp_min_souid: Option<u32>,
+    pub temp_max_souid: Option<u32>,
+    pub temp_avg_souid: Option<u32>,
+    pub precip_souid: Option<u32>,
+    pub wind_souid: Option<u32>,
+    pub sunshine_souid: Option<u32>,
+    pub humidity_souid: Option<u32>,
+    pub pressure_souid: Option<u32>,
+    pub cloud_cover_souid: Option<u32>,
+    pub snow_depth_souid: Option<u32>,
+    pub wind_gust_souid: Option<u32>,
+    pub wind_direction_souid: Option<u32>,
+    pub global_radiation_souid: Option<u32>,
+
+    // Spatial QC against neighbouring stations: status and signed score
+    pub temp_spatial: Option<SpatialConsistency>,
+    pub temp_spatial_score: Option<f32>,
+    pub precip_spatial: Option<SpatialConsistency>,
+    pub precip_spatial_score: Option<f32>,
+
+    // Temporal QC flags, None when the checks were not run
+    pub qc_spike: Option<bool>,
+    pub qc_stuck: Option<bool>,
+    pub qc_precip_run: Option<bool>,
+    pub qc_tmax_below_tmin: Option<bool>,
+
+    // How a value was infilled, None for observed values
+    pub temp_min_infilled: Option<InfillMethod>,
+    pub temp_max_infilled: Option<InfillMethod>,
+    pub temp_avg_infilled: Option<InfillMethod>,
+    pub precip_infilled: Option<InfillMethod>,
+
+    // Why a value column is null, keyed by column name; only recorded when
+    // processing keeps missing observations
+    pub missing_reasons: BTreeMap<String, MissingReason>,
 }
 
 impl WeatherRecord {
@@ -91,7 +265,7 @@ impl WeatherRecord {
         precip_quality: Option<String>,
         wind_quality: Option<String>,
     ) -> Self {
-        let mut record = Self {
+        let mut record = Self::new_raw(
             station_id,
             station_name,
             date,
@@ -105,10 +279,10 @@ impl WeatherRecord {
             temp_quality,
             precip_quality,
             wind_quality,
-            temp_validation: None,
-            precip_validation: None,
-            wind_validation: None,
-        };
+            None,
+            None,
+            None,
+        );
 
         // Automatically perform physical validation
         record.perform_physical_validation();
@@ -139,6 +313,8 @@ impl WeatherRecord {
             station_id,
             station_name,
             date,
+            country: None,
+            elevation: None,
             latitude,
             longitude,
             temp_min,
@@ -152,6 +328,56 @@ impl WeatherRecord {
             temp_validation,
             precip_validation,
             wind_validation,
+            sunshine: None,
+            humidity: None,
+            pressure: None,
+            cloud_cover: None,
+            snow_depth: None,
+            wind_gust: None,
+            wind_direction: None,
+            global_radiation: None,
+            sunshine_quality: None,
+            humidity_quality: None,
+            pressure_quality: None,
+            cloud_cover_quality: None,
+            snow_depth_quality: None,
+            wind_gust_quality: None,
+            wind_direction_quality: None,
+            global_radiation_quality: None,
+            sunshine_validation: None,
+            humidity_validation: None,
+            pressure_validation: None,
+            cloud_cover_validation: None,
+            snow_depth_validation: None,
+            wind_gust_validation: None,
+            wind_direction_validation: None,
+            global_radiation_validation: None,
+            temp_min_souid: None,
+            temp_max_souid: None,
+            temp_avg_souid: None,
+            precip_souid: None,
+            wind_souid: None,
+            sunshine_souid: None,
+            humidity_souid: None,
+            pressure_souid: None,
+            cloud_cover_souid: None,
+            snow_depth_souid: None,
+            wind_gust_souid: None,
+            wind_direction_souid: None,
+            global_radiation_souid: None,
+            temp_spatial: None,
+            temp_spatial_score: None,
+            precip_spatial: None,
+            precip_spatial_score: None,
+            qc_spike: None,
+            qc_stuck: None,
+            qc_precip_run: None,
+            qc_tmax_below_tmin: None,
+            temp_min_infilled: None,
+            temp_max_infilled: None,
+            temp_avg_infilled: None,
+            precip_infilled: None,
+            missing_reasons: BTreeMap::new(),
         }
     }
 
@@ -214,15 +440,237 @@ impl WeatherRecord {
         if self.has_wind_speed() {
             metrics.push("wind_speed");
         }
+        for (metric, value, _, _) in self.supplementary_elements() {
+            if value.is_some() {
+                metrics.push(metric.column_name());
+            }
+        }
         metrics
     }
 
     pub fn metric_coverage_score(&self) -> f32 {
         let total_metrics = 3.0; // temp, precip, wind
-        let available = self.available_metrics().len() as f32;
+        let available = [
+            self.has_temperature_data(),
+            self.has_precipitation(),
+            self.has_wind_speed(),
+        ]
+        .into_iter()
+        .filter(|&present| present)
+        .count() as f32;
         available / total_metrics
     }
 
+    /// Every value column, keyed by Parquet column name
+    pub fn value_columns(&self) -> Vec<(&'static str, Option<f32>)> {
+        let mut columns = vec![
+            ("temp_min", self.temp_min),
+            ("temp_max", self.temp_max),
+            ("temp_avg", self.temp_avg),
+            ("precipitation", self.precipitation),
+            ("wind_speed", self.wind_speed),
+        ];
+        for (metric, value, _, _) in self.supplementary_elements() {
+            columns.push((metric.column_name(), value));
+        }
+        columns
+    }
+
+    /// Value, quality flag and validation for each supplementary element
+    pub fn supplementary_elements(&self) -> [ElementView<'_>; 8] {
+        [
+            (
+                WeatherMetric::Sunshine,
+                self.sunshine,
+                self.sunshine_quality.as_deref(),
+                self.sunshine_validation,
+            ),
+            (
+                WeatherMetric::Humidity,
+                self.humidity,
+                self.humidity_quality.as_deref(),
+                self.humidity_validation,
+            ),
+            (
+                WeatherMetric::Pressure,
+                self.pressure,
+                self.pressure_quality.as_deref(),
+                self.pressure_validation,
+            ),
+            (
+                WeatherMetric::CloudCover,
+                self.cloud_cover,
+                self.cloud_cover_quality.as_deref(),
+                self.cloud_cover_validation,
+            ),
+            (
+                WeatherMetric::SnowDepth,
+                self.snow_depth,
+                self.snow_depth_quality.as_deref(),
+                self.snow_depth_validation,
+            ),
+            (
+                WeatherMetric::WindGust,
+                self.wind_gust,
+                self.wind_gust_quality.as_deref(),
+                self.wind_gust_validation,
+            ),
+            (
+                WeatherMetric::WindDirection,
+                self.wind_direction,
+                self.wind_direction_quality.as_deref(),
+                self.wind_direction_validation,
+            ),
+            (
+                WeatherMetric::GlobalRadiation,
+                self.global_radiation,
+                self.global_radiation_quality.as_deref(),
+                self.global_radiation_validation,
+            ),
+        ]
+    }
+
+    /// Mutable value, quality and validation slots for a supplementary element
+    fn supplementary_slots_mut(
+        &mut self,
+        metric: &WeatherMetric,
+    ) -> Option<SupplementarySlots<'_>> {
+        match metric {
+            WeatherMetric::Sunshine => Some((
+                &mut self.sunshine,
+                &mut self.sunshine_quality,
+                &mut self.sunshine_validation,
+            )),
+            WeatherMetric::Humidity => Some((
+                &mut self.humidity,
+                &mut self.humidity_quality,
+                &mut self.humidity_validation,
+            )),
+            WeatherMetric::Pressure => Some((
+                &mut self.pressure,
+                &mut self.pressure_quality,
+                &mut self.pressure_validation,
+            )),
+            WeatherMetric::CloudCover => Some((
+                &mut self.cloud_cover,
+                &mut self.cloud_cover_quality,
+                &mut self.cloud_cover_validation,
+            )),
+            WeatherMetric::SnowDepth => Some((
+                &mut self.snow_depth,
+                &mut self.snow_depth_quality,
+                &mut self.snow_depth_validation,
+            )),
+            WeatherMetric::WindGust => Some((
+                &mut self.wind_gust,
+                &mut self.wind_gust_quality,
+                &mut self.wind_gust_validation,
+            )),
+            WeatherMetric::WindDirection => Some((
+                &mut self.wind_direction,
+                &mut self.wind_direction_quality,
+                &mut self.wind_direction_validation,
+            )),
+            WeatherMetric::GlobalRadiation => Some((
+                &mut self.global_radiation,
+                &mut self.global_radiation_quality,
+                &mut self.global_radiation_validation,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Set a supplementary element value (physical units) and its ECAD quality flag
+    pub fn set_supplementary_element(
+        &mut self,
+        metric: &WeatherMetric,
+        value: Option<f32>,
+        quality: Option<String>,
+    ) {
+        if let Some((value_slot, quality_slot, _)) = self.supplementary_slots_mut(metric) {
+            *value_slot = value;
+            *quality_slot = quality;
+        }
+    }
+
+    /// Set a previously computed physical validation for a supplementary element
+    pub fn set_supplementary_validation(
+        &mut self,
+        metric: &WeatherMetric,
+        validation: Option<PhysicalValidity>,
+    ) {
+        if let Some((_, _, validation_slot)) = self.supplementary_slots_mut(metric) {
+            *validation_slot = validation;
+        }
+    }
+
+    /// Source series ID (SOUID) for each value column, keyed by column name
+    pub fn source_ids(&self) -> [(&'static str, Option<u32>); 13] {
+        [
+            ("temp_min", self.temp_min_souid),
+            ("temp_max", self.temp_max_souid),
+            ("temp_avg", self.temp_avg_souid),
+            ("precipitation", self.precip_souid),
+            ("wind_speed", self.wind_souid),
+            ("sunshine", self.sunshine_souid),
+            ("humidity", self.humidity_souid),
+            ("pressure", self.pressure_souid),
+            ("cloud_cover", self.cloud_cover_souid),
+            ("snow_depth", self.snow_depth_souid),
+            ("wind_gust", self.wind_gust_souid),
+            ("wind_direction", self.wind_direction_souid),
+            ("global_radiation", self.global_radiation_souid),
+        ]
+    }
+
+    /// Set the source series ID for a value column (see [`Self::source_ids`])
+    pub fn set_source_id(&mut self, column: &str, souid: Option<u32>) {
+        let slot = match column {
+            "temp_min" => &mut self.temp_min_souid,
+            "temp_max" => &mut self.temp_max_souid,
+            "temp_avg" => &mut self.temp_avg_souid,
+            "precipitation" => &mut self.precip_souid,
+            "wind_speed" => &mut self.wind_souid,
+            "sunshine" => &mut self.sunshine_souid,
+            "humidity" => &mut self.humidity_souid,
+            "pressure" => &mut self.pressure_souid,
+            "cloud_cover" => &mut self.cloud_cover_souid,
+            "snow_depth" => &mut self.snow_depth_souid,
+            "wind_gust" => &mut self.wind_gust_souid,
+            "wind_direction" => &mut self.wind_direction_souid,
+            "global_radiation" => &mut self.global_radiation_souid,
+            _ => return,
+        };
+        *slot = souid;
+    }
+
+    pub fn missing_reason(&self, column: &str) -> Option<MissingReason> {
+        self.missing_reasons.get(column).copied()
+    }
+
+    /// Record why a value column is null, unless it already has a value
+    pub fn set_missing_reason(&mut self, column: &str, reason: MissingReason) {
+        let has_value = self
+            .value_columns()
+            .into_iter()
+            .any(|(name, value)| name == column && value.is_some());
+        if !has_value {
+            self.missing_reasons.insert(column.to_string(), reason);
+        }
+    }
+
+    /// Drop missing reasons for columns that have since been given a value
+    pub fn clear_filled_missing_reasons(&mut self) {
+        if self.missing_reasons.is_empty() {
+            return;
+        }
+        for (column, value) in self.value_columns() {
+            if value.is_some() {
+                self.missing_reasons.remove(column);
+            }
+        }
+    }
+
     pub fn temperature_range(&self) -> Option<f32> {
         match (self.temp_min, self.temp_max) {
             (Some(min), Some(max)) => Some(max - min),
@@ -249,6 +697,10 @@ impl WeatherRecord {
                 .as_ref()
                 .is_some_and(|q| q.contains('1'))
             || self.wind_quality.as_ref().is_some_and(|q| q.contains('1'))
+            || self
+                .supplementary_elements()
+                .iter()
+                .any(|(_, _, quality, _)| *quality == Some("1"))
     }
 
     pub fn has_missing_data(&self) -> bool {
@@ -258,75 +710,67 @@ impl WeatherRecord {
                 .as_ref()
                 .is_some_and(|q| q.contains('9'))
             || self.wind_quality.as_ref().is_some_and(|q| q.contains('9'))
+            || self
+                .supplementary_elements()
+                .iter()
+                .any(|(_, _, quality, _)| *quality == Some("9"))
     }
 
-    /// Perform physical validation on all metrics
+    /// Perform physical validation on all metrics with the built-in limits
     pub fn perform_physical_validation(&mut self) {
-        self.temp_validation = self.validate_temperature_physics();
-        self.precip_validation = self.validate_precipitation_physics();
-        self.wind_validation = self.validate_wind_physics();
-    }
-
-    /// Validate temperature values against physical limits
-    fn validate_temperature_physics(&self) -> Option<PhysicalValidity> {
-        let temps = [self.temp_min, self.temp_max, self.temp_avg];
-        let existing_temps: Vec<f32> = temps.into_iter().flatten().collect();
-
-        if existing_temps.is_empty() {
-            return None;
-        }
-
-        for &temp in &existing_temps {
-            // Physical impossibility (below absolute zero or above physically possible)
-            if !(-90.0..=60.0).contains(&temp) {
-                return Some(PhysicalValidity::Invalid);
-            }
-
-            // Suspect but possible for UK/Ireland climate
-            if !(-35.0..=45.0).contains(&temp) {
-                return Some(PhysicalValidity::Suspect);
-            }
-        }
-
-        Some(PhysicalValidity::Valid)
+        self.perform_physical_validation_with(ValidationRules::builtin());
     }
 
-    /// Validate precipitation values against physical limits
-    fn validate_precipitation_physics(&self) -> Option<PhysicalValidity> {
-        if let Some(precip) = self.precipitation {
-            // Physical impossibility
-            if !(0.0..=2000.0).contains(&precip) {
-                return Some(PhysicalValidity::Invalid);
-            }
-
-            // Suspect but possible (extreme rainfall events)
-            if precip > 500.0 {
-                return Some(PhysicalValidity::Suspect);
-            }
-
-            Some(PhysicalValidity::Valid)
+    /// Perform physical validation on all metrics with the limits `rules`
+    /// give for this record's station
+    pub fn perform_physical_validation_with(&mut self, rules: &ValidationRules) {
+        let limits = rules.for_station(self.country.as_deref(), self.latitude, self.elevation);
+
+        self.temp_validation = self.validate_temperature_physics(&limits);
+        self.precip_validation =
+            Self::validate_physics(&limits, "precipitation", self.precipitation);
+        self.wind_validation = Self::validate_physics(&limits, "wind_speed", self.wind_speed);
+
+        self.sunshine_validation = Self::validate_physics(&limits, "sunshine", self.sunshine);
+        self.humidity_validation = Self::validate_physics(&limits, "humidity", self.humidity);
+        self.pressure_validation = Self::validate_physics(&limits, "pressure", self.pressure);
+        // Oktas are whole numbers
+        self.cloud_cover_validation = if self.cloud_cover.is_some_and(|c| c.fract() != 0.0) {
+            Some(PhysicalValidity::Invalid)
         } else {
-            None
-        }
+            Self::validate_physics(&limits, "cloud_cover", self.cloud_cover)
+        };
+        self.snow_depth_validation = Self::validate_physics(&limits, "snow_depth", self.snow_depth);
+        self.wind_gust_validation = Self::validate_physics(&limits, "wind_gust", self.wind_gust);
+        self.wind_direction_validation =
+            Self::validate_physics(&limits, "wind_direction", self.wind_direction);
+        self.global_radiation_validation =
+            Self::validate_physics(&limits, "global_radiation", self.global_radiation);
     }
 
-    /// Validate wind speed values against physical limits
-    fn validate_wind_physics(&self) -> Option<PhysicalValidity> {
-        if let Some(wind) = self.wind_speed {
-            // Physical impossibility
-            if !(0.0..=120.0).contains(&wind) {
-                return Some(PhysicalValidity::Invalid);
-            }
-
-            // Suspect but possible (hurricane-force winds)
-            if wind > 50.0 {
-                return Some(PhysicalValidity::Suspect);
-            }
+    /// Validate a single value against its element's limits
+    fn validate_physics(
+        limits: &StationLimits,
+        element: &str,
+        value: Option<f32>,
+    ) -> Option<PhysicalValidity> {
+        let value = value?;
+        limits.get(element).map(|l| l.classify(value))
+    }
 
-            Some(PhysicalValidity::Valid)
-        } else {
-            None
-        }
+    /// Validate temperature values against physical limits; the worst of
+    /// the minimum, maximum and mean stands for all three
+    fn validate_temperature_physics(&self, limits: &StationLimits) -> Option<PhysicalValidity> {
+        let limits = limits.get("temperature")?;
+        [self.temp_min, self.temp_max, self.temp_avg]
+            .into_iter()
+            .flatten()
+            .map(|temp| limits.classify(temp))
+            .max_by_key(|validity| match validity {
+                PhysicalValidity::Valid => 0,
+                PhysicalValidity::Suspect => 1,
+                PhysicalValidity::Invalid => 2,
+            })
     }
 
     /// Assess overall temperature data quality combining ECAD flags and physical validation
@@ -367,11 +811,35 @@ impl WeatherRecord {
         }
     }
 
+    /// Assess supplementary element quality from its ECAD flag and physical validation
+    pub fn assess_element_quality(
+        quality: Option<&str>,
+        validation: Option<PhysicalValidity>,
+    ) -> DataQuality {
+        match (quality, validation) {
+            (Some("9"), _) => DataQuality::Missing,
+            (_, Some(PhysicalValidity::Invalid)) => DataQuality::Invalid,
+            (Some("1"), Some(PhysicalValidity::Suspect)) => DataQuality::SuspectBoth,
+            (Some("1"), _) => DataQuality::SuspectOriginal,
+            (_, Some(PhysicalValidity::Suspect)) => DataQuality::SuspectRange,
+            _ => DataQuality::Valid,
+        }
+    }
+
     /// Check if record has any invalid data (physically impossible)
     pub fn has_invalid_data(&self) -> bool {
         matches!(self.assess_temperature_quality(), DataQuality::Invalid)
             || matches!(self.assess_precipitation_quality(), DataQuality::Invalid)
             || matches!(self.assess_wind_quality(), DataQuality::Invalid)
+            || self
+                .supplementary_elements()
+                .iter()
+                .any(|(_, _, quality, validation)| {
+                    matches!(
+                        Self::assess_element_quality(*quality, *validation),
+                        DataQuality::Invalid
+                    )
+                })
     }
 
     /// Check if record has high-quality data (valid with no flags)
@@ -382,8 +850,18 @@ impl WeatherRecord {
             || matches!(self.assess_precipitation_quality(), DataQuality::Valid);
         let wind_ok = self.wind_validation.is_none()
             || matches!(self.assess_wind_quality(), DataQuality::Valid);
+        let supplementary_ok =
+            self.supplementary_elements()
+                .iter()
+                .all(|(_, _, quality, validation)| {
+                    validation.is_none()
+                        || matches!(
+                            Self::assess_element_quality(*quality, *validation),
+                            DataQuality::Valid
+                        )
+                });
 
-        temp_ok && precip_ok && wind_ok
+        temp_ok && precip_ok && wind_ok && supplementary_ok
     }
 }
 
@@ -391,6 +869,7 @@ pub struct WeatherRecordBuilder {
     station_id: Option<u32>,
     station_name: Option<String>,
     date: Option<NaiveDate>,
+    country: Option<String>,
     latitude: Option<f64>,
     longitude: Option<f64>,
     temp_min: Option<f32>,
@@ -404,6 +883,7 @@ pub struct WeatherRecordBuilder {
     temp_validation: Option<PhysicalValidity>,
     precip_validation: Option<PhysicalValidity>,
     wind_validation: Option<PhysicalValidity>,
+    supplementary: Vec<(WeatherMetric, f32, Option<String>)>,
 }
 
 impl Default for WeatherRecordBuilder {
@@ -418,6 +898,7 @@ impl WeatherRecordBuilder {
             station_id: None,
             station_name: None,
             date: None,
+            country: None,
             latitude: None,
             longitude: None,
             temp_min: None,
@@ -431,6 +912,7 @@ impl WeatherRecordBuilder {
             temp_validation: None,
             precip_validation: None,
             wind_validation: None,
+            supplementary: Vec::new(),
         }
     }
 
@@ -449,6 +931,11 @@ impl WeatherRecordBuilder {
         self
     }
 
+    pub fn country(mut self, country: String) -> Self {
+        self.country = Some(country);
+        self
+    }
+
     pub fn coordinates(mut self, latitude: f64, longitude: f64) -> Self {
         self.latitude = Some(latitude);
         self.longitude = Some(longitude);
@@ -502,40 +989,56 @@ impl WeatherRecordBuilder {
         self
     }
 
+    /// Set a supplementary element (sunshine, humidity, ...) in physical units
+    pub fn supplementary_element(
+        mut self,
+        metric: WeatherMetric,
+        value: f32,
+        quality: Option<String>,
+    ) -> Self {
+        self.supplementary.push((metric, value, quality));
+        self
+    }
+
     pub fn build(self) -> Result<WeatherRecord> {
-        let mut record = WeatherRecord {
-            station_id: self
-                .station_id
+        let mut record = WeatherRecord::new_raw(
+            self.station_id
                 .ok_or_else(|| ProcessingError::MissingData("station_id".to_string()))?,
-            station_name: self
-                .station_name
+            self.station_name
                 .ok_or_else(|| ProcessingError::MissingData("station_name".to_string()))?,
-            date: self
-                .date
+            self.date
                 .ok_or_else(|| ProcessingError::MissingData("date".to_string()))?,
-            latitude: self
-                .latitude
+            self.latitude
                 .ok_or_else(|| ProcessingError::MissingData("latitude".to_string()))?,
-            longitude: self
-                .longitude
+            self.longitude
                 .ok_or_else(|| ProcessingError::MissingData("longitude".to_string()))?,
-            temp_min: self.temp_min,
-            temp_max: self.temp_max,
-            temp_avg: self.temp_avg,
-            precipitation: self.precipitation,
-            wind_speed: self.wind_speed,
-            temp_quality: self.temp_quality,
-            precip_quality: self.precip_quality,
-            wind_quality: self.wind_quality,
-            temp_validation: self.temp_validation,
-            precip_validation: self.precip_validation,
-            wind_validation: self.wind_validation,
-        };
+            self.temp_min,
+            self.temp_max,
+            self.temp_avg,
+            self.precipitation,
+            self.wind_speed,
+            self.temp_quality,
+            self.precip_quality,
+            self.wind_quality,
+            self.temp_validation,
+            self.precip_validation,
+            self.wind_validation,
+        );
+
+        record.country = self.country;
+
+        for (metric, value, quality) in self.supplementary {
+            record.set_supplementary_element(&metric, Some(value), quality);
+        }
 
         // Perform physical validation if not already set
         if record.temp_validation.is_none()
             || record.precip_validation.is_none()
             || record.wind_validation.is_none()
+            || record
+                .supplementary_elements()
+                .iter()
+                .any(|(_, value, _, validation)| value.is_some() && validation.is_none())
         {
             record.perform_physical_validation();
         }
@@ -625,6 +1128,30 @@ mod tests {
         assert!(record.validate_relationships().is_err());
     }
 
+    #[test]
+    fn test_extreme_values_left_to_validation_rules() {
+        let date = NaiveDate::from_ymd_opt(2023, 1, 15).unwrap();
+
+        // -51 °C and 1200 mm are rare but possible; only the QC rules judge them
+        let record = WeatherRecord::new(
+            12345,
+            "Test Station".to_string(),
+            date,
+            67.0,
+            25.0,
+            Some(-52.0),
+            Some(-50.0),
+            Some(-51.0),
+            Some(1200.0),
+            None,
+            Some("000".to_string()),
+            None,
+            None,
+        );
+
+        assert!(record.validate_relationships().is_ok());
+    }
+
     #[test]
     fn test_builder_pattern() {
         let date = NaiveDate::from_ymd_opt(2023, 7, 15).unwrap();
@@ -689,4 +1216,36 @@ mod tests {
         assert!(record.has_suspect_data());
         assert!(!record.has_missing_data());
     }
+
+    #[test]
+    fn test_supplementary_elements() {
+        let date = NaiveDate::from_ymd_opt(2023, 7, 15).unwrap();
+
+        let record = WeatherRecord::builder()
+            .station_id(12345)
+            .station_name("Test Station".to_string())
+            .date(date)
+            .coordinates(51.5074, -0.1278)
+            .supplementary_element(WeatherMetric::Pressure, 1013.2, Some("0".to_string()))
+            .supplementary_element(WeatherMetric::Humidity, 150.0, Some("0".to_string()))
+            .supplementary_element(WeatherMetric::CloudCover, 4.5, Some("1".to_string()))
+            .supplementary_element(WeatherMetric::Sunshine, 22.0, Some("0".to_string()))
+            .build()
+            .unwrap();
+
+        assert_eq!(record.pressure, Some(1013.2));
+        assert_eq!(record.pressure_validation, Some(PhysicalValidity::Valid));
+        assert_eq!(record.humidity_validation, Some(PhysicalValidity::Invalid));
+        assert_eq!(
+            record.cloud_cover_validation,
+            Some(PhysicalValidity::Invalid)
+        );
+        assert_eq!(record.sunshine_validation, Some(PhysicalValidity::Suspect));
+        assert_eq!(record.snow_depth_validation, None);
+
+        assert!(record.has_invalid_data());
+        assert!(record.has_suspect_data());
+        assert!(record.available_metrics().contains(&"pressure"));
+        assert_eq!(record.metric_coverage_score(), 0.0);
+    }
 }
@@ -0,0 +1,777 @@
+//! End-to-end processing for embedding the crate in other programs.
+//!
+//! A [`Pipeline`] reads one or more ECA&D archives station by station,
+//! runs the chosen QC stages and hands the records to its sinks, reporting
+//! progress as it goes and stopping early when cancelled.
+
+use crate::archive::processor::{build_thread_pool, finalize_station_records};
+use crate::archive::{
+    ArchiveIndex, ArchiveMetadata, ArchiveProcessor, MultiArchiveProcessor, WeatherMetric,
+};
+use crate::error::{ProcessingError, Result};
+use crate::models::{DateFilter, SourceMetadata, StationSelection, ValidationRules, WeatherRecord};
+use crate::processors::{
+    CompletenessChecker, CompletenessReport, IntegrityReport, PhaseTiming, RecordCounts,
+    RunWarning, SpatialQcSummary, SpatialQualityControl, TemporalQualityControl, WarningCode,
+};
+use crate::utils::constants::DEFAULT_ROW_GROUP_SIZE;
+use crate::utils::generate_sources_filename;
+use crate::writers::{ParquetWriter, PartitionKey, WeatherStreamWriter};
+use chrono::NaiveDate;
+use rayon::ThreadPool;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
+use tracing::{debug_span, info};
+
+type ProgressCallback = Box<dyn Fn(&PipelineProgress) + Send + Sync>;
+type RecordCallback = Box<dyn FnMut(Vec<WeatherRecord>) -> Result<()> + Send>;
+
+/// Shared flag for stopping a running pipeline from another task or thread.
+///
+/// The pipeline checks it before each archive and each station, and fails
+/// with [`ProcessingError::Cancelled`] once it is set.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// A step completed by a running pipeline
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipelineProgress {
+    /// An archive's station metadata was read and its data entries listed
+    ArchiveIndexed { path: PathBuf, stations: usize },
+    /// A station's records were merged, checked and passed on to the sinks
+    StationProcessed {
+        station_id: u32,
+        records: usize,
+        completed: usize,
+        total: usize,
+    },
+    /// A file or dataset directory was written
+    OutputWritten { path: PathBuf, records: usize },
+}
+
+/// Where a pipeline reads archives from
+#[derive(Debug, Clone)]
+enum PipelineInput {
+    Archive(PathBuf),
+    Directory {
+        path: PathBuf,
+        file_pattern: Option<String>,
+    },
+}
+
+/// Where a pipeline's records go
+enum PipelineSink {
+    /// A single Parquet file, written as stations complete, with its
+    /// sources table alongside
+    Parquet {
+        path: PathBuf,
+        writer: ParquetWriter,
+    },
+    /// A Hive-partitioned dataset directory, written once all records are
+    /// in, with its sources table alongside
+    Partitioned {
+        dir: PathBuf,
+        keys: Vec<PartitionKey>,
+        writer: ParquetWriter,
+    },
+    /// A caller-supplied function, given each station's records. Held in a
+    /// mutex so the pipeline stays `Sync` and its run can be spawned.
+    Records(Mutex<RecordCallback>),
+}
+
+/// What a pipeline run produced
+#[derive(Debug, Clone, Default)]
+pub struct PipelineOutput {
+    /// Every archive read, in the order read
+    pub archives: Vec<(PathBuf, ArchiveMetadata)>,
+    pub integrity: IntegrityReport,
+    pub counts: RecordCounts,
+    /// Completeness of every series read, when requested or a minimum was set
+    pub completeness: Option<CompletenessReport>,
+    pub spatial_qc: Option<SpatialQcSummary>,
+    /// Every warning raised, including those in the integrity report
+    pub warnings: Vec<RunWarning>,
+    /// Files and dataset directories written
+    pub outputs: Vec<PathBuf>,
+    /// Time spent inspecting archives, processing stations and writing
+    pub timings: Vec<PhaseTiming>,
+}
+
+/// Builder for an end-to-end processing run: archives in, checked
+/// `WeatherRecord`s out.
+///
+/// ```no_run
+/// # async fn example() -> ecad_processor::Result<()> {
+/// use ecad_processor::pipeline::{CancellationToken, Pipeline};
+/// use ecad_processor::writers::ParquetWriter;
+///
+/// let cancel = CancellationToken::new();
+/// let output = Pipeline::new()
+///     .with_archive("UK_ALL.zip")
+///     .with_parquet_output("weather.parquet", ParquetWriter::new())
+///     .with_progress(|progress| println!("{:?}", progress))
+///     .with_cancellation(cancel.clone())
+///     .run()
+///     .await?;
+/// println!("{} records", output.counts.total_records);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Pipeline {
+    inputs: Vec<PipelineInput>,
+    station_selection: Option<StationSelection>,
+    date_filter: DateFilter,
+    metrics: Option<Vec<WeatherMetric>>,
+    validation_rules: Arc<ValidationRules>,
+    keep_missing: bool,
+    temporal_qc: Option<TemporalQualityControl>,
+    spatial_qc: Option<SpatialQualityControl>,
+    min_completeness: Option<f64>,
+    completeness_report: bool,
+    thread_pool: Option<Arc<ThreadPool>>,
+    batch_size: usize,
+    sinks: Vec<PipelineSink>,
+    progress: Option<ProgressCallback>,
+    cancellation: CancellationToken,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self {
+            inputs: Vec::new(),
+            station_selection: None,
+            date_filter: DateFilter::default(),
+            metrics: None,
+            validation_rules: Arc::new(ValidationRules::default()),
+            keep_missing: false,
+            temporal_qc: None,
+            spatial_qc: None,
+            min_completeness: None,
+            completeness_report: false,
+            thread_pool: None,
+            batch_size: DEFAULT_ROW_GROUP_SIZE,
+            sinks: Vec::new(),
+            progress: None,
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    /// Read an archive; archives covering the same station are merged into
+    /// the same (station, date) records
+    pub fn with_archive(mut self, path: impl Into<PathBuf>) -> Self {
+        self.inputs.push(PipelineInput::Archive(path.into()));
+        self
+    }
+
+    /// Read every zip archive in a directory whose name contains
+    /// `file_pattern`, skipping any that cannot be inspected with a warning
+    pub fn with_archive_directory(
+        mut self,
+        path: impl Into<PathBuf>,
+        file_pattern: Option<&str>,
+    ) -> Self {
+        self.inputs.push(PipelineInput::Directory {
+            path: path.into(),
+            file_pattern: file_pattern.map(str::to_string),
+        });
+        self
+    }
+
+    /// Only read the data of stations matching `selection`
+    pub fn with_station_selection(mut self, selection: StationSelection) -> Self {
+        self.station_selection = Some(selection);
+        self
+    }
+
+    /// Skip data lines dated outside `date_filter`
+    pub fn with_date_filter(mut self, date_filter: DateFilter) -> Self {
+        self.date_filter = date_filter;
+        self
+    }
+
+    /// Only read these elements; by default every element in the archives
+    pub fn with_metrics(mut self, metrics: Vec<WeatherMetric>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Classify physical validity with `rules` instead of the built-in limits
+    pub fn with_validation_rules(mut self, rules: Arc<ValidationRules>) -> Self {
+        self.validation_rules = rules;
+        self
+    }
+
+    /// Keep missing observations as rows with a missing reason
+    pub fn with_keep_missing(mut self, keep_missing: bool) -> Self {
+        self.keep_missing = keep_missing;
+        self
+    }
+
+    /// Flag spikes, stuck sensors and implausible wet runs in each series
+    pub fn with_temporal_qc(mut self, temporal_qc: TemporalQualityControl) -> Self {
+        self.temporal_qc = Some(temporal_qc);
+        self
+    }
+
+    /// Score values against neighbouring stations.
+    ///
+    /// This needs every station at once, so records are held in memory until
+    /// all stations are read, rather than passed on as each completes.
+    pub fn with_spatial_qc(mut self, spatial_qc: SpatialQualityControl) -> Self {
+        self.spatial_qc = Some(spatial_qc);
+        self
+    }
+
+    /// Drop stations whose series are less than `percent` complete
+    pub fn with_min_completeness(mut self, percent: f64) -> Result<Self> {
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(ProcessingError::Config(
+                "Minimum completeness must be between 0 and 100".to_string(),
+            ));
+        }
+        self.min_completeness = Some(percent);
+        Ok(self)
+    }
+
+    /// Check the completeness of every series read without dropping any
+    /// stations; a minimum completeness implies this
+    pub fn with_completeness_report(mut self) -> Self {
+        self.completeness_report = true;
+        self
+    }
+
+    /// Parse archive entries on a dedicated pool of `max_workers` threads
+    pub fn with_max_workers(mut self, max_workers: usize) -> Result<Self> {
+        self.thread_pool = Some(build_thread_pool(max_workers)?);
+        Ok(self)
+    }
+
+    /// Records encoded per Parquet write
+    pub fn with_batch_size(mut self, batch_size: usize) -> Result<Self> {
+        if batch_size == 0 {
+            return Err(ProcessingError::Config(
+                "Batch size must be at least 1".to_string(),
+            ));
+        }
+        self.batch_size = batch_size;
+        Ok(self)
+    }
+
+    /// Write records to a Parquet file, and source series to its sources table
+    pub fn with_parquet_output(mut self, path: impl Into<PathBuf>, writer: ParquetWriter) -> Self {
+        self.sinks.push(PipelineSink::Parquet {
+            path: path.into(),
+            writer,
+        });
+        self
+    }
+
+    /// Write records to a dataset directory partitioned by `keys`
+    pub fn with_partitioned_output(
+        mut self,
+        dir: impl Into<PathBuf>,
+        keys: Vec<PartitionKey>,
+        writer: ParquetWriter,
+    ) -> Self {
+        self.sinks.push(PipelineSink::Partitioned {
+            dir: dir.into(),
+            keys,
+            writer,
+        });
+        self
+    }
+
+    /// Hand each station's records to `sink`; an error from it stops the run
+    pub fn with_record_sink(
+        mut self,
+        sink: impl FnMut(Vec<WeatherRecord>) -> Result<()> + Send + 'static,
+    ) -> Self {
+        self.sinks
+            .push(PipelineSink::Records(Mutex::new(Box::new(sink))));
+        self
+    }
+
+    /// Call `progress` after each archive, station and output
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(&PipelineProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Stop the run, failing with `ProcessingError::Cancelled`, once `token`
+    /// is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Run the pipeline to completion.
+    ///
+    /// Files written by a run that fails or is cancelled are removed.
+    pub async fn run(mut self) -> Result<PipelineOutput> {
+        if self.inputs.is_empty() {
+            return Err(ProcessingError::Config(
+                "Pipeline has no input archives".to_string(),
+            ));
+        }
+
+        let mut output = PipelineOutput::default();
+        let mut sinks = std::mem::take(&mut self.sinks)
+            .into_iter()
+            .map(|sink| OpenSink::open(sink, self.batch_size))
+            .collect::<Result<Vec<_>>>()?;
+
+        match self.process(&mut sinks, &mut output).await {
+            Ok(sources) => {
+                let phase = Instant::now();
+                let mut sinks = sinks.into_iter();
+                let finished: Result<()> = sinks.by_ref().try_for_each(|sink| {
+                    for (path, records) in sink.finish(&sources, self.batch_size)? {
+                        self.report(PipelineProgress::OutputWritten {
+                            path: path.clone(),
+                            records,
+                        });
+                        output.outputs.push(path);
+                    }
+                    Ok(())
+                });
+                if let Err(e) = finished {
+                    // Don't leave the outputs of a failed run half written
+                    for sink in sinks {
+                        sink.abort();
+                    }
+                    for path in output.outputs.drain(..) {
+                        remove_output(&path);
+                    }
+                    return Err(e);
+                }
+                output.add_timing("write", phase);
+                if output.counts.total_records == 0 {
+                    output.warnings.push(RunWarning::new(
+                        WarningCode::NoRecords,
+                        "No records to write",
+                    ));
+                }
+                Ok(output)
+            }
+            Err(e) => {
+                for sink in sinks {
+                    sink.abort();
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Read every station into the sinks, returning the archives' source
+    /// series for the sources table
+    async fn process(
+        &self,
+        sinks: &mut [OpenSink],
+        output: &mut PipelineOutput,
+    ) -> Result<Vec<SourceMetadata>> {
+        let phase = Instant::now();
+        let archives = self.resolve_inputs(&mut output.warnings)?;
+
+        let mut indexed = Vec::with_capacity(archives.len());
+        let mut sources = BTreeMap::new();
+        for path in &archives {
+            self.check_cancelled()?;
+            let (mut processor, index) = self.index_archive(path).await?;
+            for source in processor.read_sources(path)? {
+                sources
+                    .entry((source.staid, source.souid))
+                    .or_insert(source);
+            }
+            output
+                .integrity
+                .warnings
+                .extend(index.warnings.iter().cloned());
+            output
+                .warnings
+                .extend(processor.metadata().warnings.iter().cloned());
+            output
+                .archives
+                .push((path.clone(), processor.metadata().clone()));
+            self.report(PipelineProgress::ArchiveIndexed {
+                path: path.clone(),
+                stations: index.station_files.len(),
+            });
+            indexed.push((processor, index));
+        }
+
+        let station_ids: BTreeSet<u32> = indexed
+            .iter()
+            .flat_map(|(_, index)| index.station_files.keys().copied())
+            .collect();
+        let total = station_ids.len();
+        output.add_timing("inspect", phase);
+        let phase = Instant::now();
+        info!(
+            archives = indexed.len(),
+            stations = total,
+            "Running pipeline"
+        );
+
+        let mut completeness = (self.completeness_report || self.min_completeness.is_some())
+            .then(CompletenessReport::default);
+        let mut dropped_stations = 0;
+        let mut held = Vec::new();
+
+        for (completed, station_id) in station_ids.into_iter().enumerate() {
+            self.check_cancelled()?;
+
+            // Later archives fill in metrics on the same (station, date) records
+            let mut weather_data: HashMap<(u32, NaiveDate), WeatherRecord> = HashMap::new();
+            for ((processor, index), path) in indexed.iter().zip(&archives) {
+                let _span = debug_span!("archive", path = %path.display()).entered();
+                processor.load_station(index, station_id, &mut weather_data)?;
+            }
+
+            let _span = debug_span!("station", station_id).entered();
+            let mut records = finalize_station_records(weather_data, &self.validation_rules);
+            output
+                .integrity
+                .merge(ArchiveProcessor::calculate_integrity_report(&records));
+            if let Some(temporal_qc) = &self.temporal_qc {
+                output
+                    .integrity
+                    .temporal_qc
+                    .merge(&temporal_qc.check(&mut records));
+            }
+            if let Some(completeness) = completeness.as_mut() {
+                let report = CompletenessChecker::new().check(&records);
+                let below = self
+                    .min_completeness
+                    .is_some_and(|min_percent| !report.stations_below(min_percent).is_empty());
+                if below {
+                    records.clear();
+                    dropped_stations += 1;
+                }
+                completeness.merge(report);
+            }
+
+            self.report(PipelineProgress::StationProcessed {
+                station_id,
+                records: records.len(),
+                completed: completed + 1,
+                total,
+            });
+            if records.is_empty() {
+                continue;
+            }
+            if self.spatial_qc.is_some() {
+                held.extend(records);
+            } else {
+                output.counts.add_records(&records);
+                write_to_sinks(sinks, records)?;
+            }
+        }
+
+        if let Some(spatial_qc) = &self.spatial_qc {
+            self.check_cancelled()?;
+            output.spatial_qc = Some(spatial_qc.check(&mut held));
+            output.counts.add_records(&held);
+            if !held.is_empty() {
+                write_to_sinks(sinks, held)?;
+            }
+        }
+
+        if let Some(min_percent) = self.min_completeness.filter(|_| dropped_stations > 0) {
+            output.warnings.push(RunWarning::new(
+                WarningCode::StationsBelowCompleteness,
+                format!(
+                    "Dropped {} stations below {:.1}% completeness",
+                    dropped_stations, min_percent
+                ),
+            ));
+        }
+        output.completeness = completeness;
+        output.add_timing("process", phase);
+        output
+            .warnings
+            .extend(output.integrity.warnings.iter().cloned());
+
+        Ok(sources.into_values().collect())
+    }
+
+    /// The archive paths to read, in input order, with those in a directory
+    /// sorted by file name
+    fn resolve_inputs(&self, warnings: &mut Vec<RunWarning>) -> Result<Vec<PathBuf>> {
+        let mut archives = Vec::new();
+        for input in &self.inputs {
+            match input {
+                PipelineInput::Archive(path) => archives.push(path.clone()),
+                PipelineInput::Directory { path, file_pattern } => {
+                    let directory =
+                        MultiArchiveProcessor::from_directory(path, file_pattern.as_deref())?;
+                    warnings.extend(directory.warnings().iter().cloned());
+                    archives.extend(directory.archives().iter().map(|a| a.path.clone()));
+                }
+            }
+        }
+        Ok(archives)
+    }
+
+    async fn index_archive(&self, path: &Path) -> Result<(ArchiveProcessor, ArchiveIndex)> {
+        let mut processor = ArchiveProcessor::from_zip(path)
+            .await?
+            .with_date_filter(self.date_filter)
+            .with_validation_rules(Arc::clone(&self.validation_rules))
+            .with_keep_missing(self.keep_missing);
+        if let Some(thread_pool) = &self.thread_pool {
+            processor = processor.with_thread_pool(Arc::clone(thread_pool));
+        }
+        if let Some(selection) = &self.station_selection {
+            processor = processor.with_station_selection(selection.clone());
+        }
+        if let Some(metrics) = &self.metrics {
+            processor = processor.with_metrics(metrics.clone());
+        }
+
+        let index = debug_span!("archive", path = %path.display())
+            .in_scope(|| processor.index_archive(path))?;
+        Ok((processor, index))
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        if self.cancellation.is_cancelled() {
+            return Err(ProcessingError::Cancelled);
+        }
+        Ok(())
+    }
+
+    fn report(&self, progress: PipelineProgress) {
+        if let Some(callback) = &self.progress {
+            callback(&progress);
+        }
+    }
+}
+
+impl PipelineOutput {
+    fn add_timing(&mut self, phase: &str, started: Instant) {
+        self.timings.push(PhaseTiming {
+            phase: phase.to_string(),
+            seconds: started.elapsed().as_secs_f64(),
+        });
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A sink ready to receive records during a run
+enum OpenSink {
+    Parquet {
+        path: PathBuf,
+        writer: ParquetWriter,
+        stream: Box<WeatherStreamWriter>,
+    },
+    Partitioned {
+        dir: PathBuf,
+        keys: Vec<PartitionKey>,
+        writer: ParquetWriter,
+        records: Vec<WeatherRecord>,
+    },
+    Records(RecordCallback),
+}
+
+impl OpenSink {
+    /// Create output files, failing before any work is done if they can't be
+    fn open(sink: PipelineSink, batch_size: usize) -> Result<Self> {
+        match sink {
+            PipelineSink::Parquet { path, writer } => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let stream = Box::new(writer.create_weather_stream(&path, batch_size)?);
+                Ok(OpenSink::Parquet {
+                    path,
+                    writer,
+                    stream,
+                })
+            }
+            PipelineSink::Partitioned { dir, keys, writer } => {
+                if keys.is_empty() {
+                    return Err(ProcessingError::Config(
+                        "Partitioned output needs at least one partition key".to_string(),
+                    ));
+                }
+                if dir.is_dir() && std::fs::read_dir(&dir)?.next().is_some() {
+                    return Err(ProcessingError::Config(format!(
+                        "Dataset directory is not empty: {}",
+                        dir.display()
+                    )));
+                }
+                Ok(OpenSink::Partitioned {
+                    dir,
+                    keys,
+                    writer,
+                    records: Vec::new(),
+                })
+            }
+            PipelineSink::Records(callback) => Ok(OpenSink::Records(
+                callback
+                    .into_inner()
+                    .unwrap_or_else(PoisonError::into_inner),
+            )),
+        }
+    }
+
+    fn write(&mut self, records: Vec<WeatherRecord>) -> Result<()> {
+        match self {
+            OpenSink::Parquet { stream, .. } => stream.write_records(records),
+            OpenSink::Partitioned { records: held, .. } => {
+                held.extend(records);
+                Ok(())
+            }
+            OpenSink::Records(callback) => callback(records),
+        }
+    }
+
+    /// Close the sink, returning each file or dataset directory it wrote
+    /// with its row count
+    fn finish(
+        self,
+        sources: &[SourceMetadata],
+        batch_size: usize,
+    ) -> Result<Vec<(PathBuf, usize)>> {
+        let (path, writer, written) = match self {
+            OpenSink::Parquet {
+                path,
+                writer,
+                stream,
+            } => {
+                let written = match stream.finish() {
+                    Ok(written) => written,
+                    Err(e) => {
+                        let _ = std::fs::remove_file(&path);
+                        return Err(e);
+                    }
+                };
+                if written == 0 {
+                    std::fs::remove_file(&path)?;
+                    return Ok(Vec::new());
+                }
+                (path, writer, written)
+            }
+            OpenSink::Partitioned {
+                dir,
+                keys,
+                writer,
+                records,
+            } => {
+                if records.is_empty() {
+                    return Ok(Vec::new());
+                }
+                // The directory was empty or missing when the sink was
+                // opened, so anything in it now is a partial write
+                if let Err(e) =
+                    writer.write_weather_records_partitioned(&records, &dir, &keys, batch_size)
+                {
+                    if dir.is_dir() {
+                        let _ = std::fs::remove_dir_all(&dir);
+                    }
+                    return Err(e);
+                }
+                (dir, writer, records.len())
+            }
+            OpenSink::Records(_) => return Ok(Vec::new()),
+        };
+
+        let sources_path = generate_sources_filename(&path);
+        let mut outputs = vec![(path, written)];
+        if !sources.is_empty() {
+            if let Err(e) = writer.write_sources(sources, &sources_path) {
+                remove_output(&outputs[0].0);
+                let _ = std::fs::remove_file(&sources_path);
+                return Err(e);
+            }
+            outputs.push((sources_path, sources.len()));
+        }
+        Ok(outputs)
+    }
+
+    /// Remove a partly written output after a failed or cancelled run
+    fn abort(self) {
+        if let OpenSink::Parquet { path, stream, .. } = self {
+            drop(stream);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Remove a file or dataset directory written by a sink
+fn remove_output(path: &Path) {
+    let _ = if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    };
+}
+
+/// Pass records to every sink, cloning them for all but the last
+fn write_to_sinks(sinks: &mut [OpenSink], records: Vec<WeatherRecord>) -> Result<()> {
+    let Some((last, rest)) = sinks.split_last_mut() else {
+        return Ok(());
+    };
+    for sink in rest {
+        sink.write(records.clone())?;
+    }
+    last.write(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation_token_shared_between_clones() {
+        let token = CancellationToken::new();
+        let handle = token.clone();
+        assert!(!token.is_cancelled());
+        handle.cancel();
+        assert!(token.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_configuration_rejected() {
+        assert!(Pipeline::new().with_min_completeness(120.0).is_err());
+        assert!(Pipeline::new().with_batch_size(0).is_err());
+
+        let result = Pipeline::new().run().await;
+        assert!(matches!(result, Err(ProcessingError::Config(_))));
+
+        // Cancelled before any archive is opened
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = Pipeline::new()
+            .with_archive("missing.zip")
+            .with_cancellation(token)
+            .run()
+            .await;
+        assert!(matches!(result, Err(ProcessingError::Cancelled)));
+    }
+}
@@ -0,0 +1,376 @@
+use crate::error::{ProcessingError, Result};
+use crate::models::WeatherRecord;
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+/// A run of consecutive days without a value inside a series
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GapInterval {
+    /// First missing day
+    pub start: NaiveDate,
+    /// Last missing day
+    pub end: NaiveDate,
+    pub days: i64,
+    /// Days of the gap the source reported as missing, rather than omitted
+    pub days_reported_missing: usize,
+}
+
+/// Share of a calendar year's days with a value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct YearCoverage {
+    pub year: i32,
+    pub days_present: usize,
+    pub days_in_year: usize,
+    pub percent: f64,
+}
+
+/// Completeness of one station's series for one metric
+#[derive(Debug, Clone, Serialize)]
+pub struct SeriesCompleteness {
+    pub station_id: u32,
+    pub station_name: String,
+    /// Parquet column name, e.g. `temp_max` or `precipitation`
+    pub metric: String,
+    pub first_date: NaiveDate,
+    pub last_date: NaiveDate,
+    pub days_present: usize,
+    /// Days the source listed without a value, with a missing reason
+    pub days_reported_missing: usize,
+    /// Days from the first to the last observation, inclusive, counting
+    /// days reported as missing
+    pub days_expected: usize,
+    pub completeness_percent: f64,
+    pub longest_gap_days: i64,
+    pub gaps: Vec<GapInterval>,
+    pub yearly: Vec<YearCoverage>,
+}
+
+/// Per-station, per-metric completeness findings
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CompletenessReport {
+    pub series: Vec<SeriesCompleteness>,
+}
+
+impl CompletenessReport {
+    /// Fold another report (e.g. for a further station) into this one
+    pub fn merge(&mut self, other: CompletenessReport) {
+        self.series.extend(other.series);
+    }
+
+    /// Completeness of a station's most complete metric
+    pub fn station_completeness(&self, station_id: u32) -> Option<f64> {
+        self.series
+            .iter()
+            .filter(|s| s.station_id == station_id)
+            .map(|s| s.completeness_percent)
+            .reduce(f64::max)
+    }
+
+    /// Stations where no metric reaches `min_percent` completeness
+    pub fn stations_below(&self, min_percent: f64) -> BTreeSet<u32> {
+        let stations: BTreeSet<u32> = self.series.iter().map(|s| s.station_id).collect();
+        stations
+            .into_iter()
+            .filter(|id| {
+                self.station_completeness(*id)
+                    .map_or(true, |pct| pct < min_percent)
+            })
+            .collect()
+    }
+
+    pub fn summary(&self) -> String {
+        let stations: BTreeSet<u32> = self.series.iter().map(|s| s.station_id).collect();
+        let gaps: usize = self.series.iter().map(|s| s.gaps.len()).sum();
+        let mean = if self.series.is_empty() {
+            0.0
+        } else {
+            self.series
+                .iter()
+                .map(|s| s.completeness_percent)
+                .sum::<f64>()
+                / self.series.len() as f64
+        };
+
+        let mut summary = format!(
+            "Completeness Summary:\n  Stations: {}\n  Series: {}\n  Mean completeness: {:.1}%\n  Gaps: {}",
+            stations.len(),
+            self.series.len(),
+            mean,
+            gaps
+        );
+        let reported_missing: usize = self.series.iter().map(|s| s.days_reported_missing).sum();
+        if reported_missing > 0 {
+            summary.push_str(&format!("\n  Days reported missing: {}", reported_missing));
+        }
+        if let Some(longest) = self.series.iter().max_by_key(|s| s.longest_gap_days) {
+            if longest.longest_gap_days > 0 {
+                summary.push_str(&format!(
+                    "\n  Longest gap: {} days (station {} {})",
+                    longest.longest_gap_days, longest.station_id, longest.metric
+                ));
+            }
+        }
+        summary
+    }
+
+    /// Write the report as pretty-printed JSON
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, self).map_err(|e| ProcessingError::Io(e.into()))
+    }
+}
+
+/// Dates with a value and dates reported missing
+type SeriesDates = (BTreeSet<NaiveDate>, BTreeSet<NaiveDate>);
+
+/// Analyses gaps and coverage of every station and metric in weather records
+pub struct CompletenessChecker;
+
+impl CompletenessChecker {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn check(&self, records: &[WeatherRecord]) -> CompletenessReport {
+        let mut stations: BTreeMap<u32, Vec<&WeatherRecord>> = BTreeMap::new();
+        for record in records {
+            stations.entry(record.station_id).or_default().push(record);
+        }
+
+        let mut report = CompletenessReport::default();
+        for records in stations.into_values() {
+            // Dates with a value and dates reported missing, per metric column
+            let mut metric_dates: BTreeMap<&'static str, SeriesDates> = BTreeMap::new();
+            for record in &records {
+                for (column, value) in record.value_columns() {
+                    if value.is_some() {
+                        metric_dates
+                            .entry(column)
+                            .or_default()
+                            .0
+                            .insert(record.date);
+                    } else if record.missing_reason(column).is_some() {
+                        metric_dates
+                            .entry(column)
+                            .or_default()
+                            .1
+                            .insert(record.date);
+                    }
+                }
+            }
+
+            let first = records[0];
+            for (metric, (dates, missing)) in metric_dates {
+                report.series.push(Self::check_series(
+                    first.station_id,
+                    &first.station_name,
+                    metric,
+                    &dates,
+                    &missing,
+                ));
+            }
+        }
+
+        report
+    }
+
+    fn check_series(
+        station_id: u32,
+        station_name: &str,
+        metric: &str,
+        dates: &BTreeSet<NaiveDate>,
+        missing: &BTreeSet<NaiveDate>,
+    ) -> SeriesCompleteness {
+        let bounds = |set: &BTreeSet<NaiveDate>| set.first().copied().zip(set.last().copied());
+        let (first_date, last_date) = match (bounds(dates), bounds(missing)) {
+            (Some((f, l)), Some((mf, ml))) => (f.min(mf), l.max(ml)),
+            (Some(span), None) | (None, Some(span)) => span,
+            (None, None) => unreachable!("series has at least one date"),
+        };
+
+        // Days before the first and after the last value close the gaps at
+        // either end of the series
+        let before = first_date.pred_opt().unwrap_or(first_date);
+        let after = last_date.succ_opt().unwrap_or(last_date);
+        let edges: Vec<NaiveDate> = std::iter::once(before)
+            .chain(dates.iter().copied())
+            .chain(std::iter::once(after))
+            .collect();
+        let gaps: Vec<GapInterval> = edges
+            .windows(2)
+            .filter_map(|pair| {
+                let (prev, next) = (pair[0], pair[1]);
+                let days = (next - prev).num_days() - 1;
+                (days > 0).then(|| {
+                    let start = prev.succ_opt().unwrap_or(prev);
+                    let end = next.pred_opt().unwrap_or(next);
+                    GapInterval {
+                        start,
+                        end,
+                        days,
+                        days_reported_missing: missing.range(start..=end).count(),
+                    }
+                })
+            })
+            .collect();
+
+        let mut per_year: BTreeMap<i32, usize> = BTreeMap::new();
+        for date in dates {
+            *per_year.entry(date.year()).or_default() += 1;
+        }
+        let yearly = (first_date.year()..=last_date.year())
+            .map(|year| {
+                let days_present = per_year.get(&year).copied().unwrap_or(0);
+                let days_in_year = days_in_year(year);
+                YearCoverage {
+                    year,
+                    days_present,
+                    days_in_year,
+                    percent: days_present as f64 / days_in_year as f64 * 100.0,
+                }
+            })
+            .collect();
+
+        let days_expected = (last_date - first_date).num_days() as usize + 1;
+        SeriesCompleteness {
+            station_id,
+            station_name: station_name.to_string(),
+            metric: metric.to_string(),
+            first_date,
+            last_date,
+            days_present: dates.len(),
+            days_reported_missing: missing.len(),
+            days_expected,
+            completeness_percent: dates.len() as f64 / days_expected as f64 * 100.0,
+            longest_gap_days: gaps.iter().map(|g| g.days).max().unwrap_or(0),
+            gaps,
+            yearly,
+        }
+    }
+}
+
+impl Default for CompletenessChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn days_in_year(year: i32) -> usize {
+    if NaiveDate::from_ymd_opt(year, 2, 29).is_some() {
+        366
+    } else {
+        365
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        station_id: u32,
+        date: &str,
+        temp_avg: Option<f32>,
+        rr: Option<f32>,
+    ) -> WeatherRecord {
+        WeatherRecord::new(
+            station_id,
+            format!("Station {}", station_id),
+            date.parse().unwrap(),
+            52.4,
+            -1.8,
+            None,
+            None,
+            temp_avg,
+            rr,
+            None,
+            Some("0".to_string()),
+            Some("0".to_string()),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_gaps_and_yearly_coverage() {
+        let records = vec![
+            record(1, "2023-12-30", Some(5.0), Some(0.0)),
+            record(1, "2023-12-31", Some(5.0), None),
+            record(1, "2024-01-04", Some(4.0), None),
+            record(1, "2024-01-05", Some(4.0), Some(1.0)),
+        ];
+
+        let report = CompletenessChecker::new().check(&records);
+        assert_eq!(report.series.len(), 2);
+
+        let rr = &report.series[0];
+        assert_eq!(rr.metric, "precipitation");
+        assert_eq!(rr.gaps.len(), 1);
+        assert_eq!(rr.longest_gap_days, 5);
+
+        let tg = &report.series[1];
+        assert_eq!(tg.metric, "temp_avg");
+        assert_eq!(
+            tg.first_date,
+            NaiveDate::from_ymd_opt(2023, 12, 30).unwrap()
+        );
+        assert_eq!(tg.days_expected, 7);
+        assert_eq!(tg.days_present, 4);
+        assert_eq!(
+            tg.gaps,
+            vec![GapInterval {
+                start: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                end: NaiveDate::from_ymd_opt(2024, 1, 3).unwrap(),
+                days: 3,
+                days_reported_missing: 0,
+            }]
+        );
+        assert_eq!(tg.yearly[0].days_present, 2);
+        assert_eq!(tg.yearly[1].days_in_year, 366);
+    }
+
+    #[test]
+    fn test_stations_below_threshold() {
+        let records = vec![
+            record(1, "2023-01-01", Some(5.0), None),
+            record(1, "2023-01-02", Some(5.0), None),
+            record(2, "2023-01-01", Some(5.0), None),
+            record(2, "2023-01-04", Some(5.0), None),
+        ];
+
+        let report = CompletenessChecker::new().check(&records);
+        assert_eq!(report.station_completeness(2), Some(50.0));
+        assert_eq!(report.stations_below(80.0), BTreeSet::from([2]));
+        assert!(report.stations_below(50.0).is_empty());
+    }
+
+    #[test]
+    fn test_reported_missing_days_extend_series() {
+        use crate::models::weather::MissingReason;
+
+        let mut records = vec![
+            record(1, "2023-01-01", None, None),
+            record(1, "2023-01-02", Some(5.0), None),
+            record(1, "2023-01-04", Some(5.0), None),
+            record(1, "2023-01-05", None, None),
+        ];
+        records[0].set_missing_reason("temp_avg", MissingReason::Flag9);
+        records[3].set_missing_reason("temp_avg", MissingReason::Sentinel);
+
+        let report = CompletenessChecker::new().check(&records);
+        let tg = &report.series[0];
+        assert_eq!((tg.days_present, tg.days_reported_missing), (2, 2));
+        assert_eq!(tg.days_expected, 5);
+        assert_eq!(tg.completeness_percent, 40.0);
+        // Omitted 3 January is a gap, but not one the source reported
+        let reported: Vec<(i64, usize)> = tg
+            .gaps
+            .iter()
+            .map(|g| (g.days, g.days_reported_missing))
+            .collect();
+        assert_eq!(reported, vec![(1, 1), (1, 0), (1, 1)]);
+    }
+}
@@ -0,0 +1,280 @@
+use crate::archive::MultiArchiveProcessor;
+use crate::error::{ProcessingError, Result};
+use crate::models::WeatherRecord;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+/// Kind of difference found between an existing dataset and a newer release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// A station/date that was not in the existing dataset
+    NewRecord,
+    /// A value that differs from the one already stored
+    ValueRevised,
+    /// A quality flag that differs from the one already stored
+    QualityChanged,
+}
+
+/// A single changelog entry
+#[derive(Debug, Clone, Serialize)]
+pub struct Revision {
+    pub station_id: u32,
+    pub date: NaiveDate,
+    pub kind: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    pub previous: Option<String>,
+    pub current: Option<String>,
+}
+
+/// Summary and itemised list of revisions applied by an update
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UpdateChangelog {
+    pub existing_records: usize,
+    pub incoming_records: usize,
+    pub new_records: usize,
+    pub revised_records: usize,
+    pub unchanged_records: usize,
+    pub value_revisions: usize,
+    pub quality_changes: usize,
+    pub revisions: Vec<Revision>,
+}
+
+impl UpdateChangelog {
+    pub fn summary(&self) -> String {
+        format!(
+            "Update Summary:\n  Existing records: {}\n  Records in release: {}\n  New records: {}\n  Revised records: {}\n  Unchanged records: {}\n  Value revisions: {}\n  Quality flag changes: {}",
+            self.existing_records,
+            self.incoming_records,
+            self.new_records,
+            self.revised_records,
+            self.unchanged_records,
+            self.value_revisions,
+            self.quality_changes
+        )
+    }
+
+    /// Write the changelog as pretty-printed JSON
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, self).map_err(|e| ProcessingError::Io(e.into()))
+    }
+}
+
+/// Applies a newer ECA&D release on top of an existing weather dataset
+pub struct DatasetUpdater;
+
+impl DatasetUpdater {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Merge `incoming` records into `existing`, recording every difference.
+    ///
+    /// Values present in the release replace stored ones; values the release
+    /// does not carry (e.g. metrics from other archives) are kept.
+    pub fn apply(
+        &self,
+        existing: Vec<WeatherRecord>,
+        incoming: Vec<WeatherRecord>,
+    ) -> Result<(Vec<WeatherRecord>, UpdateChangelog)> {
+        let mut changelog = UpdateChangelog {
+            existing_records: existing.len(),
+            incoming_records: incoming.len(),
+            ..Default::default()
+        };
+
+        let mut record_map: HashMap<(u32, NaiveDate), WeatherRecord> = existing
+            .into_iter()
+            .map(|r| ((r.station_id, r.date), r))
+            .collect();
+
+        for record in incoming {
+            let key = (record.station_id, record.date);
+
+            match record_map.get_mut(&key) {
+                Some(current) => {
+                    let revisions = Self::diff_records(current, &record);
+                    if revisions.is_empty() {
+                        changelog.unchanged_records += 1;
+                        continue;
+                    }
+
+                    changelog.revised_records += 1;
+                    for revision in revisions {
+                        match revision.kind {
+                            ChangeKind::ValueRevised => changelog.value_revisions += 1,
+                            ChangeKind::QualityChanged => changelog.quality_changes += 1,
+                            ChangeKind::NewRecord => {}
+                        }
+                        changelog.revisions.push(revision);
+                    }
+
+                    MultiArchiveProcessor::merge_weather_records(current, record)?;
+                }
+                None => {
+                    changelog.new_records += 1;
+                    changelog.revisions.push(Revision {
+                        station_id: record.station_id,
+                        date: record.date,
+                        kind: ChangeKind::NewRecord,
+                        column: None,
+                        previous: None,
+                        current: None,
+                    });
+                    record_map.insert(key, record);
+                }
+            }
+        }
+
+        let mut records: Vec<WeatherRecord> = record_map.into_values().collect();
+        records.sort_by(|a, b| {
+            a.station_id
+                .cmp(&b.station_id)
+                .then_with(|| a.date.cmp(&b.date))
+        });
+        changelog.revisions.sort_by(|a, b| {
+            a.station_id
+                .cmp(&b.station_id)
+                .then_with(|| a.date.cmp(&b.date))
+        });
+
+        Ok((records, changelog))
+    }
+
+    /// Compare the values and quality flags a release record carries
+    fn diff_records(current: &WeatherRecord, incoming: &WeatherRecord) -> Vec<Revision> {
+        let mut revisions = Vec::new();
+        let mut push = |kind, column: &str, previous: Option<String>, current: Option<String>| {
+            revisions.push(Revision {
+                station_id: incoming.station_id,
+                date: incoming.date,
+                kind,
+                column: Some(column.to_string()),
+                previous,
+                current,
+            });
+        };
+
+        for ((column, previous), (_, value)) in current
+            .value_columns()
+            .into_iter()
+            .zip(incoming.value_columns())
+        {
+            if value.is_some() && value != previous {
+                push(
+                    ChangeKind::ValueRevised,
+                    column,
+                    previous.map(|v| v.to_string()),
+                    value.map(|v| v.to_string()),
+                );
+            }
+        }
+
+        for ((column, previous), (_, quality)) in quality_columns(current)
+            .into_iter()
+            .zip(quality_columns(incoming))
+        {
+            // A flag arriving with a newly added value is not a re-flag
+            if previous.is_some() && quality.is_some() && quality != previous {
+                push(
+                    ChangeKind::QualityChanged,
+                    &column,
+                    previous.map(str::to_string),
+                    quality.map(str::to_string),
+                );
+            }
+        }
+
+        revisions
+    }
+}
+
+impl Default for DatasetUpdater {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Every quality flag column of a record, keyed by Parquet column name
+fn quality_columns(record: &WeatherRecord) -> Vec<(String, Option<&str>)> {
+    let mut columns = vec![
+        ("temp_quality".to_string(), record.temp_quality.as_deref()),
+        (
+            "precip_quality".to_string(),
+            record.precip_quality.as_deref(),
+        ),
+        ("wind_quality".to_string(), record.wind_quality.as_deref()),
+    ];
+    for (metric, _, quality, _) in record.supplementary_elements() {
+        columns.push((format!("{}_quality", metric.column_name()), quality));
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(day: u32, temp_avg: Option<f32>, quality: &str) -> WeatherRecord {
+        WeatherRecord::new(
+            257,
+            "Test Station".to_string(),
+            NaiveDate::from_ymd_opt(2023, 1, day).unwrap(),
+            52.4,
+            -1.8,
+            None,
+            None,
+            temp_avg,
+            None,
+            None,
+            Some(quality.to_string()),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_apply_update() {
+        let mut existing_day_one = record(1, Some(5.5), "0");
+        existing_day_one.precipitation = Some(2.5);
+        let existing = vec![
+            existing_day_one,
+            record(2, Some(6.1), "0"),
+            record(3, Some(7.0), "0"),
+        ];
+
+        let incoming = vec![
+            record(1, Some(5.7), "0"), // revised value
+            record(2, Some(6.1), "1"), // changed quality flag
+            record(3, Some(7.0), "0"), // unchanged
+            record(4, Some(4.2), "0"), // new day
+        ];
+
+        let updater = DatasetUpdater::new();
+        let (records, changelog) = updater.apply(existing, incoming).unwrap();
+
+        assert_eq!(records.len(), 4);
+        assert_eq!(changelog.new_records, 1);
+        assert_eq!(changelog.revised_records, 2);
+        assert_eq!(changelog.unchanged_records, 1);
+        assert_eq!(changelog.value_revisions, 1);
+        assert_eq!(changelog.quality_changes, 1);
+
+        // Revised values are replaced, values the release lacks are kept
+        assert_eq!(records[0].temp_avg, Some(5.7));
+        assert_eq!(records[0].precipitation, Some(2.5));
+        assert_eq!(records[1].temp_quality.as_deref(), Some("1"));
+
+        let revision = &changelog.revisions[0];
+        assert_eq!(revision.kind, ChangeKind::ValueRevised);
+        assert_eq!(revision.column.as_deref(), Some("temp_avg"));
+        assert_eq!(revision.previous.as_deref(), Some("5.5"));
+        assert_eq!(revision.current.as_deref(), Some("5.7"));
+    }
+}
@@ -0,0 +1,537 @@
+use crate::error::{ProcessingError, Result};
+use crate::models::weather::{InfillMethod, PhysicalValidity};
+use crate::models::WeatherRecord;
+use crate::processors::spatial_qc::{stations_from_records, SpatialQualityControl};
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::{BTreeMap, HashMap};
+
+/// Elements that can be infilled; the first three are temperatures
+const ELEMENTS: [&str; 4] = ["temp_min", "temp_max", "temp_avg", "precipitation"];
+
+/// Days on which both stations observed an element before a neighbour
+/// regression is fitted
+const MIN_OVERLAP_DAYS: usize = 60;
+
+/// Observed values a calendar month needs for a climatology estimate
+const MIN_CLIMATOLOGY_VALUES: u32 = 20;
+
+/// Counts from an infill run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InfillSummary {
+    /// Records created for days on which the station reported nothing
+    pub records_added: usize,
+    pub interpolated: usize,
+    pub neighbour: usize,
+    pub climatology: usize,
+    /// Gaps left open because they are longer than the maximum gap
+    pub gaps_too_long: usize,
+    /// Days inside short gaps that no method could estimate
+    pub days_unfilled: usize,
+}
+
+impl InfillSummary {
+    pub fn values_filled(&self) -> usize {
+        self.interpolated + self.neighbour + self.climatology
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Infilled {} values ({} interpolated, {} from neighbours, {} from climatology), {} records added, {} gaps too long, {} days unfilled",
+            self.values_filled(),
+            self.interpolated,
+            self.neighbour,
+            self.climatology,
+            self.records_added,
+            self.gaps_too_long,
+            self.days_unfilled
+        )
+    }
+}
+
+/// Linear fit of a station's element on one neighbour's
+#[derive(Debug, Clone, Copy)]
+struct NeighbourFit {
+    station_id: u32,
+    intercept: f64,
+    slope: f64,
+    correlation: f64,
+}
+
+/// Fills short gaps in each station's temperature and precipitation series.
+///
+/// For every missing day the first applicable method is used, in the
+/// configured order: linear interpolation (temperatures only, for gaps up
+/// to a few days), regression on the best-correlated neighbour that
+/// reported that day, or the station's own mean for the calendar month.
+/// Filled values are marked in the `*_infilled` columns.
+pub struct Infiller {
+    methods: Vec<InfillMethod>,
+    max_interpolation_days: usize,
+    max_gap_days: usize,
+    neighbours: usize,
+    max_distance_km: f64,
+    min_correlation: f64,
+}
+
+impl Default for Infiller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Infiller {
+    pub fn new() -> Self {
+        Self {
+            methods: vec![
+                InfillMethod::Interpolation,
+                InfillMethod::Neighbour,
+                InfillMethod::Climatology,
+            ],
+            max_interpolation_days: 3,
+            max_gap_days: 10,
+            neighbours: 5,
+            max_distance_km: 150.0,
+            min_correlation: 0.7,
+        }
+    }
+
+    /// Methods to try, in order of preference
+    pub fn with_methods(mut self, methods: Vec<InfillMethod>) -> Result<Self> {
+        if methods.is_empty() {
+            return Err(ProcessingError::Config(
+                "At least one infill method is required".to_string(),
+            ));
+        }
+        self.methods = methods;
+        Ok(self)
+    }
+
+    /// Longest temperature gap bridged by linear interpolation
+    pub fn with_max_interpolation_days(mut self, days: usize) -> Self {
+        self.max_interpolation_days = days;
+        self
+    }
+
+    /// Longest gap filled by any method; longer gaps are left open
+    pub fn with_max_gap_days(mut self, days: usize) -> Self {
+        self.max_gap_days = days;
+        self
+    }
+
+    pub fn with_neighbours(mut self, neighbours: usize) -> Self {
+        self.neighbours = neighbours;
+        self
+    }
+
+    pub fn with_max_distance_km(mut self, max_distance_km: f64) -> Self {
+        self.max_distance_km = max_distance_km;
+        self
+    }
+
+    /// Weakest daily correlation a neighbour regression may have
+    pub fn with_min_correlation(mut self, min_correlation: f64) -> Result<Self> {
+        if !(0.0..=1.0).contains(&min_correlation) {
+            return Err(ProcessingError::Config(format!(
+                "Minimum correlation must be between 0 and 1, got {}",
+                min_correlation
+            )));
+        }
+        self.min_correlation = min_correlation;
+        Ok(self)
+    }
+
+    /// Fill gaps between each station's first and last observation of an
+    /// element, adding records for days the station did not report.
+    ///
+    /// Returns the records sorted by station and date.
+    pub fn infill(&self, records: Vec<WeatherRecord>) -> (Vec<WeatherRecord>, InfillSummary) {
+        let mut summary = InfillSummary::default();
+
+        let neighbours = if self.methods.contains(&InfillMethod::Neighbour) {
+            SpatialQualityControl::new()
+                .with_neighbours(self.neighbours)
+                .with_max_distance_km(self.max_distance_km)
+                .find_neighbours(&stations_from_records(&records))
+        } else {
+            HashMap::new()
+        };
+
+        let observed: HashMap<(u32, NaiveDate), [Option<f32>; 4]> = records
+            .iter()
+            .map(|r| {
+                let values = std::array::from_fn(|e| observed_value(r, e));
+                ((r.station_id, r.date), values)
+            })
+            .collect();
+        let mut stations: BTreeMap<u32, (WeatherRecord, Vec<NaiveDate>)> = BTreeMap::new();
+        for record in &records {
+            stations
+                .entry(record.station_id)
+                .or_insert_with(|| (record.clone(), Vec::new()))
+                .1
+                .push(record.date);
+        }
+
+        let mut weather_data: HashMap<(u32, NaiveDate), WeatherRecord> = records
+            .into_iter()
+            .map(|r| ((r.station_id, r.date), r))
+            .collect();
+
+        for (station_id, (template, mut dates)) in stations {
+            dates.sort();
+            let station_neighbours = neighbours.get(&station_id).map_or(&[][..], |n| &n[..]);
+
+            for element in 0..ELEMENTS.len() {
+                let series: Vec<(NaiveDate, f32)> = dates
+                    .iter()
+                    .filter_map(|date| {
+                        let value = observed[&(station_id, *date)][element]?;
+                        Some((*date, value))
+                    })
+                    .collect();
+                if series.len() < 2 {
+                    continue;
+                }
+
+                let climatology = monthly_means(&series);
+                let mut fits: Vec<NeighbourFit> = station_neighbours
+                    .iter()
+                    .filter_map(|n| self.fit_neighbour(&series, &observed, n.station_id, element))
+                    .collect();
+                fits.sort_by(|a, b| b.correlation.total_cmp(&a.correlation));
+
+                for pair in series.windows(2) {
+                    let ((start, before), (end, after)) = (pair[0], pair[1]);
+                    let gap = (end - start).num_days() as usize - 1;
+                    if gap == 0 {
+                        continue;
+                    }
+                    if gap > self.max_gap_days {
+                        summary.gaps_too_long += 1;
+                        continue;
+                    }
+
+                    for offset in 1..=gap {
+                        let date = start + Duration::days(offset as i64);
+                        let estimate = self.methods.iter().find_map(|method| {
+                            let value = match method {
+                                InfillMethod::Interpolation => {
+                                    (element < 3 && gap <= self.max_interpolation_days).then(|| {
+                                        let fraction = offset as f32 / (gap + 1) as f32;
+                                        before + (after - before) * fraction
+                                    })
+                                }
+                                InfillMethod::Neighbour => fits.iter().find_map(|fit| {
+                                    let x = observed.get(&(fit.station_id, date))?[element]?;
+                                    let y = fit.intercept + fit.slope * x as f64;
+                                    // A regression can dip below zero on dry days
+                                    Some(if element == 3 { y.max(0.0) } else { y } as f32)
+                                }),
+                                InfillMethod::Climatology => {
+                                    climatology[date.month0() as usize].map(|v| v as f32)
+                                }
+                            };
+                            value.map(|v| (v, *method))
+                        });
+
+                        let Some((value, method)) = estimate else {
+                            summary.days_unfilled += 1;
+                            continue;
+                        };
+                        let record = weather_data.entry((station_id, date)).or_insert_with(|| {
+                            summary.records_added += 1;
+                            blank_record(&template, date)
+                        });
+                        set_infilled(record, element, value, method);
+                        match method {
+                            InfillMethod::Interpolation => summary.interpolated += 1,
+                            InfillMethod::Neighbour => summary.neighbour += 1,
+                            InfillMethod::Climatology => summary.climatology += 1,
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut records: Vec<WeatherRecord> = weather_data.into_values().collect();
+        records.sort_by_key(|r| (r.station_id, r.date));
+        (records, summary)
+    }
+
+    /// Regress the station's series on a neighbour's over the days both
+    /// observed. Precipitation is fitted through the origin so dry days
+    /// stay dry.
+    fn fit_neighbour(
+        &self,
+        series: &[(NaiveDate, f32)],
+        observed: &HashMap<(u32, NaiveDate), [Option<f32>; 4]>,
+        neighbour_id: u32,
+        element: usize,
+    ) -> Option<NeighbourFit> {
+        let pairs: Vec<(f64, f64)> = series
+            .iter()
+            .filter_map(|(date, y)| {
+                let x = observed.get(&(neighbour_id, *date))?[element]?;
+                Some((x as f64, *y as f64))
+            })
+            .collect();
+        if pairs.len() < MIN_OVERLAP_DAYS {
+            return None;
+        }
+
+        let n = pairs.len() as f64;
+        let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let sxx: f64 = pairs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+        let syy: f64 = pairs.iter().map(|(_, y)| (y - mean_y).powi(2)).sum();
+        let sxy: f64 = pairs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+        if sxx == 0.0 || syy == 0.0 {
+            return None;
+        }
+        let correlation = sxy / (sxx * syy).sqrt();
+        if correlation < self.min_correlation {
+            return None;
+        }
+
+        let (intercept, slope) = if element == 3 {
+            (0.0, mean_y / mean_x)
+        } else {
+            let slope = sxy / sxx;
+            (mean_y - slope * mean_x, slope)
+        };
+        Some(NeighbourFit {
+            station_id: neighbour_id,
+            intercept,
+            slope,
+            correlation,
+        })
+    }
+}
+
+/// An element's value if it was observed and is physically possible
+fn observed_value(record: &WeatherRecord, element: usize) -> Option<f32> {
+    let (value, infilled, validity) = match element {
+        0 => (
+            record.temp_min,
+            record.temp_min_infilled,
+            record.temp_validation,
+        ),
+        1 => (
+            record.temp_max,
+            record.temp_max_infilled,
+            record.temp_validation,
+        ),
+        2 => (
+            record.temp_avg,
+            record.temp_avg_infilled,
+            record.temp_validation,
+        ),
+        _ => (
+            record.precipitation,
+            record.precip_infilled,
+            record.precip_validation,
+        ),
+    };
+    value.filter(|_| infilled.is_none() && validity != Some(PhysicalValidity::Invalid))
+}
+
+fn set_infilled(record: &mut WeatherRecord, element: usize, value: f32, method: InfillMethod) {
+    match element {
+        0 => (record.temp_min, record.temp_min_infilled) = (Some(value), Some(method)),
+        1 => (record.temp_max, record.temp_max_infilled) = (Some(value), Some(method)),
+        2 => (record.temp_avg, record.temp_avg_infilled) = (Some(value), Some(method)),
+        _ => (record.precipitation, record.precip_infilled) = (Some(value), Some(method)),
+    }
+}
+
+/// A record with the station details of `template` and no values
+fn blank_record(template: &WeatherRecord, date: NaiveDate) -> WeatherRecord {
+    let mut record = WeatherRecord::new(
+        template.station_id,
+        template.station_name.clone(),
+        date,
+        template.latitude,
+        template.longitude,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    record.country = template.country.clone();
+    record.elevation = template.elevation;
+    record
+}
+
+/// Mean of the observed values in each calendar month
+fn monthly_means(series: &[(NaiveDate, f32)]) -> [Option<f64>; 12] {
+    let mut totals = [(0.0f64, 0u32); 12];
+    for (date, value) in series {
+        let total = &mut totals[date.month0() as usize];
+        total.0 += *value as f64;
+        total.1 += 1;
+    }
+    totals.map(|(sum, count)| (count >= MIN_CLIMATOLOGY_VALUES).then(|| sum / count as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(station_id: u32, lon: f64, date: NaiveDate, tg: f32, rr: f32) -> WeatherRecord {
+        WeatherRecord::new(
+            station_id,
+            format!("Station {}", station_id),
+            date,
+            52.0,
+            lon,
+            None,
+            None,
+            Some(tg),
+            Some(rr),
+            None,
+            Some("0".to_string()),
+            Some("0".to_string()),
+            None,
+        )
+    }
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2020, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_interpolation_and_long_gaps() {
+        // 1-31 January with 3-4 and 10-24 January missing
+        let records: Vec<WeatherRecord> = (1..=31)
+            .filter(|d| !(3..=4).contains(d) && !(10..=24).contains(d))
+            .map(|d| record(1, 0.0, date(1, d), d as f32, 0.0))
+            .collect();
+        let observed = records.len();
+
+        let (records, summary) = Infiller::new().infill(records);
+        assert_eq!(summary.interpolated, 2);
+        assert_eq!(summary.records_added, 2);
+        assert_eq!(summary.gaps_too_long, 2); // temperature and precipitation
+        assert_eq!(records.len(), observed + 2);
+
+        let third = records.iter().find(|r| r.date == date(1, 3)).unwrap();
+        assert!((third.temp_avg.unwrap() - 3.0).abs() < 1e-5);
+        assert_eq!(third.temp_avg_infilled, Some(InfillMethod::Interpolation));
+        // Precipitation is never interpolated, and January lacks a
+        // climatology with so few days
+        assert_eq!(third.precipitation, None);
+        assert_eq!(third.precip_infilled, None);
+        assert!(records
+            .iter()
+            .filter(|r| r.date != date(1, 3) && r.date != date(1, 4))
+            .all(|r| r.temp_avg_infilled.is_none()));
+    }
+
+    #[test]
+    fn test_neighbour_regression_and_climatology() {
+        // Station 2 runs 1.5°C warmer than station 1 and has twice its rain
+        let mut records = Vec::new();
+        for (i, day) in date(3, 1).iter_days().take(90).enumerate() {
+            let tg = 5.0 + (i as f32 * 0.37).sin() * 4.0 + i as f32 * 0.05;
+            let rr = ((i * 7) % 5) as f32;
+            records.push(record(1, 0.0, day, tg, rr));
+            if !(40..46).contains(&i) {
+                records.push(record(2, 0.2, day, tg + 1.5, rr * 2.0));
+            }
+        }
+        // A third, distant station with no neighbours
+        for (i, day) in date(3, 1).iter_days().take(90).enumerate() {
+            if i != 50 {
+                records.push(record(3, 10.0, day, 10.0 + (i % 3) as f32, 1.0));
+            }
+        }
+
+        let infiller = Infiller::new().with_max_interpolation_days(0);
+        let (records, summary) = infiller.infill(records);
+        assert_eq!(summary.neighbour, 12);
+        assert_eq!(summary.climatology, 2);
+
+        let gap_day = date(3, 1) + Duration::days(42);
+        let source = records
+            .iter()
+            .find(|r| r.station_id == 1 && r.date == gap_day)
+            .unwrap();
+        let filled = records
+            .iter()
+            .find(|r| r.station_id == 2 && r.date == gap_day)
+            .unwrap();
+        assert_eq!(filled.temp_avg_infilled, Some(InfillMethod::Neighbour));
+        assert!((filled.temp_avg.unwrap() - (source.temp_avg.unwrap() + 1.5)).abs() < 1e-3);
+        assert!((filled.precipitation.unwrap() - source.precipitation.unwrap() * 2.0).abs() < 1e-3);
+
+        let distant = records
+            .iter()
+            .find(|r| r.station_id == 3 && r.date == date(3, 1) + Duration::days(50))
+            .unwrap();
+        assert_eq!(distant.temp_avg_infilled, Some(InfillMethod::Climatology));
+        assert_eq!(distant.precip_infilled, Some(InfillMethod::Climatology));
+
+        assert!(Infiller::new().with_methods(Vec::new()).is_err());
+        assert!(Infiller::new().with_min_correlation(1.5).is_err());
+    }
+
+    #[test]
+    fn test_all_neighbours_missing() {
+        // Two well-correlated stations that both miss the same six days
+        let mut records = Vec::new();
+        for (i, day) in date(3, 1).iter_days().take(90).enumerate() {
+            if (40..46).contains(&i) {
+                continue;
+            }
+            let tg = 5.0 + (i as f32 * 0.37).sin() * 4.0;
+            records.push(record(1, 0.0, day, tg, 1.0 + (i % 4) as f32));
+            records.push(record(2, 0.2, day, tg + 1.5, 2.0 + (i % 4) as f32));
+        }
+        let observed = records.len();
+
+        let infiller = Infiller::new()
+            .with_methods(vec![InfillMethod::Neighbour])
+            .unwrap();
+        let (records, summary) = infiller.infill(records);
+        assert_eq!(summary.values_filled(), 0);
+        assert_eq!(summary.records_added, 0);
+        // Six days of temperature and precipitation at both stations
+        assert_eq!(summary.days_unfilled, 24);
+        assert_eq!(records.len(), observed);
+    }
+
+    #[test]
+    fn test_gap_length_edges() {
+        let january = |missing: std::ops::RangeInclusive<u32>| -> Vec<WeatherRecord> {
+            (1..=31)
+                .filter(|d| !missing.contains(d))
+                .map(|d| record(1, 0.0, date(1, d), d as f32, 0.0))
+                .collect()
+        };
+        let interpolation = || {
+            Infiller::new()
+                .with_methods(vec![InfillMethod::Interpolation])
+                .unwrap()
+        };
+
+        // A gap as long as the interpolation limit is bridged
+        let (_, summary) = interpolation().infill(january(5..=7));
+        assert_eq!(summary.interpolated, 3);
+        let (_, summary) = interpolation()
+            .with_max_interpolation_days(2)
+            .infill(january(5..=7));
+        assert_eq!(summary.interpolated, 0);
+        assert_eq!(summary.days_unfilled, 6);
+
+        // A gap as long as the maximum is attempted, a longer one is not
+        let (_, summary) = interpolation().with_max_gap_days(3).infill(january(5..=7));
+        assert_eq!(summary.gaps_too_long, 0);
+        let (_, summary) = interpolation().with_max_gap_days(3).infill(january(5..=8));
+        assert_eq!(summary.gaps_too_long, 2);
+        assert_eq!(summary.values_filled(), 0);
+    }
+}
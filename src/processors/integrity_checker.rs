@@ -1,9 +1,12 @@
 use crate::error::Result;
 use crate::models::ConsolidatedRecord;
+use crate::processors::{RunWarning, TemporalQcCounts};
 use crate::utils::constants::{MAX_VALID_TEMP, MIN_VALID_TEMP};
+use serde::Serialize;
 use std::collections::HashMap;
+use tracing::debug;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct IntegrityReport {
     pub total_records: usize,
     pub valid_records: usize,
@@ -12,9 +15,30 @@ pub struct IntegrityReport {
     pub missing_data_records: usize,
     pub temperature_violations: Vec<TemperatureViolation>,
     pub station_statistics: HashMap<u32, StationStatistics>,
+    /// Flag counts from the temporal QC checks, when they were run
+    pub temporal_qc: TemporalQcCounts,
+    /// Warnings raised while reading the data; reported separately
+    #[serde(skip)]
+    pub warnings: Vec<RunWarning>,
 }
 
-#[derive(Debug, Clone)]
+impl IntegrityReport {
+    /// Fold another report (e.g. for a further station or archive) into this one
+    pub fn merge(&mut self, other: IntegrityReport) {
+        self.total_records += other.total_records;
+        self.valid_records += other.valid_records;
+        self.suspect_records += other.suspect_records;
+        self.invalid_records += other.invalid_records;
+        self.missing_data_records += other.missing_data_records;
+        self.temperature_violations
+            .extend(other.temperature_violations);
+        self.station_statistics.extend(other.station_statistics);
+        self.temporal_qc.merge(&other.temporal_qc);
+        self.warnings.extend(other.warnings);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct TemperatureViolation {
     pub station_id: u32,
     pub date: chrono::NaiveDate,
@@ -22,7 +46,7 @@ pub struct TemperatureViolation {
     pub details: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum ViolationType {
     MinGreaterThanAvg,
     AvgGreaterThanMax,
@@ -30,7 +54,7 @@ pub enum ViolationType {
     SuspiciousJump,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct StationStatistics {
     pub total_records: usize,
     pub valid_records: usize,
@@ -68,6 +92,8 @@ impl IntegrityChecker {
             missing_data_records: 0,
             temperature_violations: Vec::new(),
             station_statistics: HashMap::new(),
+            temporal_qc: TemporalQcCounts::default(),
+            warnings: Vec::new(),
         };
 
         // Group records by station for time series checks
@@ -126,6 +152,12 @@ impl IntegrityChecker {
             self.check_time_series_integrity(station_id, &records, &mut report)?;
         }
 
+        debug!(
+            records = report.total_records,
+            invalid = report.invalid_records,
+            violations = report.temperature_violations.len(),
+            "Integrity check complete"
+        );
         Ok(report)
     }
 
@@ -274,6 +306,27 @@ impl IntegrityChecker {
             report.temperature_violations.len()
         ));
 
+        let temporal = &report.temporal_qc;
+        if temporal.records_checked > 0 {
+            summary.push_str(&format!(
+                "\nTemporal QC ({} records checked):\n",
+                temporal.records_checked
+            ));
+            summary.push_str(&format!("  Day-to-day spikes: {}\n", temporal.spikes));
+            summary.push_str(&format!(
+                "  Stuck sensor values: {}\n",
+                temporal.stuck_values
+            ));
+            summary.push_str(&format!(
+                "  Implausible precipitation runs: {}\n",
+                temporal.precipitation_runs
+            ));
+            summary.push_str(&format!(
+                "  Tmax below Tmin: {}\n",
+                temporal.tmax_below_tmin
+            ));
+        }
+
         if !report.temperature_violations.is_empty() {
             summary.push_str("\nTop 10 Violations:\n");
             for (i, violation) in report.temperature_violations.iter().take(10).enumerate() {
@@ -1,9 +1,26 @@
+pub mod completeness;
 pub mod data_merger;
+pub mod dataset_updater;
+pub mod infill;
 pub mod integrity_checker;
 pub mod parallel_processor;
+pub mod run_report;
+pub mod spatial_qc;
+pub mod temporal_qc;
 
+pub use completeness::{
+    CompletenessChecker, CompletenessReport, GapInterval, SeriesCompleteness, YearCoverage,
+};
 pub use data_merger::DataMerger;
+pub use dataset_updater::{ChangeKind, DatasetUpdater, Revision, UpdateChangelog};
+pub use infill::{InfillSummary, Infiller};
 pub use integrity_checker::{
     IntegrityChecker, IntegrityReport, StationStatistics, TemperatureViolation, ViolationType,
 };
 pub use parallel_processor::ParallelProcessor;
+pub use run_report::{
+    ArchiveReport, ExitCondition, PhaseTiming, RecordCounts, RunReport, RunWarning, WarningCode,
+    EXIT_CONDITION_CODE,
+};
+pub use spatial_qc::{stations_from_records, Neighbour, SpatialQcSummary, SpatialQualityControl};
+pub use temporal_qc::{TemporalQcCounts, TemporalQualityControl};
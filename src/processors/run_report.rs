@@ -0,0 +1,432 @@
+use crate::archive::ArchiveMetadata;
+use crate::error::{ProcessingError, Result};
+use crate::models::WeatherRecord;
+use crate::processors::IntegrityReport;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Exit status of a run that completed but met a `--fail-on` condition
+pub const EXIT_CONDITION_CODE: i32 = 2;
+
+/// Stable identifier of a kind of warning, for matching in orchestration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningCode {
+    /// A data file belongs to a station missing from stations.txt
+    StationNotInMetadata,
+    /// A data file's element is not listed in elements.txt
+    MetricNotInElements,
+    /// stations.txt lists stations from more than one country
+    MultipleCountries,
+    /// A zip file in the input directory could not be read as an archive
+    ArchiveInspectionFailed,
+    /// Stations were dropped for falling below `--min-completeness`
+    StationsBelowCompleteness,
+    /// The run produced no records to write
+    NoRecords,
+}
+
+/// A warning raised while processing, with enough context to act on it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunWarning {
+    pub code: WarningCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub station_id: Option<u32>,
+    /// Archive or data file the warning concerns
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+impl RunWarning {
+    pub fn new(code: WarningCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            station_id: None,
+            path: None,
+        }
+    }
+
+    pub fn with_station(mut self, station_id: u32) -> Self {
+        self.station_id = Some(station_id);
+        self
+    }
+
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+}
+
+/// Outcome that makes an otherwise successful run exit non-zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExitCondition {
+    /// Any warning was raised
+    Warnings,
+    /// Any record counted has a value the QC rules mark physically invalid
+    InvalidRecords,
+    /// The integrity check found temperature inconsistencies
+    TemperatureViolations,
+    /// No records were produced
+    NoRecords,
+}
+
+impl ExitCondition {
+    /// Parse a comma-separated list such as `warnings,no-records`
+    pub fn parse_list(s: &str) -> Result<Vec<Self>> {
+        s.split(',')
+            .filter(|c| !c.trim().is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    pub fn is_met(&self, report: &RunReport) -> bool {
+        match self {
+            ExitCondition::Warnings => !report.warnings.is_empty(),
+            ExitCondition::InvalidRecords => report.counts.invalid_records > 0,
+            ExitCondition::TemperatureViolations => report
+                .integrity
+                .as_ref()
+                .is_some_and(|i| !i.temperature_violations.is_empty()),
+            ExitCondition::NoRecords => report.counts.total_records == 0,
+        }
+    }
+}
+
+impl fmt::Display for ExitCondition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ExitCondition::Warnings => "warnings",
+            ExitCondition::InvalidRecords => "invalid-records",
+            ExitCondition::TemperatureViolations => "temperature-violations",
+            ExitCondition::NoRecords => "no-records",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl std::str::FromStr for ExitCondition {
+    type Err = ProcessingError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "warnings" => Ok(ExitCondition::Warnings),
+            "invalid-records" => Ok(ExitCondition::InvalidRecords),
+            "temperature-violations" => Ok(ExitCondition::TemperatureViolations),
+            "no-records" => Ok(ExitCondition::NoRecords),
+            other => Err(ProcessingError::Config(format!(
+                "Unsupported exit condition: {} (expected warnings, invalid-records, temperature-violations or no-records)",
+                other
+            ))),
+        }
+    }
+}
+
+/// An input file with its size and SHA-256 digest
+#[derive(Debug, Clone, Serialize)]
+pub struct InputFile {
+    pub path: PathBuf,
+    pub bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+/// A file or dataset directory written by the run
+#[derive(Debug, Clone, Serialize)]
+pub struct OutputFile {
+    pub path: PathBuf,
+    /// Size of a file output; None for dataset directories
+    pub bytes: Option<u64>,
+}
+
+/// An archive's metadata, with elements named by their output column
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveReport {
+    pub path: PathBuf,
+    pub country: String,
+    pub station_count: usize,
+    pub date_range: Option<(NaiveDate, NaiveDate)>,
+    pub total_files: usize,
+    /// Data files per element column
+    pub file_counts: BTreeMap<&'static str, usize>,
+}
+
+impl ArchiveReport {
+    pub fn new(path: &Path, metadata: &ArchiveMetadata) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            country: metadata.country.clone(),
+            station_count: metadata.station_count,
+            date_range: metadata.date_range,
+            total_files: metadata.total_files,
+            file_counts: metadata
+                .metrics
+                .iter()
+                .map(|m| {
+                    (
+                        m.column_name(),
+                        metadata.file_counts.get(m).copied().unwrap_or(0),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Records of one station, and how many have a value per column
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StationCounts {
+    pub station_name: String,
+    pub records: usize,
+    pub metrics: BTreeMap<&'static str, usize>,
+}
+
+/// Record counts per metric column and per station
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RecordCounts {
+    pub total_records: usize,
+    /// Records with any value marked physically invalid by the QC rules
+    pub invalid_records: usize,
+    pub metrics: BTreeMap<&'static str, usize>,
+    pub stations: BTreeMap<u32, StationCounts>,
+}
+
+impl RecordCounts {
+    /// Count a further batch of records
+    pub fn add_records(&mut self, records: &[WeatherRecord]) {
+        self.total_records += records.len();
+        for record in records {
+            let station = self.stations.entry(record.station_id).or_default();
+            if station.records == 0 {
+                station.station_name = record.station_name.clone();
+            }
+            station.records += 1;
+            if record.has_invalid_data() {
+                self.invalid_records += 1;
+            }
+            for (column, value) in record.value_columns() {
+                if value.is_some() {
+                    *self.metrics.entry(column).or_default() += 1;
+                    *station.metrics.entry(column).or_default() += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Time spent in one phase of the run
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub seconds: f64,
+}
+
+/// Machine-readable account of a processing run: what was read, what was
+/// produced, what went wrong and how the process exited
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub command: String,
+    pub version: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub exit_code: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub failed_conditions: Vec<ExitCondition>,
+    pub inputs: Vec<InputFile>,
+    pub archives: Vec<ArchiveReport>,
+    pub counts: RecordCounts,
+    pub warnings: Vec<RunWarning>,
+    pub integrity: Option<IntegrityReport>,
+    pub timings: Vec<PhaseTiming>,
+    pub outputs: Vec<OutputFile>,
+    #[serde(skip)]
+    started: Instant,
+    #[serde(skip)]
+    checksums: bool,
+}
+
+impl RunReport {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at: Utc::now(),
+            finished_at: None,
+            exit_code: 0,
+            error: None,
+            failed_conditions: Vec::new(),
+            inputs: Vec::new(),
+            archives: Vec::new(),
+            counts: RecordCounts::default(),
+            warnings: Vec::new(),
+            integrity: None,
+            timings: Vec::new(),
+            outputs: Vec::new(),
+            started: Instant::now(),
+            checksums: true,
+        }
+    }
+
+    /// Whether to hash inputs; hashing a large archive set takes a while,
+    /// so it can be skipped when no report will be written
+    pub fn with_checksums(mut self, checksums: bool) -> Self {
+        self.checksums = checksums;
+        self
+    }
+
+    /// Record an input file, hashing it if checksums are enabled
+    pub fn add_input(&mut self, path: &Path) -> Result<()> {
+        let bytes = std::fs::metadata(path)?.len();
+        let sha256 = if self.checksums {
+            Some(sha256_file(path)?)
+        } else {
+            None
+        };
+        self.inputs.push(InputFile {
+            path: path.to_path_buf(),
+            bytes,
+            sha256,
+        });
+        Ok(())
+    }
+
+    pub fn add_warning(&mut self, warning: RunWarning) {
+        self.warnings.push(warning);
+    }
+
+    pub fn add_timing(&mut self, phase: &str, elapsed: Duration) {
+        self.timings.push(PhaseTiming {
+            phase: phase.to_string(),
+            seconds: elapsed.as_secs_f64(),
+        });
+    }
+
+    /// Record an output, if it was written
+    pub fn add_output(&mut self, path: &Path) {
+        if !path.exists() {
+            return;
+        }
+        let bytes = path.is_file().then(|| path.metadata().ok()).flatten();
+        self.outputs.push(OutputFile {
+            path: path.to_path_buf(),
+            bytes: bytes.map(|m| m.len()),
+        });
+    }
+
+    /// Close the report with the run's outcome, checking `conditions` if it
+    /// succeeded
+    pub fn finish(&mut self, outcome: &Result<()>, conditions: &[ExitCondition]) {
+        self.finished_at = Some(Utc::now());
+        self.add_timing("total", self.started.elapsed());
+
+        match outcome {
+            Ok(()) => {
+                self.failed_conditions = conditions
+                    .iter()
+                    .filter(|c| c.is_met(self))
+                    .copied()
+                    .collect();
+                if !self.failed_conditions.is_empty() {
+                    self.exit_code = EXIT_CONDITION_CODE;
+                }
+            }
+            Err(e) => {
+                self.exit_code = 1;
+                self.error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Write the report as pretty-printed JSON
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, self).map_err(|e| ProcessingError::Io(e.into()))
+    }
+}
+
+/// Lower-case hex SHA-256 digest of a file
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 1 << 20];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_inputs_hashed_and_warnings_coded() -> Result<()> {
+        let mut input = NamedTempFile::new()?;
+        write!(input, "abc")?;
+
+        let mut report = RunReport::new("process");
+        report.add_input(input.path())?;
+        assert_eq!(report.inputs[0].bytes, 3);
+        assert_eq!(
+            report.inputs[0].sha256.as_deref(),
+            Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        let mut unhashed = RunReport::new("process").with_checksums(false);
+        unhashed.add_input(input.path())?;
+        assert_eq!(unhashed.inputs[0].sha256, None);
+
+        report.add_warning(
+            RunWarning::new(WarningCode::StationNotInMetadata, "Station 999 not found")
+                .with_station(999),
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["warnings"][0]["code"], "station_not_in_metadata");
+        assert_eq!(json["warnings"][0]["station_id"], 999);
+        assert!(json["warnings"][0].get("path").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_exit_conditions() {
+        assert_eq!(
+            ExitCondition::parse_list("warnings, no-records").unwrap(),
+            vec![ExitCondition::Warnings, ExitCondition::NoRecords]
+        );
+        assert!(ExitCondition::parse_list("warnings,typo").is_err());
+
+        let conditions = [ExitCondition::Warnings, ExitCondition::NoRecords];
+        let mut clean = RunReport::new("process");
+        clean.counts.total_records = 10;
+        clean.finish(&Ok(()), &conditions);
+        assert_eq!((clean.exit_code, clean.failed_conditions.len()), (0, 0));
+
+        let mut empty = RunReport::new("process");
+        empty.finish(&Ok(()), &conditions);
+        assert_eq!(empty.exit_code, EXIT_CONDITION_CODE);
+        assert_eq!(empty.failed_conditions, vec![ExitCondition::NoRecords]);
+
+        // Errors take precedence over conditions
+        let mut failed = RunReport::new("process");
+        failed.finish(&Err(ProcessingError::Cancelled), &conditions);
+        assert_eq!(failed.exit_code, 1);
+        assert!(failed.failed_conditions.is_empty());
+        assert!(failed.error.is_some());
+    }
+}
@@ -0,0 +1,410 @@
+use crate::error::{ProcessingError, Result};
+use crate::models::weather::{PhysicalValidity, SpatialConsistency};
+use crate::models::{StationMetadata, WeatherRecord};
+use crate::utils::coordinates::haversine_distance;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+use tracing::debug;
+
+/// Standard atmosphere lapse rate (°C per metre) used to bring neighbour
+/// temperatures to the target station's elevation
+const LAPSE_RATE: f64 = 0.0065;
+
+/// Neighbour values a day needs before its value is scored
+const MIN_NEIGHBOUR_VALUES: usize = 3;
+
+/// Lower bounds on the neighbour spread, so near-identical neighbours do not
+/// turn every small difference into an outlier. Precipitation is scored on
+/// square-rooted values.
+const TEMP_MIN_SPREAD: f64 = 1.5;
+const PRECIP_MIN_SPREAD: f64 = 1.0;
+
+/// A nearby station used to estimate another station's values
+#[derive(Debug, Clone, PartialEq)]
+pub struct Neighbour {
+    pub station_id: u32,
+    pub distance_km: f64,
+    /// Neighbour elevation minus target elevation, when both are known
+    pub elevation_difference_m: Option<i32>,
+}
+
+/// Counts from a spatial QC run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpatialQcSummary {
+    pub stations: usize,
+    pub stations_with_neighbours: usize,
+    pub values_scored: usize,
+    pub suspect: usize,
+    pub outliers: usize,
+}
+
+impl SpatialQcSummary {
+    pub fn summary(&self) -> String {
+        format!(
+            "Spatial QC: {} of {} stations have neighbours, {} values scored, {} suspect, {} outliers",
+            self.stations_with_neighbours,
+            self.stations,
+            self.values_scored,
+            self.suspect,
+            self.outliers
+        )
+    }
+}
+
+/// Daily values a neighbour can contribute: TN, TX, TG, RR
+type DailyValues = [Option<f32>; 4];
+
+/// Flags values that disagree with an estimate from the nearest stations.
+///
+/// The estimate is the median of the neighbours' values on the same day,
+/// with temperatures adjusted for elevation. The score is the difference
+/// from that median in units of the neighbours' spread (scaled MAD).
+pub struct SpatialQualityControl {
+    neighbours: usize,
+    max_distance_km: f64,
+    max_elevation_difference_m: i32,
+    suspect_score: f64,
+    outlier_score: f64,
+}
+
+impl Default for SpatialQualityControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpatialQualityControl {
+    pub fn new() -> Self {
+        Self {
+            neighbours: 5,
+            max_distance_km: 150.0,
+            max_elevation_difference_m: 500,
+            suspect_score: 3.0,
+            outlier_score: 5.0,
+        }
+    }
+
+    pub fn with_neighbours(mut self, neighbours: usize) -> Self {
+        self.neighbours = neighbours;
+        self
+    }
+
+    pub fn with_max_distance_km(mut self, max_distance_km: f64) -> Self {
+        self.max_distance_km = max_distance_km;
+        self
+    }
+
+    /// Skip neighbours whose elevation differs by more than this many metres
+    pub fn with_max_elevation_difference(mut self, metres: i32) -> Self {
+        self.max_elevation_difference_m = metres;
+        self
+    }
+
+    /// Absolute scores at which a value becomes Suspect and an Outlier
+    pub fn with_thresholds(mut self, suspect_score: f64, outlier_score: f64) -> Result<Self> {
+        if !(suspect_score > 0.0 && suspect_score <= outlier_score) {
+            return Err(ProcessingError::Config(format!(
+                "Spatial QC thresholds must satisfy 0 < suspect ({}) <= outlier ({})",
+                suspect_score, outlier_score
+            )));
+        }
+        self.suspect_score = suspect_score;
+        self.outlier_score = outlier_score;
+        Ok(self)
+    }
+
+    /// Nearest stations for every station, closest first
+    pub fn find_neighbours(&self, stations: &[StationMetadata]) -> HashMap<u32, Vec<Neighbour>> {
+        stations
+            .iter()
+            .map(|target| {
+                let mut neighbours: Vec<Neighbour> = stations
+                    .iter()
+                    .filter(|other| other.staid != target.staid)
+                    .filter_map(|other| {
+                        let distance_km = haversine_distance(
+                            target.latitude,
+                            target.longitude,
+                            other.latitude,
+                            other.longitude,
+                        );
+                        let elevation_difference_m =
+                            other.elevation.zip(target.elevation).map(|(o, t)| o - t);
+                        let too_high = elevation_difference_m
+                            .is_some_and(|d| d.abs() > self.max_elevation_difference_m);
+                        (distance_km <= self.max_distance_km && !too_high).then_some(Neighbour {
+                            station_id: other.staid,
+                            distance_km,
+                            elevation_difference_m,
+                        })
+                    })
+                    .collect();
+                neighbours.sort_by(|a, b| a.distance_km.total_cmp(&b.distance_km));
+                neighbours.truncate(self.neighbours);
+                (target.staid, neighbours)
+            })
+            .collect()
+    }
+
+    /// Score temperature and precipitation values against their neighbours,
+    /// setting the `*_spatial` status and score on each record.
+    ///
+    /// Values without enough neighbour values that day are left unscored.
+    pub fn check(&self, records: &mut [WeatherRecord]) -> SpatialQcSummary {
+        let stations = stations_from_records(records);
+        let neighbours = self.find_neighbours(&stations);
+
+        let daily: HashMap<(u32, NaiveDate), DailyValues> = records
+            .iter()
+            .map(|r| ((r.station_id, r.date), usable_values(r)))
+            .collect();
+
+        let mut summary = SpatialQcSummary {
+            stations: stations.len(),
+            stations_with_neighbours: neighbours.values().filter(|n| !n.is_empty()).count(),
+            ..Default::default()
+        };
+
+        for record in records.iter_mut() {
+            let Some(station_neighbours) = neighbours.get(&record.station_id) else {
+                continue;
+            };
+            let values = usable_values(record);
+
+            // The worst of TN, TX and TG stands for the temperature
+            let temp_score = (0..3)
+                .filter_map(|element| {
+                    let value = values[element]?;
+                    let estimates: Vec<f64> = station_neighbours
+                        .iter()
+                        .filter_map(|n| {
+                            let neighbour = daily.get(&(n.station_id, record.date))?[element]?;
+                            let adjustment =
+                                n.elevation_difference_m.unwrap_or(0) as f64 * LAPSE_RATE;
+                            Some(neighbour as f64 + adjustment)
+                        })
+                        .collect();
+                    score(value as f64, estimates, TEMP_MIN_SPREAD)
+                })
+                .max_by(|a, b| a.abs().total_cmp(&b.abs()));
+
+            let precip_score = values[3].and_then(|value| {
+                let estimates: Vec<f64> = station_neighbours
+                    .iter()
+                    .filter_map(|n| daily.get(&(n.station_id, record.date))?[3])
+                    .map(|v| (v.max(0.0) as f64).sqrt())
+                    .collect();
+                score((value.max(0.0) as f64).sqrt(), estimates, PRECIP_MIN_SPREAD)
+            });
+
+            record.temp_spatial = temp_score.map(|s| self.classify(s, &mut summary));
+            record.temp_spatial_score = temp_score.map(|s| s as f32);
+            record.precip_spatial = precip_score.map(|s| self.classify(s, &mut summary));
+            record.precip_spatial_score = precip_score.map(|s| s as f32);
+        }
+
+        debug!(
+            stations = summary.stations,
+            values_scored = summary.values_scored,
+            outliers = summary.outliers,
+            "Spatial QC complete"
+        );
+        summary
+    }
+
+    fn classify(&self, score: f64, summary: &mut SpatialQcSummary) -> SpatialConsistency {
+        summary.values_scored += 1;
+        if score.abs() >= self.outlier_score {
+            summary.outliers += 1;
+            SpatialConsistency::Outlier
+        } else if score.abs() >= self.suspect_score {
+            summary.suspect += 1;
+            SpatialConsistency::Suspect
+        } else {
+            SpatialConsistency::Consistent
+        }
+    }
+}
+
+/// One StationMetadata per station, from the coordinates on its records
+pub fn stations_from_records(records: &[WeatherRecord]) -> Vec<StationMetadata> {
+    let mut stations: BTreeMap<u32, StationMetadata> = BTreeMap::new();
+    for record in records {
+        stations.entry(record.station_id).or_insert_with(|| {
+            StationMetadata::new(
+                record.station_id,
+                record.station_name.clone(),
+                record.country.clone().unwrap_or_default(),
+                record.latitude,
+                record.longitude,
+                record.elevation,
+            )
+        });
+    }
+    stations.into_values().collect()
+}
+
+/// TN, TX, TG and RR, without physically impossible values
+fn usable_values(record: &WeatherRecord) -> DailyValues {
+    let temp_ok = record.temp_validation != Some(PhysicalValidity::Invalid);
+    let precip_ok = record.precip_validation != Some(PhysicalValidity::Invalid);
+    [
+        record.temp_min.filter(|_| temp_ok),
+        record.temp_max.filter(|_| temp_ok),
+        record.temp_avg.filter(|_| temp_ok),
+        record.precipitation.filter(|_| precip_ok),
+    ]
+}
+
+/// Distance of `value` from the median of `estimates`, in units of their
+/// scaled median absolute deviation
+fn score(value: f64, mut estimates: Vec<f64>, min_spread: f64) -> Option<f64> {
+    if estimates.len() < MIN_NEIGHBOUR_VALUES {
+        return None;
+    }
+    let centre = median(&mut estimates);
+    let mut deviations: Vec<f64> = estimates.iter().map(|e| (e - centre).abs()).collect();
+    let spread = (1.4826 * median(&mut deviations)).max(min_spread);
+    Some((value - centre) / spread)
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        station_id: u32,
+        lat: f64,
+        lon: f64,
+        elevation: i32,
+        tg: f32,
+        rr: f32,
+    ) -> WeatherRecord {
+        let mut record = WeatherRecord::new(
+            station_id,
+            format!("Station {}", station_id),
+            NaiveDate::from_ymd_opt(2020, 7, 1).unwrap(),
+            lat,
+            lon,
+            None,
+            None,
+            Some(tg),
+            Some(rr),
+            None,
+            Some("0".to_string()),
+            Some("0".to_string()),
+            None,
+        );
+        record.elevation = Some(elevation);
+        record
+    }
+
+    #[test]
+    fn test_spatial_outlier_flagged() {
+        // Shetland-style cluster reporting around 12°C, one station at 35°C
+        let mut records = vec![
+            record(1, 60.15, -1.15, 80, 35.0, 1.0),
+            record(2, 60.20, -1.30, 20, 12.0, 1.2),
+            record(3, 60.40, -1.20, 10, 11.5, 0.8),
+            record(4, 59.90, -1.30, 5, 12.4, 1.0),
+            record(5, 60.00, -1.00, 30, 12.1, 1.1),
+            // Mainland station far outside the search radius
+            record(6, 51.50, -0.10, 20, 35.0, 0.0),
+        ];
+
+        let summary = SpatialQualityControl::new().check(&mut records);
+        assert_eq!(summary.stations, 6);
+        assert_eq!(summary.stations_with_neighbours, 5);
+
+        assert_eq!(records[0].temp_spatial, Some(SpatialConsistency::Outlier));
+        assert!(records[0].temp_spatial_score.unwrap() > 5.0);
+        assert_eq!(
+            records[1].temp_spatial,
+            Some(SpatialConsistency::Consistent)
+        );
+        assert_eq!(
+            records[0].precip_spatial,
+            Some(SpatialConsistency::Consistent)
+        );
+        assert_eq!(records[5].temp_spatial, None);
+        assert_eq!(summary.outliers, 1);
+    }
+
+    #[test]
+    fn test_elevation_adjustment_and_limits() {
+        // A hill station 400 m up is 2.6°C colder than the valley
+        let mut records = vec![
+            record(1, 50.00, 0.00, 450, 17.4, 0.0),
+            record(2, 50.10, 0.00, 50, 20.0, 0.0),
+            record(3, 50.00, 0.10, 50, 20.0, 0.0),
+            record(4, 49.90, 0.00, 50, 20.0, 0.0),
+        ];
+        let qc = SpatialQualityControl::new();
+        qc.check(&mut records);
+        assert!(records[0].temp_spatial_score.unwrap().abs() < 0.1);
+
+        // Neighbours too far above or below are not used
+        let neighbours = qc
+            .with_max_elevation_difference(300)
+            .find_neighbours(&stations_from_records(&records));
+        assert!(neighbours[&1].is_empty());
+        assert_eq!(neighbours[&2].len(), 2);
+
+        assert!(SpatialQualityControl::new()
+            .with_thresholds(5.0, 3.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_threshold_edges() {
+        // Identical neighbours leave the minimum spread of 1.5°C, so 4.5°C
+        // above them scores exactly 3 and 7.5°C exactly 5
+        let cluster = |elevation: i32, tg: f32| {
+            vec![
+                record(1, 50.00, 0.00, elevation, tg, 0.0),
+                record(2, 50.10, 0.00, 50, 20.0, 0.0),
+                record(3, 50.00, 0.10, 50, 20.0, 0.0),
+                record(4, 49.90, 0.00, 50, 20.0, 0.0),
+            ]
+        };
+        let qc = SpatialQualityControl::new();
+        for (tg, expected) in [
+            (24.4, SpatialConsistency::Consistent),
+            (24.5, SpatialConsistency::Suspect),
+            (27.4, SpatialConsistency::Suspect),
+            (27.5, SpatialConsistency::Outlier),
+        ] {
+            let mut records = cluster(50, tg);
+            qc.check(&mut records);
+            assert_eq!(records[0].temp_spatial, Some(expected), "{}", tg);
+        }
+
+        // Three neighbour values are needed to score a value
+        let mut records = cluster(50, 35.0);
+        records.pop();
+        qc.check(&mut records);
+        assert_eq!(records[0].temp_spatial, None);
+        assert_eq!(records[0].temp_spatial_score, None);
+
+        // A neighbour exactly at the elevation limit is kept
+        let stations = stations_from_records(&cluster(450, 20.0));
+        let neighbours = SpatialQualityControl::new()
+            .with_max_elevation_difference(400)
+            .find_neighbours(&stations);
+        assert_eq!(neighbours[&1].len(), 3);
+        let neighbours = SpatialQualityControl::new()
+            .with_max_elevation_difference(399)
+            .find_neighbours(&stations);
+        assert!(neighbours[&1].is_empty());
+    }
+}
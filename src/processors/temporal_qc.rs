@@ -0,0 +1,421 @@
+use crate::error::{ProcessingError, Result};
+use crate::models::weather::PhysicalValidity;
+use crate::models::WeatherRecord;
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Jump threshold (°C) used when a month has too few day-to-day differences
+/// to scale one from, matching the legacy consolidated-record check
+const DEFAULT_JUMP_THRESHOLD: f64 = 20.0;
+
+/// Day-to-day differences a calendar month needs for a scaled threshold
+const MIN_DIFFERENCES_PER_MONTH: usize = 30;
+
+/// Lowest scaled jump threshold (°C), so very stable climates do not flag
+/// ordinary weather changes
+const MIN_JUMP_THRESHOLD: f64 = 5.0;
+
+/// Daily precipitation (mm) at or above which a day is wet
+const WET_DAY_MM: f32 = 1.0;
+
+/// Five-day precipitation total (mm) beyond any recorded European extreme
+const MAX_FIVE_DAY_TOTAL_MM: f32 = 1000.0;
+
+/// Record counts per temporal check, for the integrity report
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TemporalQcCounts {
+    pub records_checked: usize,
+    pub spikes: usize,
+    pub stuck_values: usize,
+    pub precipitation_runs: usize,
+    pub tmax_below_tmin: usize,
+}
+
+impl TemporalQcCounts {
+    pub fn merge(&mut self, other: &TemporalQcCounts) {
+        self.records_checked += other.records_checked;
+        self.spikes += other.spikes;
+        self.stuck_values += other.stuck_values;
+        self.precipitation_runs += other.precipitation_runs;
+        self.tmax_below_tmin += other.tmax_below_tmin;
+    }
+}
+
+/// Time-series checks on each station's daily records: day-to-day spikes,
+/// stuck sensors, implausible precipitation runs and Tmax below Tmin
+pub struct TemporalQualityControl {
+    spike_factor: f64,
+    stuck_days: usize,
+    max_wet_run_days: usize,
+}
+
+impl Default for TemporalQualityControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TemporalQualityControl {
+    pub fn new() -> Self {
+        Self {
+            spike_factor: 4.0,
+            stuck_days: 5,
+            max_wet_run_days: 60,
+        }
+    }
+
+    /// Jumps larger than this many standard deviations of the station's
+    /// day-to-day differences for that calendar month are spikes
+    pub fn with_spike_factor(mut self, spike_factor: f64) -> Result<Self> {
+        if spike_factor.is_nan() || spike_factor <= 0.0 {
+            return Err(ProcessingError::Config(format!(
+                "Spike factor must be positive, got {}",
+                spike_factor
+            )));
+        }
+        self.spike_factor = spike_factor;
+        Ok(self)
+    }
+
+    /// Identical values on this many consecutive days mark a stuck sensor
+    pub fn with_stuck_days(mut self, stuck_days: usize) -> Result<Self> {
+        if stuck_days < 2 {
+            return Err(ProcessingError::Config(format!(
+                "Stuck sensor run must be at least 2 days, got {}",
+                stuck_days
+            )));
+        }
+        self.stuck_days = stuck_days;
+        Ok(self)
+    }
+
+    /// Longest plausible run of consecutive wet days
+    pub fn with_max_wet_run_days(mut self, days: usize) -> Self {
+        self.max_wet_run_days = days;
+        self
+    }
+
+    /// Run every check and set the `qc_*` flags on each record
+    pub fn check(&self, records: &mut [WeatherRecord]) -> TemporalQcCounts {
+        let mut stations: HashMap<u32, Vec<usize>> = HashMap::new();
+        for (i, record) in records.iter().enumerate() {
+            stations.entry(record.station_id).or_default().push(i);
+        }
+
+        let mut flags = vec![Flags::default(); records.len()];
+        for mut indices in stations.into_values() {
+            indices.sort_by_key(|&i| records[i].date);
+            let series: Vec<&WeatherRecord> = indices.iter().map(|&i| &records[i]).collect();
+            let station_flags = self.check_station(&series);
+            for (i, f) in indices.into_iter().zip(station_flags) {
+                flags[i] = f;
+            }
+        }
+
+        let mut counts = TemporalQcCounts {
+            records_checked: records.len(),
+            ..Default::default()
+        };
+        for (record, f) in records.iter_mut().zip(flags) {
+            counts.spikes += f.spike as usize;
+            counts.stuck_values += f.stuck as usize;
+            counts.precipitation_runs += f.precip_run as usize;
+            counts.tmax_below_tmin += f.tmax_below_tmin as usize;
+
+            record.qc_spike = Some(f.spike);
+            record.qc_stuck = Some(f.stuck);
+            record.qc_precip_run = Some(f.precip_run);
+            record.qc_tmax_below_tmin = Some(f.tmax_below_tmin);
+        }
+        debug!(
+            records = counts.records_checked,
+            spikes = counts.spikes,
+            stuck_values = counts.stuck_values,
+            "Temporal QC complete"
+        );
+        counts
+    }
+
+    /// Flags for one station's records, sorted by date
+    fn check_station(&self, series: &[&WeatherRecord]) -> Vec<Flags> {
+        let mut flags = vec![Flags::default(); series.len()];
+        let dates: Vec<NaiveDate> = series.iter().map(|r| r.date).collect();
+
+        let temperatures: [Vec<Option<f32>>; 3] = [
+            usable(series, |r| r.temp_min, |r| r.temp_validation),
+            usable(series, |r| r.temp_max, |r| r.temp_validation),
+            usable(series, |r| r.temp_avg, |r| r.temp_validation),
+        ];
+        let precipitation = usable(series, |r| r.precipitation, |r| r.precip_validation);
+        let wind_speed = usable(series, |r| r.wind_speed, |r| r.wind_validation);
+
+        for values in &temperatures {
+            for i in self.spikes(&dates, values) {
+                flags[i].spike = true;
+            }
+            for i in self.stuck_runs(&dates, values, |_| true) {
+                flags[i].stuck = true;
+            }
+        }
+        // Runs of identical dry days or calm days are ordinary
+        for i in self.stuck_runs(&dates, &precipitation, |v| v > 0.0) {
+            flags[i].stuck = true;
+        }
+        for i in self.stuck_runs(&dates, &wind_speed, |v| v > 0.0) {
+            flags[i].stuck = true;
+        }
+        for i in self.precipitation_runs(&dates, &precipitation) {
+            flags[i].precip_run = true;
+        }
+
+        for (f, (tn, tx)) in flags
+            .iter_mut()
+            .zip(temperatures[0].iter().zip(&temperatures[1]))
+        {
+            f.tmax_below_tmin = matches!((tn, tx), (Some(tn), Some(tx)) if tx < tn);
+        }
+
+        flags
+    }
+
+    /// Days whose change from the previous day exceeds the month's threshold
+    fn spikes(&self, dates: &[NaiveDate], values: &[Option<f32>]) -> Vec<usize> {
+        let jumps: Vec<(usize, f64)> = (1..values.len())
+            .filter(|&i| consecutive(dates[i - 1], dates[i]))
+            .filter_map(|i| Some((i, (values[i]? - values[i - 1]?) as f64)))
+            .collect();
+
+        let mut by_month: [Vec<f64>; 12] = Default::default();
+        for &(i, jump) in &jumps {
+            by_month[dates[i].month0() as usize].push(jump);
+        }
+        let thresholds: Vec<f64> = by_month
+            .iter()
+            .map(|month| {
+                if month.len() < MIN_DIFFERENCES_PER_MONTH {
+                    return DEFAULT_JUMP_THRESHOLD;
+                }
+                let mean = month.iter().sum::<f64>() / month.len() as f64;
+                let variance =
+                    month.iter().map(|j| (j - mean).powi(2)).sum::<f64>() / month.len() as f64;
+                (self.spike_factor * variance.sqrt()).max(MIN_JUMP_THRESHOLD)
+            })
+            .collect();
+
+        jumps
+            .into_iter()
+            .filter(|&(i, jump)| jump.abs() > thresholds[dates[i].month0() as usize])
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Days in runs of identical values at least `stuck_days` long
+    fn stuck_runs(
+        &self,
+        dates: &[NaiveDate],
+        values: &[Option<f32>],
+        counts: impl Fn(f32) -> bool,
+    ) -> Vec<usize> {
+        runs(dates, values, counts, |prev, curr| prev == curr)
+            .into_iter()
+            .filter(|run| run.len() >= self.stuck_days)
+            .flatten()
+            .collect()
+    }
+
+    /// Days in wet runs longer than the limit, or in five-day windows whose
+    /// total no European station has recorded
+    fn precipitation_runs(&self, dates: &[NaiveDate], values: &[Option<f32>]) -> Vec<usize> {
+        let mut flagged: Vec<usize> = runs(dates, values, |v| v >= WET_DAY_MM, |_, _| true)
+            .into_iter()
+            .filter(|run| run.len() > self.max_wet_run_days)
+            .flatten()
+            .collect();
+
+        for start in 0..values.len().saturating_sub(4) {
+            let window = start..start + 5;
+            if !consecutive_span(&dates[window.clone()]) {
+                continue;
+            }
+            let total: f32 = values[window.clone()].iter().flatten().sum();
+            if total > MAX_FIVE_DAY_TOTAL_MM {
+                flagged.extend(window);
+            }
+        }
+
+        flagged.sort_unstable();
+        flagged.dedup();
+        flagged
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Flags {
+    spike: bool,
+    stuck: bool,
+    precip_run: bool,
+    tmax_below_tmin: bool,
+}
+
+/// An element's values with physically impossible ones removed
+fn usable(
+    series: &[&WeatherRecord],
+    value: impl Fn(&WeatherRecord) -> Option<f32>,
+    validity: impl Fn(&WeatherRecord) -> Option<PhysicalValidity>,
+) -> Vec<Option<f32>> {
+    series
+        .iter()
+        .map(|r| value(r).filter(|_| validity(r) != Some(PhysicalValidity::Invalid)))
+        .collect()
+}
+
+fn consecutive(prev: NaiveDate, curr: NaiveDate) -> bool {
+    (curr - prev).num_days() == 1
+}
+
+fn consecutive_span(dates: &[NaiveDate]) -> bool {
+    dates.windows(2).all(|pair| consecutive(pair[0], pair[1]))
+}
+
+/// Maximal runs of consecutive days whose values all satisfy `member`,
+/// each one continuing the value before it
+fn runs(
+    dates: &[NaiveDate],
+    values: &[Option<f32>],
+    member: impl Fn(f32) -> bool,
+    continues: impl Fn(f32, f32) -> bool,
+) -> Vec<Vec<usize>> {
+    let mut runs: Vec<Vec<usize>> = Vec::new();
+
+    for (i, value) in values.iter().enumerate() {
+        let Some(value) = value.filter(|v| member(*v)) else {
+            continue;
+        };
+        let extends = runs.last().and_then(|run| run.last()).is_some_and(|&last| {
+            consecutive(dates[last], dates[i])
+                && values[last].is_some_and(|prev| continues(prev, value))
+        });
+        match runs.last_mut() {
+            Some(run) if extends => run.push(i),
+            _ => runs.push(vec![i]),
+        }
+    }
+
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(values: &[(f32, f32, f32)]) -> Vec<WeatherRecord> {
+        let start = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, &(tn, tx, rr))| {
+                WeatherRecord::new(
+                    257,
+                    "Test Station".to_string(),
+                    start + chrono::Duration::days(i as i64),
+                    52.4,
+                    -1.8,
+                    Some(tn),
+                    Some(tx),
+                    None,
+                    Some(rr),
+                    None,
+                    Some("0".to_string()),
+                    Some("0".to_string()),
+                    None,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_spike_and_tmax_below_tmin() {
+        let mut records = series(&[
+            (1.0, 6.0, 0.0),
+            (2.0, 7.0, 0.0),
+            (2.5, 30.0, 0.0), // TX jumps 23°C
+            (3.0, 2.0, 0.0),  // TX below TN, and back down 28°C
+            (1.5, 6.5, 0.0),
+        ]);
+
+        let counts = TemporalQualityControl::new().check(&mut records);
+        assert_eq!(counts.records_checked, 5);
+        assert_eq!(counts.spikes, 2);
+        assert_eq!(counts.tmax_below_tmin, 1);
+        assert_eq!(records[2].qc_spike, Some(true));
+        assert_eq!(records[3].qc_tmax_below_tmin, Some(true));
+        assert_eq!(records[1].qc_spike, Some(false));
+    }
+
+    #[test]
+    fn test_stuck_sensor_and_precipitation_runs() {
+        // TN stuck at 4.2 for five days; a run of identical dry days is fine
+        let mut days: Vec<(f32, f32, f32)> = (0..5).map(|i| (4.2, 8.0 + i as f32, 0.0)).collect();
+        days.push((3.0, 9.0, 0.0));
+        let mut records = series(&days);
+        let counts = TemporalQualityControl::new().check(&mut records);
+        assert_eq!(counts.stuck_values, 5);
+        assert_eq!(records[5].qc_stuck, Some(false));
+
+        // Ten wet days in a row against a limit of seven
+        let days: Vec<(f32, f32, f32)> = (0..10)
+            .map(|i| (i as f32, 10.0 + i as f32, 1.0 + i as f32))
+            .collect();
+        let mut records = series(&days);
+        let counts = TemporalQualityControl::new()
+            .with_max_wet_run_days(7)
+            .check(&mut records);
+        assert_eq!(counts.precipitation_runs, 10);
+        assert_eq!(counts.stuck_values, 0);
+
+        assert!(TemporalQualityControl::new().with_stuck_days(1).is_err());
+        assert!(TemporalQualityControl::new()
+            .with_spike_factor(0.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_threshold_edges() {
+        // With too few differences for a scaled threshold, a jump of exactly
+        // 20°C is allowed and anything beyond it is a spike
+        let mut records = series(&[(1.0, 6.0, 0.0), (1.5, 26.0, 0.0), (2.0, 5.5, 0.0)]);
+        let counts = TemporalQualityControl::new().check(&mut records);
+        assert_eq!(counts.spikes, 1);
+        assert_eq!(records[1].qc_spike, Some(false));
+        assert_eq!(records[2].qc_spike, Some(true));
+
+        // A run as long as the stuck limit is flagged, one day shorter is not
+        let days: Vec<(f32, f32, f32)> = (0..4).map(|i| (4.2, 8.0 + i as f32, 0.0)).collect();
+        let counts = TemporalQualityControl::new().check(&mut series(&days));
+        assert_eq!(counts.stuck_values, 0);
+        let counts = TemporalQualityControl::new()
+            .with_stuck_days(4)
+            .unwrap()
+            .check(&mut series(&days));
+        assert_eq!(counts.stuck_values, 4);
+
+        // 1 mm is a wet day, and a wet run as long as the limit is plausible
+        let days: Vec<(f32, f32, f32)> = (0..8)
+            .map(|i| (i as f32, 10.0 + i as f32, 1.0 + i as f32))
+            .collect();
+        let qc = |max_days| TemporalQualityControl::new().with_max_wet_run_days(max_days);
+        assert_eq!(qc(8).check(&mut series(&days)).precipitation_runs, 0);
+        assert_eq!(qc(7).check(&mut series(&days)).precipitation_runs, 8);
+
+        // Five days totalling exactly 1000 mm are allowed, a little more is not
+        let days: Vec<(f32, f32, f32)> = (0..5)
+            .map(|i| (i as f32, 10.0 + i as f32, 199.0 + i as f32 * 0.5))
+            .collect();
+        assert_eq!(qc(60).check(&mut series(&days)).precipitation_runs, 0);
+        let mut wetter = days.clone();
+        wetter[4].2 += 0.5;
+        assert_eq!(qc(60).check(&mut series(&wetter)).precipitation_runs, 5);
+    }
+}
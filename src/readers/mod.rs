@@ -1,7 +1,9 @@
 pub mod concurrent_reader;
+pub mod source_reader;
 pub mod station_reader;
 pub mod temperature_reader;
 
 pub use concurrent_reader::{ConcurrentReader, StationTemperatureData, TemperatureData};
+pub use source_reader::SourceReader;
 pub use station_reader::StationReader;
 pub use temperature_reader::{TemperatureIterator, TemperatureReader};
@@ -0,0 +1,197 @@
+use crate::error::{ProcessingError, Result};
+use crate::models::SourceMetadata;
+use crate::utils::coordinates::parse_coordinate;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use tracing::debug;
+
+pub struct SourceReader {
+    skip_headers: bool,
+}
+
+impl SourceReader {
+    pub fn new() -> Self {
+        Self { skip_headers: true }
+    }
+
+    pub fn with_skip_headers(skip_headers: bool) -> Self {
+        Self { skip_headers }
+    }
+
+    /// Read source series metadata from the sources.txt file
+    pub fn read_sources(&self, path: &Path) -> Result<Vec<SourceMetadata>> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        let mut sources = Vec::new();
+
+        for line_result in reader.lines() {
+            let line = line_result?;
+
+            // Skip empty lines
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // Skip header lines: data lines start with the station ID
+            if self.skip_headers
+                && !line
+                    .trim_start()
+                    .chars()
+                    .next()
+                    .unwrap_or(' ')
+                    .is_ascii_digit()
+            {
+                continue;
+            }
+
+            if let Some(source) = self.parse_source_line(&line)? {
+                sources.push(source);
+            }
+        }
+
+        debug!(path = %path.display(), sources = sources.len(), "Read source metadata");
+        Ok(sources)
+    }
+
+    /// Parse a single line from the sources file
+    fn parse_source_line(&self, line: &str) -> Result<Option<SourceMetadata>> {
+        // Expected format: STAID, SOUID,SOUNAME, CN, LAT, LON, HGHT, ELEI, START, STOP, PARID, PARNAME
+        let parts: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
+
+        if parts.len() < 12 {
+            return Ok(None); // Skip malformed lines
+        }
+
+        let staid = parts[0].parse::<u32>().map_err(|_| {
+            ProcessingError::InvalidFormat(format!("Invalid station ID: '{}'", parts[0]))
+        })?;
+        let souid = parts[1].parse::<u32>().map_err(|_| {
+            ProcessingError::InvalidFormat(format!("Invalid source ID: '{}'", parts[1]))
+        })?;
+
+        let name = parts[2].to_string();
+        let country = parts[3].to_string();
+        let latitude = parse_coordinate(parts[4])?;
+        let longitude = parse_coordinate(parts[5])?;
+
+        // Parse elevation (can be negative or missing)
+        let elevation = if parts[6].is_empty() || parts[6] == "-999" {
+            None
+        } else {
+            Some(parts[6].parse::<i32>().map_err(|_| {
+                ProcessingError::InvalidFormat(format!("Invalid elevation: '{}'", parts[6]))
+            })?)
+        };
+
+        let element_id = parts[7].to_string();
+        let start_date = NaiveDate::parse_from_str(parts[8], "%Y%m%d")?;
+        let end_date = NaiveDate::parse_from_str(parts[9], "%Y%m%d")?;
+
+        let participant_id = parts[10].parse::<u32>().ok();
+        // Participant names may themselves contain commas
+        let participant_name = parts[11..].join(", ");
+
+        Ok(Some(SourceMetadata {
+            staid,
+            souid,
+            name,
+            country,
+            latitude,
+            longitude,
+            elevation,
+            element_id,
+            start_date,
+            end_date,
+            participant_id,
+            participant_name,
+        }))
+    }
+
+    /// Read source metadata keyed by SOUID
+    pub fn read_sources_map(&self, path: &Path) -> Result<HashMap<u32, SourceMetadata>> {
+        let sources = self.read_sources(path)?;
+        let mut map = HashMap::with_capacity(sources.len());
+
+        for source in sources {
+            map.insert(source.souid, source);
+        }
+
+        Ok(map)
+    }
+}
+
+impl Default for SourceReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_parse_source_line() {
+        let reader = SourceReader::new();
+
+        let line = "    1,   35,VAEXJOE                                 ,SE,+56:52:00,+014:48:00, 166,TX1 ,18600101,20061231,    1,Swedish Meteorological and Hydrological Institute";
+        let source = reader.parse_source_line(line).unwrap().unwrap();
+
+        assert_eq!(source.staid, 1);
+        assert_eq!(source.souid, 35);
+        assert_eq!(source.name, "VAEXJOE");
+        assert_eq!(source.country, "SE");
+        assert!((source.latitude - 56.866667).abs() < 0.00001);
+        assert!((source.longitude - 14.8).abs() < 0.00001);
+        assert_eq!(source.elevation, Some(166));
+        assert_eq!(source.element_id, "TX1");
+        assert_eq!(
+            source.start_date,
+            NaiveDate::from_ymd_opt(1860, 1, 1).unwrap()
+        );
+        assert_eq!(
+            source.end_date,
+            NaiveDate::from_ymd_opt(2006, 12, 31).unwrap()
+        );
+        assert_eq!(source.participant_id, Some(1));
+        assert_eq!(
+            source.participant_name,
+            "Swedish Meteorological and Hydrological Institute"
+        );
+    }
+
+    #[test]
+    fn test_read_sources_file() -> Result<()> {
+        let mut temp_file = NamedTempFile::new()?;
+        writeln!(temp_file, "EUROPEAN CLIMATE ASSESSMENT & DATASET (ECA&D)")?;
+        writeln!(
+            temp_file,
+            "STAID, SOUID,SOUNAME                                 ,CN, LAT    , LON     ,HGHT,ELEI,START   ,STOP    ,PARID,PARNAME"
+        )?;
+        writeln!(temp_file)?;
+        writeln!(
+            temp_file,
+            "  257,  100,CET CENTRAL ENGLAND                     ,GB,+52:25:12,-001:49:48,  78,TG1 ,18780101,20231231,  500,Met Office, Hadley Centre"
+        )?;
+        writeln!(
+            temp_file,
+            "  257,  101,CET CENTRAL ENGLAND                     ,GB,+52:25:12,-001:49:48,  78,TX1 ,18780101,20231231,  500,Met Office"
+        )?;
+
+        let reader = SourceReader::new();
+        let sources = reader.read_sources(temp_file.path())?;
+
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].souid, 100);
+        assert_eq!(sources[0].participant_name, "Met Office, Hadley Centre");
+        assert!(sources[0].longitude < 0.0);
+        assert_eq!(sources[1].element_id, "TX1");
+
+        Ok(())
+    }
+}
@@ -5,6 +5,7 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
+use tracing::debug;
 
 pub struct StationReader {
     skip_headers: bool,
@@ -53,6 +54,7 @@ impl StationReader {
             }
         }
 
+        debug!(path = %path.display(), stations = stations.len(), "Read station metadata");
         Ok(stations)
     }
 
@@ -141,7 +143,7 @@ mod tests {
             temp_file,
             "------,----------------------------------------,---,--------,--------,-----"
         )?;
-        writeln!(temp_file, "")?;
+        writeln!(temp_file)?;
         writeln!(
             temp_file,
             "    1, VAEXJOE                                 , SE, 56:52:00, 14:48:00,  166"
@@ -1,15 +1,28 @@
+use crate::archive::{TemperatureType, WeatherMetric};
 use crate::error::{ProcessingError, Result};
-use crate::models::TemperatureRecord;
+use crate::models::weather::{MissingObservation, MissingReason};
+use crate::models::{DateFilter, TemperatureRecord};
 use crate::utils::constants::DEFAULT_BUFFER_SIZE;
 use chrono::NaiveDate;
 use memmap2::Mmap;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
+use tracing::trace;
+
+/// What a data line held
+enum ParsedLine {
+    Value(TemperatureRecord),
+    Missing(MissingObservation),
+    Skipped,
+}
+
+const TEMPERATURE: WeatherMetric = WeatherMetric::Temperature(TemperatureType::Average);
 
 pub struct TemperatureReader {
     skip_headers: bool,
     use_mmap: bool,
+    date_filter: DateFilter,
 }
 
 impl TemperatureReader {
@@ -17,6 +30,7 @@ impl TemperatureReader {
         Self {
             skip_headers: true,
             use_mmap: false,
+            date_filter: DateFilter::default(),
         }
     }
 
@@ -24,6 +38,7 @@ impl TemperatureReader {
         Self {
             skip_headers,
             use_mmap: false,
+            date_filter: DateFilter::default(),
         }
     }
 
@@ -31,9 +46,16 @@ impl TemperatureReader {
         Self {
             skip_headers: true,
             use_mmap,
+            date_filter: DateFilter::default(),
         }
     }
 
+    /// Skip lines dated outside `date_filter` while parsing
+    pub fn with_date_filter(mut self, date_filter: DateFilter) -> Self {
+        self.date_filter = date_filter;
+        self
+    }
+
     /// Read temperature records from a file (extracts station ID from filename)
     pub fn read_temperatures(&self, path: &Path) -> Result<Vec<TemperatureRecord>> {
         let station_id = self.extract_station_id_from_path(path)?;
@@ -89,7 +111,16 @@ impl TemperatureReader {
         station_id: u32,
     ) -> Result<Vec<TemperatureRecord>> {
         let file = File::open(path)?;
-        let reader = BufReader::with_capacity(DEFAULT_BUFFER_SIZE, file);
+        self.read_temperatures_from_reader(file, station_id)
+    }
+
+    /// Read temperature records from any byte source, such as a zip entry
+    pub fn read_temperatures_from_reader<R: Read>(
+        &self,
+        source: R,
+        station_id: u32,
+    ) -> Result<Vec<TemperatureRecord>> {
+        let reader = BufReader::with_capacity(DEFAULT_BUFFER_SIZE, source);
         let mut records = Vec::new();
         let mut line_count = 0;
 
@@ -152,17 +183,60 @@ impl TemperatureReader {
         Ok(records)
     }
 
+    /// Read temperature records from any byte source, also returning the
+    /// dated lines that yielded no value and why.
+    ///
+    /// Malformed lines are reported rather than failing the file.
+    pub fn read_temperatures_with_missing_from_reader<R: Read>(
+        &self,
+        source: R,
+        station_id: u32,
+    ) -> Result<(Vec<TemperatureRecord>, Vec<MissingObservation>)> {
+        let reader = BufReader::with_capacity(DEFAULT_BUFFER_SIZE, source);
+        let mut records = Vec::new();
+        let mut missing = Vec::new();
+
+        for (index, line_result) in reader.lines().enumerate() {
+            let line = line_result?;
+            if line.trim().is_empty() || (self.skip_headers && index < 20) {
+                continue;
+            }
+
+            match self.parse_line(&line, station_id) {
+                Ok(ParsedLine::Value(record)) => records.push(record),
+                Ok(ParsedLine::Missing(observation)) => missing.push(observation),
+                Ok(ParsedLine::Skipped) => {}
+                Err(e) => {
+                    trace!(station_id, error = %e, "Malformed temperature line");
+                    missing.extend(self.malformed_line(&line));
+                }
+            }
+        }
+
+        Ok((records, missing))
+    }
+
     /// Parse a single line from the temperature file with provided station ID
     fn parse_temperature_line(
         &self,
         line: &str,
         station_id: u32,
     ) -> Result<Option<TemperatureRecord>> {
+        match self.parse_line(line, station_id)? {
+            ParsedLine::Value(record) => Ok(Some(record)),
+            ParsedLine::Missing(_) | ParsedLine::Skipped => Ok(None),
+        }
+    }
+
+    fn parse_line(&self, line: &str, station_id: u32) -> Result<ParsedLine> {
         // Expected format: SOUID, DATE, TEMP, Q_TEMP
         let parts: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
 
         if parts.len() < 4 {
-            return Ok(None); // Skip malformed lines
+            // Skip malformed lines, noting the day if one can be read
+            return Ok(self
+                .malformed_line(line)
+                .map_or(ParsedLine::Skipped, ParsedLine::Missing));
         }
 
         // Parse source ID
@@ -176,17 +250,22 @@ impl TemperatureReader {
             ProcessingError::InvalidFormat(format!("Invalid date format: '{}'", date_str))
         })?;
 
-        // Parse temperature (in 0.1 degrees Celsius)
-        let temp_str = parts[2];
-        if temp_str == "-9999" {
-            return Ok(None); // Skip missing values
+        if !self.date_filter.matches(date) {
+            return Ok(ParsedLine::Skipped);
         }
 
-        let temp_tenths = temp_str.parse::<i32>().map_err(|_| {
-            ProcessingError::InvalidFormat(format!("Invalid temperature: '{}'", temp_str))
-        })?;
-
-        let temperature = temp_tenths as f32 / 10.0;
+        // TN, TX and TG share units
+        let Some(temperature) = TEMPERATURE.spec().to_physical(parts[2])? else {
+            let reason = match parts[3].parse::<u8>() {
+                Ok(9) => MissingReason::Flag9,
+                _ => MissingReason::Sentinel,
+            };
+            return Ok(ParsedLine::Missing(MissingObservation {
+                souid: Some(souid),
+                date,
+                reason,
+            }));
+        };
 
         // Parse quality flag
         let quality_flag = parts[3].parse::<u8>().map_err(|_| {
@@ -195,7 +274,23 @@ impl TemperatureReader {
 
         let record = TemperatureRecord::new(station_id, souid, date, temperature, quality_flag)?;
 
-        Ok(Some(record))
+        Ok(ParsedLine::Value(record))
+    }
+
+    /// A malformed line as a missing observation, if its date can be read
+    /// and passes the date filter
+    fn malformed_line(&self, line: &str) -> Option<MissingObservation> {
+        let mut parts = line.split(',').map(str::trim);
+        let souid = parts.next().and_then(|p| p.parse().ok());
+        let date = parts
+            .next()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y%m%d").ok())
+            .filter(|d| self.date_filter.matches(*d))?;
+        Some(MissingObservation {
+            souid,
+            date,
+            reason: MissingReason::MalformedLine,
+        })
     }
 
     /// Read temperature records for a specific station
@@ -346,6 +441,62 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_read_temperatures_from_reader() -> Result<()> {
+        let mut content = "Header line\n".repeat(20);
+        content.push_str("  101, 20230101,  125, 0\n  101, 20230102, -9999, 9\n");
+
+        let reader = TemperatureReader::new();
+        let records = reader.read_temperatures_from_reader(content.as_bytes(), 257)?;
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].staid, 257);
+        assert_eq!(records[0].temperature, 12.5);
+
+        let summer = DateFilter::new().with_months("JJA".parse()?);
+        let reader = TemperatureReader::new().with_date_filter(summer);
+        assert!(reader
+            .read_temperatures_from_reader(content.as_bytes(), 257)?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_temperatures_with_missing() -> Result<()> {
+        let mut content = "Header line\n".repeat(20);
+        content.push_str("  101, 20230101,  125, 0\n  101, 20230102, -9999, 9\n");
+        content.push_str("  101, 20230103, -9999, 0\n  101, 20230104,  1x5, 0\n");
+        content.push_str("  101, 20230105\ngarbage\n");
+
+        let reader = TemperatureReader::new();
+        let (records, missing) =
+            reader.read_temperatures_with_missing_from_reader(content.as_bytes(), 257)?;
+
+        assert_eq!(records.len(), 1);
+        let reasons: Vec<_> = missing
+            .iter()
+            .map(|m| (m.date.format("%d").to_string(), m.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ("02".to_string(), MissingReason::Flag9),
+                ("03".to_string(), MissingReason::Sentinel),
+                ("04".to_string(), MissingReason::MalformedLine),
+                ("05".to_string(), MissingReason::MalformedLine),
+            ]
+        );
+        assert!(missing.iter().all(|m| m.souid == Some(101)));
+
+        // The strict reader still rejects the malformed value
+        assert!(reader
+            .read_temperatures_from_reader(content.as_bytes(), 257)
+            .is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_temperature_validation() {
         let reader = TemperatureReader::new();
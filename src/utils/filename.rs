@@ -1,5 +1,5 @@
 use chrono::{Datelike, Local};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Generate default Parquet filename with format: ecad-weather-{YYMMDD}.parquet
 pub fn generate_default_parquet_filename() -> PathBuf {
@@ -26,6 +26,71 @@ pub fn generate_default_unified_parquet_filename() -> PathBuf {
     PathBuf::from("output").join(filename)
 }
 
+/// Companion sources table path for an output file: {stem}_sources.parquet
+pub fn generate_sources_filename(output_file: &Path) -> PathBuf {
+    let stem = output_file
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "ecad-weather".to_string());
+    output_file.with_file_name(format!("{}_sources.parquet", stem))
+}
+
+/// Changelog path for an updated output file: {stem}_changelog.json
+pub fn generate_changelog_filename(output_file: &Path) -> PathBuf {
+    let stem = output_file
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "ecad-weather".to_string());
+    output_file.with_file_name(format!("{}_changelog.json", stem))
+}
+
+/// Run report path for an output file: {stem}_report.json
+pub fn generate_report_filename(output_file: &Path) -> PathBuf {
+    let stem = output_file
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "ecad-weather".to_string());
+    output_file.with_file_name(format!("{}_report.json", stem))
+}
+
+/// Aggregates path for an input dataset: {stem}_{period}.parquet
+pub fn generate_aggregate_filename(input: &Path, period: &str) -> PathBuf {
+    let stem = input
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "ecad-weather".to_string());
+    input.with_file_name(format!("{}_{}.parquet", stem, period))
+}
+
+/// Normals table path for an input dataset: {stem}_normals.parquet
+pub fn generate_normals_filename(input: &Path) -> PathBuf {
+    generate_aggregate_filename(input, "normals")
+}
+
+/// Breakpoint table path for an input dataset: {stem}_breakpoints.parquet
+pub fn generate_breakpoints_filename(input: &Path) -> PathBuf {
+    generate_aggregate_filename(input, "breakpoints")
+}
+
+/// Infilled dataset path for an input dataset: {stem}_infilled.parquet
+pub fn generate_infilled_filename(input: &Path) -> PathBuf {
+    generate_aggregate_filename(input, "infilled")
+}
+
+/// Gridded values path for an input dataset: {stem}_grid.parquet
+pub fn generate_grid_filename(input: &Path) -> PathBuf {
+    generate_aggregate_filename(input, "grid")
+}
+
+/// Climate indices path for an input dataset: {stem}_indices.parquet
+pub fn generate_indices_filename(input: &Path) -> PathBuf {
+    let stem = input
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "ecad-weather".to_string());
+    input.with_file_name(format!("{}_indices.parquet", stem))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -60,4 +125,43 @@ mod tests {
         assert!(filename_str.ends_with(".parquet"));
         assert!(filename_str.starts_with("output/"));
     }
+
+    #[test]
+    fn test_generate_sources_filename() {
+        let sources = generate_sources_filename(Path::new("output/ecad-weather-250101.parquet"));
+        assert_eq!(
+            sources,
+            PathBuf::from("output/ecad-weather-250101_sources.parquet")
+        );
+    }
+
+    #[test]
+    fn test_generate_changelog_filename() {
+        let changelog = generate_changelog_filename(Path::new("output/ecad-weather.parquet"));
+        assert_eq!(
+            changelog,
+            PathBuf::from("output/ecad-weather_changelog.json")
+        );
+    }
+
+    #[test]
+    fn test_generate_indices_filename() {
+        assert_eq!(
+            generate_indices_filename(Path::new("output/ecad-weather.parquet")),
+            PathBuf::from("output/ecad-weather_indices.parquet")
+        );
+        // Partitioned dataset directories have no extension
+        assert_eq!(
+            generate_indices_filename(Path::new("output/ecad-weather")),
+            PathBuf::from("output/ecad-weather_indices.parquet")
+        );
+    }
+
+    #[test]
+    fn test_generate_aggregate_filename() {
+        assert_eq!(
+            generate_aggregate_filename(Path::new("output/ecad-weather.parquet"), "monthly"),
+            PathBuf::from("output/ecad-weather_monthly.parquet")
+        );
+    }
 }
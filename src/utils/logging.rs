@@ -0,0 +1,94 @@
+use crate::error::{ProcessingError, Result};
+use std::fs::OpenOptions;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::EnvFilter;
+
+/// How log lines are rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// Human-readable lines, prefixed with the enclosing spans
+    #[default]
+    Text,
+    /// One JSON object per line, including the enclosing spans' fields
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = ProcessingError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => Err(ProcessingError::Config(format!(
+                "Unsupported log format: {} (expected text or json)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Filter used when `RUST_LOG` is unset: this crate at info, or debug when
+/// verbose, and dependencies at warn
+pub fn default_filter(verbose: bool) -> String {
+    let level = if verbose { "debug" } else { "info" };
+    format!("warn,ecad_processor={}", level)
+}
+
+/// Install a global subscriber writing to stderr, or appending to `log_file`.
+///
+/// Returns false without changing anything if a subscriber is already
+/// installed, e.g. by an application embedding the library.
+pub fn init_logging(verbose: bool, format: LogFormat, log_file: Option<&Path>) -> Result<bool> {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(default_filter(verbose)));
+
+    let (writer, ansi) = match log_file {
+        Some(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            (BoxMakeWriter::new(Mutex::new(file)), false)
+        }
+        None => (
+            BoxMakeWriter::new(std::io::stderr),
+            std::io::stderr().is_terminal(),
+        ),
+    };
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .with_ansi(ansi);
+    let installed = match format {
+        LogFormat::Text => builder.try_init(),
+        LogFormat::Json => builder
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .try_init(),
+    };
+
+    Ok(installed.is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_format_parsing() {
+        assert_eq!("text".parse::<LogFormat>().unwrap(), LogFormat::Text);
+        assert_eq!(" JSON ".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn test_default_filter_levels() {
+        assert_eq!(default_filter(false), "warn,ecad_processor=info");
+        assert_eq!(default_filter(true), "warn,ecad_processor=debug");
+        assert!(EnvFilter::try_new(default_filter(true)).is_ok());
+    }
+}
@@ -0,0 +1,38 @@
+use std::fs;
+
+/// Peak resident set size of the current process in bytes.
+///
+/// Read from `VmHWM` in /proc/self/status; returns `None` on platforms
+/// without procfs.
+pub fn peak_memory_bytes() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    parse_vm_hwm(&status)
+}
+
+/// Extract the VmHWM line (reported in kB) from a /proc status file
+fn parse_vm_hwm(status: &str) -> Option<u64> {
+    status
+        .lines()
+        .find(|line| line.starts_with("VmHWM:"))
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|kb| kb.parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+}
+
+/// Format a byte count as MiB for display
+pub fn format_memory(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vm_hwm() {
+        let status = "Name:\tecad-processor\nVmPeak:\t  204800 kB\nVmHWM:\t   10240 kB\nVmRSS:\t    8192 kB\n";
+        assert_eq!(parse_vm_hwm(status), Some(10240 * 1024));
+        assert_eq!(parse_vm_hwm("Name:\tfoo\n"), None);
+        assert_eq!(format_memory(10240 * 1024), "10.0 MiB");
+    }
+}
@@ -1,9 +1,18 @@
 pub mod constants;
 pub mod coordinates;
 pub mod filename;
+pub mod logging;
+pub mod memory;
 pub mod progress;
 
 pub use constants::*;
 pub use coordinates::dms_to_decimal;
-pub use filename::{generate_default_parquet_filename, generate_default_unified_parquet_filename};
+pub use filename::{
+    generate_aggregate_filename, generate_breakpoints_filename, generate_changelog_filename,
+    generate_default_parquet_filename, generate_default_unified_parquet_filename,
+    generate_grid_filename, generate_indices_filename, generate_infilled_filename,
+    generate_normals_filename, generate_report_filename, generate_sources_filename,
+};
+pub use logging::{init_logging, LogFormat};
+pub use memory::{format_memory, peak_memory_bytes};
 pub use progress::ProgressReporter;
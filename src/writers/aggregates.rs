@@ -0,0 +1,101 @@
+use crate::analyzers::PeriodAggregate;
+use crate::error::Result;
+use crate::writers::parquet_writer::unit_field;
+use crate::writers::ParquetWriter;
+use arrow::array::{ArrayRef, Float32Array, Float64Array, Int32Array, StringArray, UInt32Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Write monthly, seasonal or annual aggregates, one row per station and
+/// period
+pub fn write_period_aggregates(
+    parquet: &ParquetWriter,
+    aggregates: &[PeriodAggregate],
+    path: &Path,
+) -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("station_id", DataType::UInt32, false),
+        Field::new("station_name", DataType::Utf8, false),
+        Field::new("latitude", DataType::Float64, false),
+        Field::new("longitude", DataType::Float64, false),
+        Field::new("year", DataType::Int32, false),
+        Field::new("period", DataType::Utf8, false),
+        Field::new("days_in_period", DataType::UInt32, false),
+        unit_field("temp_min_mean", "temp_min"),
+        unit_field("temp_max_mean", "temp_max"),
+        unit_field("temp_avg_mean", "temp_avg"),
+        unit_field("precipitation_total", "precipitation"),
+        unit_field("wind_speed_mean", "wind_speed"),
+        unit_field("wind_speed_max", "wind_speed"),
+        Field::new("temp_min_days", DataType::UInt32, false),
+        Field::new("temp_max_days", DataType::UInt32, false),
+        Field::new("temp_avg_days", DataType::UInt32, false),
+        Field::new("precipitation_days", DataType::UInt32, false),
+        Field::new("wind_speed_days", DataType::UInt32, false),
+    ]));
+
+    let mut writer = parquet.create_arrow_writer(path, schema.clone())?;
+
+    for chunk in aggregates.chunks(parquet.row_group_size()) {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt32Array::from_iter_values(
+                chunk.iter().map(|a| a.station_id),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                chunk.iter().map(|a| a.station_name.as_str()),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                chunk.iter().map(|a| a.latitude),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                chunk.iter().map(|a| a.longitude),
+            )),
+            Arc::new(Int32Array::from_iter_values(chunk.iter().map(|a| a.year))),
+            Arc::new(StringArray::from_iter_values(
+                chunk.iter().map(|a| a.period.as_str()),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                chunk.iter().map(|a| a.days_in_period),
+            )),
+            Arc::new(Float32Array::from_iter(
+                chunk.iter().map(|a| a.temp_min_mean),
+            )),
+            Arc::new(Float32Array::from_iter(
+                chunk.iter().map(|a| a.temp_max_mean),
+            )),
+            Arc::new(Float32Array::from_iter(
+                chunk.iter().map(|a| a.temp_avg_mean),
+            )),
+            Arc::new(Float32Array::from_iter(
+                chunk.iter().map(|a| a.precipitation_total),
+            )),
+            Arc::new(Float32Array::from_iter(
+                chunk.iter().map(|a| a.wind_speed_mean),
+            )),
+            Arc::new(Float32Array::from_iter(
+                chunk.iter().map(|a| a.wind_speed_max),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                chunk.iter().map(|a| a.temp_min_days),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                chunk.iter().map(|a| a.temp_max_days),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                chunk.iter().map(|a| a.temp_avg_days),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                chunk.iter().map(|a| a.precipitation_days),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                chunk.iter().map(|a| a.wind_speed_days),
+            )),
+        ];
+        writer.write(&RecordBatch::try_new(schema.clone(), columns)?)?;
+    }
+
+    writer.close()?;
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use crate::analyzers::Anomaly;
+use crate::error::{ProcessingError, Result};
+use crate::models::WeatherRecord;
+use crate::writers::ParquetWriter;
+use arrow::array::Float32Array;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Write weather records with `temp_avg_anomaly` and `precip_anomaly_pct`
+/// columns appended; `anomalies` must line up with `records`
+pub fn write_weather_records_with_anomalies(
+    parquet: &ParquetWriter,
+    records: &[WeatherRecord],
+    anomalies: &[Anomaly],
+    path: &Path,
+) -> Result<()> {
+    if records.len() != anomalies.len() {
+        return Err(ProcessingError::Config(format!(
+            "{} anomalies for {} records",
+            anomalies.len(),
+            records.len()
+        )));
+    }
+
+    let base_schema = parquet.create_weather_schema();
+    let mut fields: Vec<Field> = base_schema
+        .fields()
+        .iter()
+        .map(|f| f.as_ref().clone())
+        .collect();
+    fields.push(Field::new("temp_avg_anomaly", DataType::Float32, true));
+    fields.push(Field::new("precip_anomaly_pct", DataType::Float32, true));
+    let schema = Arc::new(Schema::new(fields));
+
+    let mut writer = parquet.create_arrow_writer(path, schema.clone())?;
+
+    for (chunk, chunk_anomalies) in records
+        .chunks(parquet.row_group_size())
+        .zip(anomalies.chunks(parquet.row_group_size()))
+    {
+        let batch = parquet.weather_records_to_batch(chunk, base_schema.clone())?;
+        let mut columns = batch.columns().to_vec();
+        columns.push(Arc::new(Float32Array::from_iter(
+            chunk_anomalies.iter().map(|a| a.temp_avg_anomaly),
+        )));
+        columns.push(Arc::new(Float32Array::from_iter(
+            chunk_anomalies.iter().map(|a| a.precip_anomaly_pct),
+        )));
+        writer.write(&RecordBatch::try_new(schema.clone(), columns)?)?;
+    }
+
+    writer.close()?;
+    Ok(())
+}
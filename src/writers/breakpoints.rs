@@ -0,0 +1,106 @@
+use crate::analyzers::Breakpoint;
+use crate::error::Result;
+use crate::writers::ParquetWriter;
+use arrow::array::{
+    ArrayRef, BooleanArray, Date32Array, Float64Array, Int32Array, StringArray, UInt32Array,
+};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Write a homogeneity breakpoint table, one row per station, element and
+/// test
+pub fn write_breakpoints(
+    parquet: &ParquetWriter,
+    breakpoints: &[Breakpoint],
+    path: &Path,
+) -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("station_id", DataType::UInt32, false),
+        Field::new("station_name", DataType::Utf8, false),
+        Field::new("element", DataType::Utf8, false),
+        Field::new("test", DataType::Utf8, false),
+        Field::new("series", DataType::Utf8, false),
+        Field::new("series_length", DataType::UInt32, false),
+        Field::new("break_year", DataType::Int32, false),
+        Field::new("break_period", DataType::Utf8, false),
+        Field::new("break_date", DataType::Date32, false),
+        Field::new("statistic", DataType::Float64, false),
+        Field::new("critical_value", DataType::Float64, false),
+        Field::new("p_value", DataType::Float64, true),
+        Field::new("significant", DataType::Boolean, false),
+        Field::new("mean_before", DataType::Float64, false),
+        Field::new("mean_after", DataType::Float64, false),
+        Field::new("shift", DataType::Float64, false),
+        Field::new("souid_change_date", DataType::Date32, true),
+        Field::new("souid_before", DataType::UInt32, true),
+        Field::new("souid_after", DataType::UInt32, true),
+        Field::new("source_change_date", DataType::Date32, true),
+    ]));
+
+    let mut writer = parquet.create_arrow_writer(path, schema.clone())?;
+
+    let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+    let days = |date: chrono::NaiveDate| date.signed_duration_since(epoch).num_days() as i32;
+    for chunk in breakpoints.chunks(parquet.row_group_size()) {
+        let tests: Vec<String> = chunk.iter().map(|b| b.test.to_string()).collect();
+        let series: Vec<String> = chunk.iter().map(|b| b.period.to_string()).collect();
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt32Array::from_iter_values(
+                chunk.iter().map(|b| b.station_id),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                chunk.iter().map(|b| b.station_name.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                chunk.iter().map(|b| b.element),
+            )),
+            Arc::new(StringArray::from_iter_values(tests.iter())),
+            Arc::new(StringArray::from_iter_values(series.iter())),
+            Arc::new(UInt32Array::from_iter_values(
+                chunk.iter().map(|b| b.series_length),
+            )),
+            Arc::new(Int32Array::from_iter_values(
+                chunk.iter().map(|b| b.break_year),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                chunk.iter().map(|b| b.break_period.as_str()),
+            )),
+            Arc::new(Date32Array::from_iter_values(
+                chunk.iter().map(|b| days(b.break_date)),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                chunk.iter().map(|b| b.statistic),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                chunk.iter().map(|b| b.critical_value),
+            )),
+            Arc::new(Float64Array::from_iter(chunk.iter().map(|b| b.p_value))),
+            Arc::new(BooleanArray::from_iter(
+                chunk.iter().map(|b| Some(b.significant)),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                chunk.iter().map(|b| b.mean_before),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                chunk.iter().map(|b| b.mean_after),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                chunk.iter().map(|b| b.shift()),
+            )),
+            Arc::new(Date32Array::from_iter(
+                chunk.iter().map(|b| b.souid_change_date.map(days)),
+            )),
+            Arc::new(UInt32Array::from_iter(chunk.iter().map(|b| b.souid_before))),
+            Arc::new(UInt32Array::from_iter(chunk.iter().map(|b| b.souid_after))),
+            Arc::new(Date32Array::from_iter(
+                chunk.iter().map(|b| b.source_change_date.map(days)),
+            )),
+        ];
+        writer.write(&RecordBatch::try_new(schema.clone(), columns)?)?;
+    }
+
+    writer.close()?;
+    Ok(())
+}
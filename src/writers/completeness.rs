@@ -0,0 +1,100 @@
+use crate::error::Result;
+use crate::processors::CompletenessReport;
+use crate::writers::ParquetWriter;
+use arrow::array::{Date32Array, Float64Array, Int64Array, ListArray, StringArray, UInt32Array};
+use arrow::datatypes::{DataType, Date32Type, Field, Float64Type, Int32Type, Schema};
+use arrow::record_batch::RecordBatch;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Write a completeness report, one row per station and metric with gap
+/// intervals and yearly coverage as list columns
+pub fn write_completeness_report(
+    parquet: &ParquetWriter,
+    report: &CompletenessReport,
+    path: &Path,
+) -> Result<()> {
+    let list = |item: DataType| DataType::List(Arc::new(Field::new("item", item, true)));
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("station_id", DataType::UInt32, false),
+        Field::new("station_name", DataType::Utf8, false),
+        Field::new("metric", DataType::Utf8, false),
+        Field::new("first_date", DataType::Date32, false),
+        Field::new("last_date", DataType::Date32, false),
+        Field::new("days_present", DataType::UInt32, false),
+        Field::new("days_reported_missing", DataType::UInt32, false),
+        Field::new("days_expected", DataType::UInt32, false),
+        Field::new("completeness_percent", DataType::Float64, false),
+        Field::new("longest_gap_days", DataType::Int64, false),
+        Field::new("gap_starts", list(DataType::Date32), false),
+        Field::new("gap_ends", list(DataType::Date32), false),
+        Field::new("years", list(DataType::Int32), false),
+        Field::new("yearly_percent", list(DataType::Float64), false),
+    ]));
+
+    let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+    let days = |date: chrono::NaiveDate| date.signed_duration_since(epoch).num_days() as i32;
+    let series = &report.series;
+
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(UInt32Array::from_iter_values(
+                series.iter().map(|s| s.station_id),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                series.iter().map(|s| s.station_name.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                series.iter().map(|s| s.metric.as_str()),
+            )),
+            Arc::new(Date32Array::from_iter_values(
+                series.iter().map(|s| days(s.first_date)),
+            )),
+            Arc::new(Date32Array::from_iter_values(
+                series.iter().map(|s| days(s.last_date)),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                series.iter().map(|s| s.days_present as u32),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                series.iter().map(|s| s.days_reported_missing as u32),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                series.iter().map(|s| s.days_expected as u32),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                series.iter().map(|s| s.completeness_percent),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                series.iter().map(|s| s.longest_gap_days),
+            )),
+            Arc::new(ListArray::from_iter_primitive::<Date32Type, _, _>(
+                series
+                    .iter()
+                    .map(|s| Some(s.gaps.iter().map(|g| Some(days(g.start))))),
+            )),
+            Arc::new(ListArray::from_iter_primitive::<Date32Type, _, _>(
+                series
+                    .iter()
+                    .map(|s| Some(s.gaps.iter().map(|g| Some(days(g.end))))),
+            )),
+            Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(
+                series
+                    .iter()
+                    .map(|s| Some(s.yearly.iter().map(|y| Some(y.year)))),
+            )),
+            Arc::new(ListArray::from_iter_primitive::<Float64Type, _, _>(
+                series
+                    .iter()
+                    .map(|s| Some(s.yearly.iter().map(|y| Some(y.percent)))),
+            )),
+        ],
+    )?;
+
+    let mut writer = parquet.create_arrow_writer(path, schema)?;
+    writer.write(&batch)?;
+    writer.close()?;
+
+    Ok(())
+}
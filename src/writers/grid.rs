@@ -0,0 +1,80 @@
+use crate::analyzers::GridValue;
+use crate::error::Result;
+use crate::writers::ParquetWriter;
+use arrow::array::{ArrayRef, Date32Array, Float64Array, StringArray, UInt32Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Incremental writer for gridded values, used so a long period on a fine
+/// grid never has to be held in memory
+pub struct GridStreamWriter {
+    writer: ArrowWriter<File>,
+    schema: Arc<Schema>,
+    values_written: usize,
+}
+
+impl GridStreamWriter {
+    /// Open a streaming writer for gridded values in long format, one row per
+    /// date, cell and metric
+    pub fn create(parquet: &ParquetWriter, path: &Path) -> Result<Self> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("date", DataType::Date32, false),
+            Field::new("latitude", DataType::Float64, false),
+            Field::new("longitude", DataType::Float64, false),
+            Field::new("metric", DataType::Utf8, false),
+            Field::new("value", DataType::Float64, false),
+            Field::new("station_count", DataType::UInt32, false),
+        ]));
+        let writer = parquet.create_arrow_writer(path, schema.clone())?;
+
+        Ok(Self {
+            writer,
+            schema,
+            values_written: 0,
+        })
+    }
+
+    pub fn write_values(&mut self, values: &[GridValue]) -> Result<()> {
+        if values.is_empty() {
+            return Ok(());
+        }
+
+        let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Date32Array::from_iter_values(
+                values
+                    .iter()
+                    .map(|v| v.date.signed_duration_since(epoch).num_days() as i32),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                values.iter().map(|v| v.latitude),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                values.iter().map(|v| v.longitude),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                values.iter().map(|v| v.metric),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                values.iter().map(|v| v.value),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                values.iter().map(|v| v.station_count),
+            )),
+        ];
+        self.writer
+            .write(&RecordBatch::try_new(self.schema.clone(), columns)?)?;
+        self.values_written += values.len();
+        Ok(())
+    }
+
+    /// Close the file, returning the number of values written
+    pub fn finish(self) -> Result<usize> {
+        self.writer.close()?;
+        Ok(self.values_written)
+    }
+}
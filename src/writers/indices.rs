@@ -0,0 +1,50 @@
+use crate::analyzers::IndexValue;
+use crate::error::Result;
+use crate::writers::ParquetWriter;
+use arrow::array::{ArrayRef, Float64Array, Int32Array, StringArray, UInt32Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Write climate index values in long format, one row per station,
+/// period and index
+pub fn write_climate_indices(
+    parquet: &ParquetWriter,
+    values: &[IndexValue],
+    path: &Path,
+) -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("station_id", DataType::UInt32, false),
+        Field::new("station_name", DataType::Utf8, false),
+        Field::new("year", DataType::Int32, false),
+        Field::new("period", DataType::Utf8, false),
+        Field::new("index", DataType::Utf8, false),
+        Field::new("value", DataType::Float64, true),
+        Field::new("units", DataType::Utf8, false),
+    ]));
+
+    let mut writer = parquet.create_arrow_writer(path, schema.clone())?;
+
+    for chunk in values.chunks(parquet.row_group_size()) {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt32Array::from_iter_values(
+                chunk.iter().map(|v| v.station_id),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                chunk.iter().map(|v| v.station_name.as_str()),
+            )),
+            Arc::new(Int32Array::from_iter_values(chunk.iter().map(|v| v.year))),
+            Arc::new(StringArray::from_iter_values(
+                chunk.iter().map(|v| v.period.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(chunk.iter().map(|v| v.index))),
+            Arc::new(Float64Array::from_iter(chunk.iter().map(|v| v.value))),
+            Arc::new(StringArray::from_iter_values(chunk.iter().map(|v| v.units))),
+        ];
+        writer.write(&RecordBatch::try_new(schema.clone(), columns)?)?;
+    }
+
+    writer.close()?;
+    Ok(())
+}
@@ -1,3 +1,22 @@
+pub mod aggregates;
+pub mod anomalies;
+pub mod breakpoints;
+pub mod completeness;
+pub mod grid;
+pub mod indices;
+pub mod normals;
 pub mod parquet_writer;
+pub mod partition;
 
-pub use parquet_writer::{ParquetFileInfo, ParquetWriter, SchemaType, WeatherDatasetSummary};
+pub use aggregates::write_period_aggregates;
+pub use anomalies::write_weather_records_with_anomalies;
+pub use breakpoints::write_breakpoints;
+pub use completeness::write_completeness_report;
+pub use grid::GridStreamWriter;
+pub use indices::write_climate_indices;
+pub use normals::write_climate_normals;
+pub use parquet_writer::{
+    ParquetFileInfo, ParquetWriter, SchemaType, WeatherDatasetSummary, WeatherStreamWriter,
+    UNIT_METADATA_KEY,
+};
+pub use partition::PartitionKey;
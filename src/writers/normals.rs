@@ -0,0 +1,62 @@
+use crate::analyzers::NormalsTable;
+use crate::error::Result;
+use crate::writers::ParquetWriter;
+use arrow::array::{ArrayRef, Float64Array, StringArray, UInt32Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Write a climatological normals table, one row per station, period and
+/// element
+pub fn write_climate_normals(
+    parquet: &ParquetWriter,
+    table: &NormalsTable,
+    path: &Path,
+) -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("station_id", DataType::UInt32, false),
+        Field::new("station_name", DataType::Utf8, false),
+        Field::new("granularity", DataType::Utf8, false),
+        Field::new("period", DataType::UInt32, false),
+        Field::new("element", DataType::Utf8, false),
+        Field::new("base_period", DataType::Utf8, false),
+        Field::new("normal", DataType::Float64, true),
+        Field::new("years", DataType::UInt32, false),
+        Field::new("units", DataType::Utf8, false),
+    ]));
+
+    let mut writer = parquet.create_arrow_writer(path, schema.clone())?;
+
+    let granularity = table.granularity.to_string();
+    let base_period = table.base_period.to_string();
+    for chunk in table.normals.chunks(parquet.row_group_size()) {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt32Array::from_iter_values(
+                chunk.iter().map(|n| n.station_id),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                chunk.iter().map(|n| n.station_name.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                chunk.iter().map(|_| granularity.as_str()),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                chunk.iter().map(|n| n.period),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                chunk.iter().map(|n| n.element),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                chunk.iter().map(|_| base_period.as_str()),
+            )),
+            Arc::new(Float64Array::from_iter(chunk.iter().map(|n| n.normal))),
+            Arc::new(UInt32Array::from_iter_values(chunk.iter().map(|n| n.years))),
+            Arc::new(StringArray::from_iter_values(chunk.iter().map(|n| n.units))),
+        ];
+        writer.write(&RecordBatch::try_new(schema.clone(), columns)?)?;
+    }
+
+    writer.close()?;
+    Ok(())
+}
@@ -1,17 +1,116 @@
-use crate::error::Result;
-use crate::models::{ConsolidatedRecord, WeatherRecord};
+use crate::archive::{ElementSpec, WeatherMetric};
+use crate::error::{ProcessingError, Result};
+use crate::models::weather::MissingReason;
+use crate::models::{ConsolidatedRecord, SourceMetadata, WeatherRecord};
 use crate::utils::constants::DEFAULT_ROW_GROUP_SIZE;
+use crate::writers::partition::{
+    dataset_files, partition_dir, with_partition_columns, PartitionKey,
+};
 use arrow::array::*;
 use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
 use chrono::Datelike;
 use parquet::arrow::ArrowWriter;
-use parquet::basic::{Compression, GzipLevel};
+use parquet::basic::{Compression, GzipLevel, ZstdLevel};
 use parquet::file::properties::WriterProperties;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Temporal QC flag columns, in schema order
+const TEMPORAL_QC_COLUMNS: [&str; 4] = [
+    "qc_spike",
+    "qc_stuck",
+    "qc_precip_run",
+    "qc_tmax_below_tmin",
+];
+
+/// Infill method columns, in schema order
+const INFILLED_COLUMNS: [&str; 4] = [
+    "temp_min_infilled",
+    "temp_max_infilled",
+    "temp_avg_infilled",
+    "precipitation_infilled",
+];
+
+/// Field metadata key holding a value column's units
+pub const UNIT_METADATA_KEY: &str = "unit";
+
+/// A nullable Float32 field carrying the units of an element column as
+/// field metadata, along with its ECA&D element code
+pub(crate) fn unit_field(name: &str, element_column: &str) -> Field {
+    let field = Field::new(name, DataType::Float32, true);
+    match ElementSpec::from_column(element_column) {
+        Some(spec) => field.with_metadata(HashMap::from([
+            (UNIT_METADATA_KEY.to_string(), spec.unit.to_string()),
+            ("ecad_element".to_string(), spec.prefix.to_string()),
+        ])),
+        None => field,
+    }
+}
+
+/// Column mapping each null value column to why it is missing
+const MISSING_REASON_COLUMN: &str = "missing_reason";
+
+/// `Map<Utf8, Utf8>` from value column name to `MissingReason` name
+fn missing_reason_field() -> Field {
+    Field::new_map(
+        MISSING_REASON_COLUMN,
+        "entries",
+        Field::new("keys", DataType::Utf8, false),
+        Field::new("values", DataType::Utf8, true),
+        false,
+        true,
+    )
+}
+
+/// One map entry per missing value column, or null if nothing is missing
+fn encode_missing_reasons(records: &[&WeatherRecord]) -> Result<MapArray> {
+    let mut builder = MapBuilder::new(None, StringBuilder::new(), StringBuilder::new());
+    for record in records {
+        for (column, reason) in &record.missing_reasons {
+            builder.keys().append_value(column);
+            builder.values().append_value(reason.as_str());
+        }
+        builder.append(!record.missing_reasons.is_empty())?;
+    }
+    Ok(builder.finish())
+}
 
+fn decode_missing_reasons(
+    reasons: &MapArray,
+    row: usize,
+) -> Result<BTreeMap<String, MissingReason>> {
+    let entries = reasons.value(row);
+    let (Some(columns), Some(names)) = (
+        entries.column(0).as_any().downcast_ref::<StringArray>(),
+        entries.column(1).as_any().downcast_ref::<StringArray>(),
+    ) else {
+        return Err(ProcessingError::Config(format!(
+            "Invalid {} column type",
+            MISSING_REASON_COLUMN
+        )));
+    };
+
+    let mut decoded = BTreeMap::new();
+    for i in 0..entries.len() {
+        let name = if names.is_null(i) { "" } else { names.value(i) };
+        let reason = MissingReason::parse(name).ok_or_else(|| {
+            ProcessingError::Config(format!(
+                "Unknown missing reason '{}' for {} in {} column",
+                name,
+                columns.value(i),
+                MISSING_REASON_COLUMN
+            ))
+        })?;
+        decoded.insert(columns.value(i).to_string(), reason);
+    }
+    Ok(decoded)
+}
+
+#[derive(Clone)]
 pub struct ParquetWriter {
     compression: Compression,
     row_group_size: usize,
@@ -25,15 +124,36 @@ impl ParquetWriter {
         }
     }
 
+    /// Set the compression codec, e.g. `snappy`, `zstd` or `zstd:9`.
+    ///
+    /// `gzip` and `zstd` accept an optional `:level` suffix.
     pub fn with_compression(mut self, compression: &str) -> Result<Self> {
-        self.compression = match compression.to_lowercase().as_str() {
-            "snappy" => Compression::SNAPPY,
-            "gzip" => Compression::GZIP(GzipLevel::default()),
-            "lz4" => Compression::LZ4,
-            "zstd" => Compression::ZSTD(parquet::basic::ZstdLevel::default()),
-            "none" => Compression::UNCOMPRESSED,
+        let spec = compression.to_lowercase();
+        let (codec, level) = match spec.split_once(':') {
+            Some((codec, level)) => {
+                let level = level.trim().parse::<u32>().map_err(|_| {
+                    ProcessingError::Config(format!("Invalid compression level: {}", compression))
+                })?;
+                (codec.trim(), Some(level))
+            }
+            None => (spec.trim(), None),
+        };
+
+        self.compression = match (codec, level) {
+            ("snappy", None) => Compression::SNAPPY,
+            ("gzip", None) => Compression::GZIP(GzipLevel::default()),
+            ("gzip", Some(level)) => Compression::GZIP(
+                GzipLevel::try_new(level).map_err(|e| ProcessingError::Config(e.to_string()))?,
+            ),
+            ("lz4", None) => Compression::LZ4,
+            ("zstd", None) => Compression::ZSTD(ZstdLevel::default()),
+            ("zstd", Some(level)) => Compression::ZSTD(
+                ZstdLevel::try_new(level as i32)
+                    .map_err(|e| ProcessingError::Config(e.to_string()))?,
+            ),
+            ("none", None) => Compression::UNCOMPRESSED,
             _ => {
-                return Err(crate::error::ProcessingError::Config(format!(
+                return Err(ProcessingError::Config(format!(
                     "Unsupported compression: {}",
                     compression
                 )))
@@ -47,6 +167,28 @@ impl ParquetWriter {
         self
     }
 
+    pub(crate) fn row_group_size(&self) -> usize {
+        self.row_group_size
+    }
+
+    /// Create `path` and open an Arrow writer for `schema` on it with this
+    /// writer's compression and row group size
+    pub(crate) fn create_arrow_writer(
+        &self,
+        path: &Path,
+        schema: Arc<Schema>,
+    ) -> Result<ArrowWriter<File>> {
+        let props = WriterProperties::builder()
+            .set_compression(self.compression)
+            .set_max_row_group_size(self.row_group_size)
+            .build();
+        Ok(ArrowWriter::try_new(
+            File::create(path)?,
+            schema,
+            Some(props),
+        )?)
+    }
+
     /// Write consolidated records to Parquet file
     pub fn write_records(&self, records: &[ConsolidatedRecord], path: &Path) -> Result<()> {
         if records.is_empty() {
@@ -56,13 +198,7 @@ impl ParquetWriter {
         let schema = self.create_schema();
         let batch = self.records_to_batch(records, schema.clone())?;
 
-        let file = File::create(path)?;
-        let props = WriterProperties::builder()
-            .set_compression(self.compression)
-            .set_max_row_group_size(self.row_group_size)
-            .build();
-
-        let mut writer = ArrowWriter::try_new(file, schema, Some(props))?;
+        let mut writer = self.create_arrow_writer(path, schema)?;
         writer.write(&batch)?;
         writer.close()?;
 
@@ -81,13 +217,7 @@ impl ParquetWriter {
         }
 
         let schema = self.create_schema();
-        let file = File::create(path)?;
-        let props = WriterProperties::builder()
-            .set_compression(self.compression)
-            .set_max_row_group_size(self.row_group_size)
-            .build();
-
-        let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(props))?;
+        let mut writer = self.create_arrow_writer(path, schema.clone())?;
 
         // Write in batches
         for chunk in records.chunks(batch_size) {
@@ -176,7 +306,6 @@ impl ParquetWriter {
         path: &Path,
         limit: usize,
     ) -> Result<Vec<ConsolidatedRecord>> {
-        use arrow::array::*;
         use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 
         let file = File::open(path)?;
@@ -306,32 +435,43 @@ impl ParquetWriter {
         Ok(records)
     }
 
-    /// Get file statistics
+    /// Get file statistics, summed over every part file for a dataset directory
     pub fn get_file_info(&self, path: &Path) -> Result<ParquetFileInfo> {
         use parquet::file::reader::{FileReader, SerializedFileReader};
         use std::fs::File;
 
-        let file = File::open(path)?;
-        let reader = SerializedFileReader::new(file)?;
-        let metadata = reader.metadata();
+        let files = dataset_files(path)?;
+        let mut total_rows = 0;
+        let mut row_group_sizes = Vec::new();
+        let mut file_size = 0;
+        let mut compression = None;
 
-        let file_metadata = metadata.file_metadata();
-        let row_groups = metadata.num_row_groups();
-        let total_rows = file_metadata.num_rows();
-        let file_size = std::fs::metadata(path)?.len();
+        for (file_path, _) in &files {
+            let file = File::open(file_path)?;
+            let reader = SerializedFileReader::new(file)?;
+            let metadata = reader.metadata();
 
-        let mut row_group_sizes = Vec::new();
-        for i in 0..row_groups {
-            let rg_metadata = metadata.row_group(i);
-            row_group_sizes.push(rg_metadata.num_rows());
+            total_rows += metadata.file_metadata().num_rows();
+            file_size += std::fs::metadata(file_path)?.len();
+
+            for i in 0..metadata.num_row_groups() {
+                let rg_metadata = metadata.row_group(i);
+                row_group_sizes.push(rg_metadata.num_rows());
+
+                // Report the codec the data was written with, not this writer's setting
+                if compression.is_none() && rg_metadata.num_columns() > 0 {
+                    compression = Some(rg_metadata.column(0).compression());
+                }
+            }
         }
 
         Ok(ParquetFileInfo {
             total_rows,
-            row_groups: row_groups as i32,
+            row_groups: row_group_sizes.len() as i32,
             row_group_sizes,
             file_size,
-            compression: self.compression,
+            file_count: files.len(),
+            compression: compression.unwrap_or(self.compression),
         })
     }
 
@@ -344,16 +484,11 @@ impl ParquetWriter {
         let schema = self.create_weather_schema();
         let batch = self.weather_records_to_batch(records, schema.clone())?;
 
-        let file = File::create(path)?;
-        let props = WriterProperties::builder()
-            .set_compression(self.compression)
-            .set_max_row_group_size(self.row_group_size)
-            .build();
-
-        let mut writer = ArrowWriter::try_new(file, schema, Some(props))?;
+        let mut writer = self.create_arrow_writer(path, schema)?;
         writer.write(&batch)?;
         writer.close()?;
 
+        info!(path = %path.display(), records = records.len(), "Wrote weather records");
         Ok(())
     }
 
@@ -369,13 +504,7 @@ impl ParquetWriter {
         }
 
         let schema = self.create_weather_schema();
-        let file = File::create(path)?;
-        let props = WriterProperties::builder()
-            .set_compression(self.compression)
-            .set_max_row_group_size(self.row_group_size)
-            .build();
-
-        let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(props))?;
+        let mut writer = self.create_arrow_writer(path, schema.clone())?;
 
         // Write in batches
         for chunk in records.chunks(batch_size) {
@@ -384,11 +513,95 @@ impl ParquetWriter {
         }
 
         writer.close()?;
+        info!(path = %path.display(), records = records.len(), "Wrote weather records");
         Ok(())
     }
 
+    /// Write weather records as a Hive-partitioned directory dataset, e.g.
+    /// `country=GB/year=1990/part-0.parquet`.
+    ///
+    /// Partition columns live in the directory path rather than in the part
+    /// files. Returns the part files written.
+    pub fn write_weather_records_partitioned(
+        &self,
+        records: &[WeatherRecord],
+        dataset_dir: &Path,
+        keys: &[PartitionKey],
+        batch_size: usize,
+    ) -> Result<Vec<PathBuf>> {
+        if dataset_dir.is_dir() && std::fs::read_dir(dataset_dir)?.next().is_some() {
+            return Err(ProcessingError::Config(format!(
+                "Dataset directory is not empty: {}",
+                dataset_dir.display()
+            )));
+        }
+
+        let schema = self.create_weather_schema();
+        let projection: Vec<usize> = (0..schema.fields().len())
+            .filter(|&idx| {
+                !keys.iter().any(|key| {
+                    key.replaces_column() && key.column_name() == schema.field(idx).name()
+                })
+            })
+            .collect();
+        let file_schema = Arc::new(schema.project(&projection)?);
+
+        let mut partitions: BTreeMap<PathBuf, Vec<&WeatherRecord>> = BTreeMap::new();
+        for record in records {
+            partitions
+                .entry(partition_dir(keys, record))
+                .or_default()
+                .push(record);
+        }
+
+        let mut part_files = Vec::with_capacity(partitions.len());
+        for (partition, partition_records) in partitions {
+            let dir = dataset_dir.join(partition);
+            std::fs::create_dir_all(&dir)?;
+
+            let path = dir.join("part-0.parquet");
+            let mut writer = self.create_arrow_writer(&path, file_schema.clone())?;
+
+            for chunk in partition_records.chunks(batch_size.max(1)) {
+                let batch = self.weather_record_refs_to_batch(chunk, schema.clone())?;
+                writer.write(&batch.project(&projection)?)?;
+            }
+
+            writer.close()?;
+            debug!(path = %path.display(), records = partition_records.len(), "Wrote partition");
+            part_files.push(path);
+        }
+
+        info!(
+            path = %dataset_dir.display(),
+            partitions = part_files.len(),
+            records = records.len(),
+            "Wrote partitioned dataset"
+        );
+        Ok(part_files)
+    }
+
+    /// Open a streaming writer that appends weather records as they are produced
+    pub fn create_weather_stream(
+        &self,
+        path: &Path,
+        batch_size: usize,
+    ) -> Result<WeatherStreamWriter> {
+        let schema = self.create_weather_schema();
+        let writer = self.create_arrow_writer(path, schema.clone())?;
+
+        Ok(WeatherStreamWriter {
+            encoder: self.clone(),
+            writer,
+            schema,
+            buffer: Vec::with_capacity(batch_size),
+            batch_size: batch_size.max(1),
+            records_written: 0,
+        })
+    }
+
     /// Create Arrow schema for multi-metric weather data
-    fn create_weather_schema(&self) -> Arc<Schema> {
+    pub(crate) fn create_weather_schema(&self) -> Arc<Schema> {
         let fields = vec![
             Field::new("station_id", DataType::UInt32, false),
             Field::new("station_name", DataType::Utf8, false),
@@ -396,13 +609,13 @@ impl ParquetWriter {
             Field::new("latitude", DataType::Float64, false),
             Field::new("longitude", DataType::Float64, false),
             // Optional temperature fields
-            Field::new("temp_min", DataType::Float32, true),
-            Field::new("temp_max", DataType::Float32, true),
-            Field::new("temp_avg", DataType::Float32, true),
+            unit_field("temp_min", "temp_min"),
+            unit_field("temp_max", "temp_max"),
+            unit_field("temp_avg", "temp_avg"),
             // Optional precipitation field
-            Field::new("precipitation", DataType::Float32, true),
+            unit_field("precipitation", "precipitation"),
             // Optional wind speed field
-            Field::new("wind_speed", DataType::Float32, true),
+            unit_field("wind_speed", "wind_speed"),
             // Quality flag fields (original ECAD)
             Field::new("temp_quality", DataType::Utf8, true),
             Field::new("precip_quality", DataType::Utf8, true),
@@ -413,14 +626,85 @@ impl ParquetWriter {
             Field::new("wind_validation", DataType::Utf8, true),
         ];
 
+        // Supplementary elements: values, then quality flags, then validation
+        let mut fields = fields;
+        let supplementary = WeatherMetric::supplementary();
+        for metric in &supplementary {
+            fields.push(unit_field(metric.column_name(), metric.column_name()));
+        }
+        for metric in &supplementary {
+            fields.push(Field::new(
+                format!("{}_quality", metric.column_name()),
+                DataType::Utf8,
+                true,
+            ));
+        }
+        for metric in &supplementary {
+            fields.push(Field::new(
+                format!("{}_validation", metric.column_name()),
+                DataType::Utf8,
+                true,
+            ));
+        }
+
+        // Source series ID (SOUID) for every value column
+        let value_columns = [
+            "temp_min",
+            "temp_max",
+            "temp_avg",
+            "precipitation",
+            "wind_speed",
+        ]
+        .into_iter()
+        .chain(supplementary.iter().map(|m| m.column_name()));
+        for column in value_columns {
+            fields.push(Field::new(
+                format!("{}_souid", column),
+                DataType::UInt32,
+                true,
+            ));
+        }
+
+        fields.push(Field::new("country", DataType::Utf8, true));
+        fields.push(Field::new("elevation", DataType::Int32, true));
+
+        // Spatial QC status and score
+        fields.push(Field::new("temp_spatial", DataType::Utf8, true));
+        fields.push(Field::new("temp_spatial_score", DataType::Float32, true));
+        fields.push(Field::new("precip_spatial", DataType::Utf8, true));
+        fields.push(Field::new("precip_spatial_score", DataType::Float32, true));
+
+        // Temporal QC flags
+        for column in TEMPORAL_QC_COLUMNS {
+            fields.push(Field::new(column, DataType::Boolean, true));
+        }
+
+        // How each infilled value was estimated
+        for column in INFILLED_COLUMNS {
+            fields.push(Field::new(column, DataType::Utf8, true));
+        }
+
+        // Why value columns are null, as a Map<Utf8, Utf8> from column name to reason
+        fields.push(missing_reason_field());
+
         Arc::new(Schema::new(fields))
     }
 
     /// Convert weather records to Arrow RecordBatch
-    fn weather_records_to_batch(
+    pub(crate) fn weather_records_to_batch(
         &self,
         records: &[WeatherRecord],
         schema: Arc<Schema>,
+    ) -> Result<RecordBatch> {
+        let records: Vec<&WeatherRecord> = records.iter().collect();
+        self.weather_record_refs_to_batch(&records, schema)
+    }
+
+    /// Convert borrowed weather records to Arrow RecordBatch without cloning them
+    fn weather_record_refs_to_batch(
+        &self,
+        records: &[&WeatherRecord],
+        schema: Arc<Schema>,
     ) -> Result<RecordBatch> {
         // Extract data into separate vectors
         let station_ids: Vec<u32> = records.iter().map(|r| r.station_id).collect();
@@ -489,30 +773,221 @@ impl ParquetWriter {
         let precip_validation_array = Arc::new(StringArray::from(precip_validations));
         let wind_validation_array = Arc::new(StringArray::from(wind_validations));
 
+        // Supplementary element columns, in schema order
+        let element_views: Vec<_> = records.iter().map(|r| r.supplementary_elements()).collect();
+        let element_count = WeatherMetric::supplementary().len();
+        let mut supplementary_values: Vec<ArrayRef> = Vec::with_capacity(element_count);
+        let mut supplementary_qualities: Vec<ArrayRef> = Vec::with_capacity(element_count);
+        let mut supplementary_validations: Vec<ArrayRef> = Vec::with_capacity(element_count);
+        for idx in 0..element_count {
+            let values: Vec<Option<f32>> = element_views.iter().map(|v| v[idx].1).collect();
+            let qualities: Vec<Option<&str>> = element_views.iter().map(|v| v[idx].2).collect();
+            let validations: Vec<Option<String>> = element_views
+                .iter()
+                .map(|v| v[idx].3.map(|val| format!("{:?}", val)))
+                .collect();
+            supplementary_values.push(Arc::new(Float32Array::from(values)));
+            supplementary_qualities.push(Arc::new(StringArray::from(qualities)));
+            supplementary_validations.push(Arc::new(StringArray::from(validations)));
+        }
+
         // Create record batch
+        let mut columns: Vec<ArrayRef> = vec![
+            station_id_array,
+            station_name_array,
+            date_array,
+            latitude_array,
+            longitude_array,
+            temp_min_array,
+            temp_max_array,
+            temp_avg_array,
+            precipitation_array,
+            wind_speed_array,
+            temp_quality_array,
+            precip_quality_array,
+            wind_quality_array,
+            temp_validation_array,
+            precip_validation_array,
+            wind_validation_array,
+        ];
+        columns.extend(supplementary_values);
+        columns.extend(supplementary_qualities);
+        columns.extend(supplementary_validations);
+
+        let source_ids: Vec<_> = records.iter().map(|r| r.source_ids()).collect();
+        for idx in 0..source_ids.first().map_or(0, |ids| ids.len()) {
+            let souids: Vec<Option<u32>> = source_ids.iter().map(|ids| ids[idx].1).collect();
+            columns.push(Arc::new(UInt32Array::from(souids)));
+        }
+
+        let countries: Vec<Option<&str>> = records.iter().map(|r| r.country.as_deref()).collect();
+        columns.push(Arc::new(StringArray::from(countries)));
+        columns.push(Arc::new(Int32Array::from_iter(
+            records.iter().map(|r| r.elevation),
+        )));
+
+        columns.push(Arc::new(StringArray::from_iter(
+            records
+                .iter()
+                .map(|r| r.temp_spatial.map(|v| format!("{:?}", v))),
+        )));
+        columns.push(Arc::new(Float32Array::from_iter(
+            records.iter().map(|r| r.temp_spatial_score),
+        )));
+        columns.push(Arc::new(StringArray::from_iter(
+            records
+                .iter()
+                .map(|r| r.precip_spatial.map(|v| format!("{:?}", v))),
+        )));
+        columns.push(Arc::new(Float32Array::from_iter(
+            records.iter().map(|r| r.precip_spatial_score),
+        )));
+
+        for flag in [
+            |r: &WeatherRecord| r.qc_spike,
+            |r: &WeatherRecord| r.qc_stuck,
+            |r: &WeatherRecord| r.qc_precip_run,
+            |r: &WeatherRecord| r.qc_tmax_below_tmin,
+        ] {
+            columns.push(Arc::new(BooleanArray::from_iter(
+                records.iter().map(|r| flag(r)),
+            )));
+        }
+
+        for method in [
+            |r: &WeatherRecord| r.temp_min_infilled,
+            |r: &WeatherRecord| r.temp_max_infilled,
+            |r: &WeatherRecord| r.temp_avg_infilled,
+            |r: &WeatherRecord| r.precip_infilled,
+        ] {
+            columns.push(Arc::new(StringArray::from_iter(
+                records
+                    .iter()
+                    .map(|r| method(r).map(|m| format!("{:?}", m))),
+            )));
+        }
+
+        columns.push(Arc::new(encode_missing_reasons(records)?));
+
+        let batch = RecordBatch::try_new(schema, columns)?;
+
+        Ok(batch)
+    }
+
+    /// Write source series metadata (sources.txt) to a companion Parquet table
+    ///
+    /// Joins to the weather table on `station_id` and any `*_souid` column.
+    pub fn write_sources(&self, sources: &[SourceMetadata], path: &Path) -> Result<()> {
+        if sources.is_empty() {
+            return Ok(());
+        }
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("station_id", DataType::UInt32, false),
+            Field::new("souid", DataType::UInt32, false),
+            Field::new("source_name", DataType::Utf8, false),
+            Field::new("country", DataType::Utf8, false),
+            Field::new("latitude", DataType::Float64, false),
+            Field::new("longitude", DataType::Float64, false),
+            Field::new("elevation", DataType::Int32, true),
+            Field::new("element_id", DataType::Utf8, false),
+            Field::new("start_date", DataType::Date32, false),
+            Field::new("end_date", DataType::Date32, false),
+            Field::new("participant_id", DataType::UInt32, true),
+            Field::new("participant_name", DataType::Utf8, false),
+        ]));
+
+        let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+        let days = |date: chrono::NaiveDate| date.signed_duration_since(epoch).num_days() as i32;
+
         let batch = RecordBatch::try_new(
-            schema,
+            schema.clone(),
             vec![
-                station_id_array,
-                station_name_array,
-                date_array,
-                latitude_array,
-                longitude_array,
-                temp_min_array,
-                temp_max_array,
-                temp_avg_array,
-                precipitation_array,
-                wind_speed_array,
-                temp_quality_array,
-                precip_quality_array,
-                wind_quality_array,
-                temp_validation_array,
-                precip_validation_array,
-                wind_validation_array,
+                Arc::new(UInt32Array::from_iter_values(
+                    sources.iter().map(|s| s.staid),
+                )),
+                Arc::new(UInt32Array::from_iter_values(
+                    sources.iter().map(|s| s.souid),
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    sources.iter().map(|s| s.name.as_str()),
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    sources.iter().map(|s| s.country.as_str()),
+                )),
+                Arc::new(Float64Array::from_iter_values(
+                    sources.iter().map(|s| s.latitude),
+                )),
+                Arc::new(Float64Array::from_iter_values(
+                    sources.iter().map(|s| s.longitude),
+                )),
+                Arc::new(Int32Array::from_iter(sources.iter().map(|s| s.elevation))),
+                Arc::new(StringArray::from_iter_values(
+                    sources.iter().map(|s| s.element_id.as_str()),
+                )),
+                Arc::new(Date32Array::from_iter_values(
+                    sources.iter().map(|s| days(s.start_date)),
+                )),
+                Arc::new(Date32Array::from_iter_values(
+                    sources.iter().map(|s| days(s.end_date)),
+                )),
+                Arc::new(UInt32Array::from_iter(
+                    sources.iter().map(|s| s.participant_id),
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    sources.iter().map(|s| s.participant_name.as_str()),
+                )),
             ],
         )?;
 
-        Ok(batch)
+        let mut writer = self.create_arrow_writer(path, schema)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        Ok(())
+    }
+
+    /// Read a sources table written by `write_sources`
+    pub fn read_sources(&self, path: &Path) -> Result<Vec<SourceMetadata>> {
+        let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+        let date = |days: i32| epoch + chrono::Duration::days(days as i64);
+
+        let mut sources = Vec::new();
+        for batch_result in Self::read_dataset_batches(path, None)? {
+            let batch = batch_result?;
+            let station_ids = Self::required_column::<UInt32Array>(&batch, "station_id")?;
+            let souids = Self::required_column::<UInt32Array>(&batch, "souid")?;
+            let names = Self::required_column::<StringArray>(&batch, "source_name")?;
+            let countries = Self::required_column::<StringArray>(&batch, "country")?;
+            let latitudes = Self::required_column::<Float64Array>(&batch, "latitude")?;
+            let longitudes = Self::required_column::<Float64Array>(&batch, "longitude")?;
+            let elevations = Self::required_column::<Int32Array>(&batch, "elevation")?;
+            let element_ids = Self::required_column::<StringArray>(&batch, "element_id")?;
+            let start_dates = Self::required_column::<Date32Array>(&batch, "start_date")?;
+            let end_dates = Self::required_column::<Date32Array>(&batch, "end_date")?;
+            let participant_ids = Self::required_column::<UInt32Array>(&batch, "participant_id")?;
+            let participant_names =
+                Self::required_column::<StringArray>(&batch, "participant_name")?;
+
+            for i in 0..batch.num_rows() {
+                sources.push(SourceMetadata {
+                    staid: station_ids.value(i),
+                    souid: souids.value(i),
+                    name: names.value(i).to_string(),
+                    country: countries.value(i).to_string(),
+                    latitude: latitudes.value(i),
+                    longitude: longitudes.value(i),
+                    elevation: (!elevations.is_null(i)).then(|| elevations.value(i)),
+                    element_id: element_ids.value(i).to_string(),
+                    start_date: date(start_dates.value(i)),
+                    end_date: date(end_dates.value(i)),
+                    participant_id: (!participant_ids.is_null(i)).then(|| participant_ids.value(i)),
+                    participant_name: participant_names.value(i).to_string(),
+                });
+            }
+        }
+
+        Ok(sources)
     }
 
     /// Read sample weather records from Parquet file
@@ -521,290 +996,284 @@ impl ParquetWriter {
         path: &Path,
         limit: usize,
     ) -> Result<Vec<WeatherRecord>> {
-        use arrow::array::*;
-        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
-
-        let file = File::open(path)?;
-        let parquet_reader = ParquetRecordBatchReaderBuilder::try_new(file)?
-            .with_batch_size(limit.min(8192))
-            .build()?;
+        let parquet_reader = Self::read_dataset_batches(path, Some(limit.min(8192)))?;
 
         let mut records = Vec::new();
-        let mut total_read = 0;
 
         for batch_result in parquet_reader {
             let batch = batch_result?;
 
-            // Extract arrays from the batch - handle both old and new schema
-            let num_columns = batch.num_columns();
-
-            if num_columns < 13 {
+            if batch.schema().index_of("temp_min").is_err() {
                 // Old schema format - return empty for now
                 return Ok(Vec::new());
             }
 
-            let has_validation_fields = num_columns >= 16;
+            let remaining = limit - records.len();
+            let mut batch_records = Self::weather_records_from_batch(&batch)?;
+            batch_records.truncate(remaining);
+            records.extend(batch_records);
 
-            // New WeatherRecord schema
-            let station_ids = batch
-                .column(0)
-                .as_any()
-                .downcast_ref::<UInt32Array>()
-                .ok_or_else(|| {
-                    crate::error::ProcessingError::Config(
-                        "Invalid station_id column type".to_string(),
-                    )
-                })?;
-            let station_names = batch
-                .column(1)
-                .as_any()
-                .downcast_ref::<StringArray>()
-                .ok_or_else(|| {
-                    crate::error::ProcessingError::Config(
-                        "Invalid station_name column type".to_string(),
-                    )
-                })?;
-            let dates = batch
-                .column(2)
-                .as_any()
-                .downcast_ref::<Date32Array>()
-                .ok_or_else(|| {
-                    crate::error::ProcessingError::Config("Invalid date column type".to_string())
-                })?;
-            let latitudes = batch
-                .column(3)
-                .as_any()
-                .downcast_ref::<Float64Array>()
-                .ok_or_else(|| {
-                    crate::error::ProcessingError::Config(
-                        "Invalid latitude column type".to_string(),
-                    )
-                })?;
-            let longitudes = batch
-                .column(4)
-                .as_any()
-                .downcast_ref::<Float64Array>()
-                .ok_or_else(|| {
-                    crate::error::ProcessingError::Config(
-                        "Invalid longitude column type".to_string(),
-                    )
-                })?;
+            if records.len() >= limit {
+                break;
+            }
+        }
 
-            // Optional temperature fields
-            let temp_mins = batch
-                .column(5)
-                .as_any()
-                .downcast_ref::<Float32Array>()
-                .ok_or_else(|| {
-                    crate::error::ProcessingError::Config(
-                        "Invalid temp_min column type".to_string(),
-                    )
-                })?;
-            let temp_maxs = batch
-                .column(6)
-                .as_any()
-                .downcast_ref::<Float32Array>()
-                .ok_or_else(|| {
-                    crate::error::ProcessingError::Config(
-                        "Invalid temp_max column type".to_string(),
-                    )
-                })?;
-            let temp_avgs = batch
-                .column(7)
-                .as_any()
-                .downcast_ref::<Float32Array>()
-                .ok_or_else(|| {
-                    crate::error::ProcessingError::Config(
-                        "Invalid temp_avg column type".to_string(),
-                    )
-                })?;
+        Ok(records)
+    }
 
-            // Optional other weather metrics
-            let precipitations = batch
-                .column(8)
-                .as_any()
-                .downcast_ref::<Float32Array>()
-                .ok_or_else(|| {
-                    crate::error::ProcessingError::Config(
-                        "Invalid precipitation column type".to_string(),
-                    )
-                })?;
-            let wind_speeds = batch
-                .column(9)
-                .as_any()
-                .downcast_ref::<Float32Array>()
-                .ok_or_else(|| {
-                    crate::error::ProcessingError::Config(
-                        "Invalid wind_speed column type".to_string(),
-                    )
-                })?;
+    /// Read every weather record from a Parquet file or partitioned dataset
+    /// directory written by this crate
+    pub fn read_weather_records(&self, path: &Path) -> Result<Vec<WeatherRecord>> {
+        let parquet_reader = Self::read_dataset_batches(path, None)?;
 
-            // Optional quality flags
-            let temp_qualities = batch
-                .column(10)
-                .as_any()
-                .downcast_ref::<StringArray>()
-                .ok_or_else(|| {
-                    crate::error::ProcessingError::Config(
-                        "Invalid temp_quality column type".to_string(),
-                    )
-                })?;
-            let precip_qualities = batch
-                .column(11)
-                .as_any()
-                .downcast_ref::<StringArray>()
-                .ok_or_else(|| {
-                    crate::error::ProcessingError::Config(
-                        "Invalid precip_quality column type".to_string(),
-                    )
-                })?;
-            let wind_qualities = batch
-                .column(12)
+        let mut records = Vec::new();
+        for batch_result in parquet_reader {
+            let batch = batch_result?;
+
+            if batch.schema().index_of("temp_min").is_err() {
+                return Err(ProcessingError::InvalidFormat(format!(
+                    "{} is not a weather record dataset",
+                    path.display()
+                )));
+            }
+
+            records.extend(Self::weather_records_from_batch(&batch)?);
+        }
+
+        Ok(records)
+    }
+
+    /// Units recorded in the field metadata of a Parquet file or dataset,
+    /// keyed by column name
+    pub fn read_column_units(&self, path: &Path) -> Result<BTreeMap<String, String>> {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let Some((file_path, _)) = dataset_files(path)?.into_iter().next() else {
+            return Ok(BTreeMap::new());
+        };
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(file_path)?)?;
+        Ok(builder
+            .schema()
+            .fields()
+            .iter()
+            .filter_map(|field| {
+                field
+                    .metadata()
+                    .get(UNIT_METADATA_KEY)
+                    .map(|unit| (field.name().clone(), unit.clone()))
+            })
+            .collect())
+    }
+
+    /// Stream record batches from a single file or every part file of a
+    /// partitioned dataset, restoring partition columns from the path.
+    ///
+    /// Part files are opened one at a time as the iterator advances.
+    fn read_dataset_batches(
+        path: &Path,
+        batch_size: Option<usize>,
+    ) -> Result<Box<dyn Iterator<Item = Result<RecordBatch>>>> {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let open = move |file_path: &Path| -> Result<_> {
+            let mut builder = ParquetRecordBatchReaderBuilder::try_new(File::open(file_path)?)?;
+            if let Some(batch_size) = batch_size {
+                builder = builder.with_batch_size(batch_size);
+            }
+            Ok(builder.build()?)
+        };
+
+        let files = dataset_files(path)?;
+        Ok(Box::new(files.into_iter().flat_map(
+            move |(file_path, partitions)| -> Box<dyn Iterator<Item = Result<RecordBatch>>> {
+                match open(&file_path) {
+                    Ok(reader) => Box::new(
+                        reader.map(move |batch| with_partition_columns(batch?, &partitions)),
+                    ),
+                    Err(e) => Box::new(std::iter::once(Err(e))),
+                }
+            },
+        )))
+    }
+
+    /// Look up an optional column by name, checking its type when present
+    fn optional_column<'a, T: Array + 'static>(
+        batch: &'a RecordBatch,
+        name: &str,
+    ) -> Result<Option<&'a T>> {
+        match batch.schema().index_of(name) {
+            Ok(idx) => batch
+                .column(idx)
                 .as_any()
-                .downcast_ref::<StringArray>()
+                .downcast_ref::<T>()
+                .map(Some)
                 .ok_or_else(|| {
-                    crate::error::ProcessingError::Config(
-                        "Invalid wind_quality column type".to_string(),
-                    )
-                })?;
+                    crate::error::ProcessingError::Config(format!("Invalid {} column type", name))
+                }),
+            Err(_) => Ok(None),
+        }
+    }
 
-            // Optional validation fields (new schema)
-            let temp_validations = if has_validation_fields {
-                Some(
-                    batch
-                        .column(13)
-                        .as_any()
-                        .downcast_ref::<StringArray>()
-                        .ok_or_else(|| {
-                            crate::error::ProcessingError::Config(
-                                "Invalid temp_validation column type".to_string(),
-                            )
-                        })?,
-                )
-            } else {
-                None
-            };
-            let precip_validations = if has_validation_fields {
-                Some(
-                    batch
-                        .column(14)
-                        .as_any()
-                        .downcast_ref::<StringArray>()
-                        .ok_or_else(|| {
-                            crate::error::ProcessingError::Config(
-                                "Invalid precip_validation column type".to_string(),
-                            )
-                        })?,
-                )
-            } else {
-                None
-            };
-            let wind_validations = if has_validation_fields {
-                Some(
-                    batch
-                        .column(15)
-                        .as_any()
-                        .downcast_ref::<StringArray>()
-                        .ok_or_else(|| {
-                            crate::error::ProcessingError::Config(
-                                "Invalid wind_validation column type".to_string(),
-                            )
-                        })?,
-                )
-            } else {
-                None
-            };
+    /// Look up a required column by name
+    fn required_column<'a, T: Array + 'static>(
+        batch: &'a RecordBatch,
+        name: &str,
+    ) -> Result<&'a T> {
+        Self::optional_column(batch, name)?.ok_or_else(|| {
+            crate::error::ProcessingError::Config(format!("Missing {} column", name))
+        })
+    }
 
-            // Convert to WeatherRecord objects
-            let batch_records_to_read = (batch.num_rows()).min(limit - total_read);
+    /// Convert a WeatherRecord batch back into records.
+    ///
+    /// Columns are resolved by name so files written before the validation
+    /// and supplementary element columns existed can still be read.
+    fn weather_records_from_batch(batch: &RecordBatch) -> Result<Vec<WeatherRecord>> {
+        use crate::models::weather::{InfillMethod, PhysicalValidity, SpatialConsistency};
 
-            for i in 0..batch_records_to_read {
-                let date = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
-                    + chrono::Duration::days(dates.value(i) as i64);
+        fn opt_f32(arr: Option<&Float32Array>, i: usize) -> Option<f32> {
+            arr.filter(|a| !a.is_null(i)).map(|a| a.value(i))
+        }
+        fn opt_string(arr: Option<&StringArray>, i: usize) -> Option<String> {
+            arr.filter(|a| !a.is_null(i))
+                .map(|a| a.value(i).to_string())
+        }
+        fn opt_validity(arr: Option<&StringArray>, i: usize) -> Option<PhysicalValidity> {
+            arr.filter(|a| !a.is_null(i))
+                .and_then(|a| PhysicalValidity::parse(a.value(i)))
+        }
+        fn opt_spatial(arr: Option<&StringArray>, i: usize) -> Option<SpatialConsistency> {
+            arr.filter(|a| !a.is_null(i))
+                .and_then(|a| SpatialConsistency::parse(a.value(i)))
+        }
 
-                use crate::models::weather::PhysicalValidity;
+        let station_ids = Self::required_column::<UInt32Array>(batch, "station_id")?;
+        let station_names = Self::required_column::<StringArray>(batch, "station_name")?;
+        let dates = Self::required_column::<Date32Array>(batch, "date")?;
+        let latitudes = Self::required_column::<Float64Array>(batch, "latitude")?;
+        let longitudes = Self::required_column::<Float64Array>(batch, "longitude")?;
+        let countries = Self::optional_column::<StringArray>(batch, "country")?;
+        let elevations = Self::optional_column::<Int32Array>(batch, "elevation")?;
+
+        let temp_mins = Self::optional_column::<Float32Array>(batch, "temp_min")?;
+        let temp_maxs = Self::optional_column::<Float32Array>(batch, "temp_max")?;
+        let temp_avgs = Self::optional_column::<Float32Array>(batch, "temp_avg")?;
+        let precipitations = Self::optional_column::<Float32Array>(batch, "precipitation")?;
+        let wind_speeds = Self::optional_column::<Float32Array>(batch, "wind_speed")?;
+
+        let temp_qualities = Self::optional_column::<StringArray>(batch, "temp_quality")?;
+        let precip_qualities = Self::optional_column::<StringArray>(batch, "precip_quality")?;
+        let wind_qualities = Self::optional_column::<StringArray>(batch, "wind_quality")?;
+
+        let temp_validations = Self::optional_column::<StringArray>(batch, "temp_validation")?;
+        let precip_validations = Self::optional_column::<StringArray>(batch, "precip_validation")?;
+        let wind_validations = Self::optional_column::<StringArray>(batch, "wind_validation")?;
+
+        let temp_spatial = Self::optional_column::<StringArray>(batch, "temp_spatial")?;
+        let temp_spatial_scores =
+            Self::optional_column::<Float32Array>(batch, "temp_spatial_score")?;
+        let precip_spatial = Self::optional_column::<StringArray>(batch, "precip_spatial")?;
+        let precip_spatial_scores =
+            Self::optional_column::<Float32Array>(batch, "precip_spatial_score")?;
+
+        let mut temporal_flags = Vec::with_capacity(TEMPORAL_QC_COLUMNS.len());
+        for column in TEMPORAL_QC_COLUMNS {
+            temporal_flags.push(Self::optional_column::<BooleanArray>(batch, column)?);
+        }
+        let opt_flag = |column: usize, i: usize| {
+            temporal_flags[column]
+                .filter(|a| !a.is_null(i))
+                .map(|a| a.value(i))
+        };
 
-                let record = WeatherRecord::new_raw(
-                    station_ids.value(i),
-                    station_names.value(i).to_string(),
-                    date,
-                    latitudes.value(i),
-                    longitudes.value(i),
-                    if temp_mins.is_null(i) {
-                        None
-                    } else {
-                        Some(temp_mins.value(i))
-                    },
-                    if temp_maxs.is_null(i) {
-                        None
-                    } else {
-                        Some(temp_maxs.value(i))
-                    },
-                    if temp_avgs.is_null(i) {
-                        None
-                    } else {
-                        Some(temp_avgs.value(i))
-                    },
-                    if precipitations.is_null(i) {
-                        None
-                    } else {
-                        Some(precipitations.value(i))
-                    },
-                    if wind_speeds.is_null(i) {
-                        None
-                    } else {
-                        Some(wind_speeds.value(i))
-                    },
-                    if temp_qualities.is_null(i) {
-                        None
-                    } else {
-                        Some(temp_qualities.value(i).to_string())
-                    },
-                    if precip_qualities.is_null(i) {
-                        None
-                    } else {
-                        Some(precip_qualities.value(i).to_string())
-                    },
-                    if wind_qualities.is_null(i) {
-                        None
-                    } else {
-                        Some(wind_qualities.value(i).to_string())
-                    },
-                    // Parse validation fields if available
-                    temp_validations.and_then(|arr| {
-                        if arr.is_null(i) {
-                            None
-                        } else {
-                            PhysicalValidity::parse(arr.value(i))
-                        }
-                    }),
-                    precip_validations.and_then(|arr| {
-                        if arr.is_null(i) {
-                            None
-                        } else {
-                            PhysicalValidity::parse(arr.value(i))
-                        }
-                    }),
-                    wind_validations.and_then(|arr| {
-                        if arr.is_null(i) {
-                            None
-                        } else {
-                            PhysicalValidity::parse(arr.value(i))
-                        }
-                    }),
-                );
+        let mut infilled = Vec::with_capacity(INFILLED_COLUMNS.len());
+        for column in INFILLED_COLUMNS {
+            infilled.push(Self::optional_column::<StringArray>(batch, column)?);
+        }
+        let opt_infilled = |column: usize, i: usize| {
+            infilled[column]
+                .filter(|a| !a.is_null(i))
+                .and_then(|a| InfillMethod::parse(a.value(i)))
+        };
 
-                records.push(record);
-                total_read += 1;
+        let missing_reasons = Self::optional_column::<MapArray>(batch, MISSING_REASON_COLUMN)?;
+
+        let mut supplementary = Vec::new();
+        for metric in WeatherMetric::supplementary() {
+            let name = metric.column_name();
+            let values = Self::optional_column::<Float32Array>(batch, name)?;
+            let qualities =
+                Self::optional_column::<StringArray>(batch, &format!("{}_quality", name))?;
+            let validations =
+                Self::optional_column::<StringArray>(batch, &format!("{}_validation", name))?;
+            supplementary.push((metric, values, qualities, validations));
+        }
 
-                if total_read >= limit {
-                    break;
-                }
+        let mut source_ids = Vec::new();
+        for field in batch.schema().fields() {
+            if let Some(column) = field.name().strip_suffix("_souid") {
+                let souids = Self::optional_column::<UInt32Array>(batch, field.name())?;
+                source_ids.push((column.to_string(), souids));
             }
+        }
 
-            if total_read >= limit {
-                break;
+        let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+        let mut records = Vec::with_capacity(batch.num_rows());
+
+        for i in 0..batch.num_rows() {
+            let date = epoch + chrono::Duration::days(dates.value(i) as i64);
+
+            let mut record = WeatherRecord::new_raw(
+                station_ids.value(i),
+                station_names.value(i).to_string(),
+                date,
+                latitudes.value(i),
+                longitudes.value(i),
+                opt_f32(temp_mins, i),
+                opt_f32(temp_maxs, i),
+                opt_f32(temp_avgs, i),
+                opt_f32(precipitations, i),
+                opt_f32(wind_speeds, i),
+                opt_string(temp_qualities, i),
+                opt_string(precip_qualities, i),
+                opt_string(wind_qualities, i),
+                opt_validity(temp_validations, i),
+                opt_validity(precip_validations, i),
+                opt_validity(wind_validations, i),
+            );
+
+            record.country = opt_string(countries, i);
+            record.elevation = elevations.filter(|a| !a.is_null(i)).map(|a| a.value(i));
+            record.temp_spatial = opt_spatial(temp_spatial, i);
+            record.temp_spatial_score = opt_f32(temp_spatial_scores, i);
+            record.precip_spatial = opt_spatial(precip_spatial, i);
+            record.precip_spatial_score = opt_f32(precip_spatial_scores, i);
+            record.qc_spike = opt_flag(0, i);
+            record.qc_stuck = opt_flag(1, i);
+            record.qc_precip_run = opt_flag(2, i);
+            record.qc_tmax_below_tmin = opt_flag(3, i);
+            record.temp_min_infilled = opt_infilled(0, i);
+            record.temp_max_infilled = opt_infilled(1, i);
+            record.temp_avg_infilled = opt_infilled(2, i);
+            record.precip_infilled = opt_infilled(3, i);
+            if let Some(reasons) = missing_reasons.filter(|a| !a.is_null(i)) {
+                record.missing_reasons = decode_missing_reasons(reasons, i)?;
+            }
+
+            for (metric, values, qualities, validations) in &supplementary {
+                record.set_supplementary_element(
+                    metric,
+                    opt_f32(*values, i),
+                    opt_string(*qualities, i),
+                );
+                record.set_supplementary_validation(metric, opt_validity(*validations, i));
+            }
+
+            for (column, souids) in &source_ids {
+                record.set_source_id(column, souids.filter(|a| !a.is_null(i)).map(|a| a.value(i)));
             }
+
+            records.push(record);
         }
 
         Ok(records)
@@ -814,18 +1283,24 @@ impl ParquetWriter {
     pub fn detect_schema_type(&self, path: &Path) -> Result<SchemaType> {
         use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 
-        let file = File::open(path)?;
+        // A dataset directory is described by its first part file
+        let Some((file_path, partitions)) = dataset_files(path)?.into_iter().next() else {
+            return Ok(SchemaType::Unknown);
+        };
+        let file = File::open(file_path)?;
         let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
         let schema = builder.schema();
 
-        // Check number of columns to determine schema type
-        let num_columns = schema.fields().len();
+        // Check number of columns to determine schema type, counting the
+        // columns stored in partition directories
+        let num_columns = schema.fields().len() + partitions.len();
 
         if num_columns == 9 {
             // Old ConsolidatedRecord schema: station_id, station_name, date, lat, lon, min_temp, max_temp, avg_temp, quality_flags
             Ok(SchemaType::ConsolidatedRecord)
-        } else if num_columns == 13 || num_columns == 16 {
-            // WeatherRecord schema: 13 cols = original, 16 cols = with validation fields
+        } else if num_columns >= 13 && schema.index_of("temp_min").is_ok() {
+            // WeatherRecord schema: 13 cols = original, 16 cols = with validation fields,
+            // more when supplementary elements are included
             Ok(SchemaType::WeatherRecord)
         } else {
             Ok(SchemaType::Unknown)
@@ -838,11 +1313,9 @@ impl ParquetWriter {
         path: &Path,
         sample_size: usize,
     ) -> Result<WeatherDatasetSummary> {
-        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
         use std::collections::HashSet;
 
-        let file = File::open(path)?;
-        let parquet_reader = ParquetRecordBatchReaderBuilder::try_new(file)?.build()?;
+        let parquet_reader = Self::read_dataset_batches(path, None)?;
 
         let mut total_records = 0;
         let mut stations: HashSet<u32> = HashSet::new();
@@ -867,6 +1340,12 @@ impl ParquetWriter {
         let mut wind_stations: HashSet<u32> = HashSet::new();
         let mut wind_dates = Vec::new();
 
+        // Supplementary element tracking: (records, stations, dates, value range)
+        let mut element_records = [0usize; 8];
+        let mut element_stations: Vec<HashSet<u32>> = vec![HashSet::new(); 8];
+        let mut element_dates: Vec<Option<(chrono::NaiveDate, chrono::NaiveDate)>> = vec![None; 8];
+        let mut element_values: Vec<Option<(f32, f32)>> = vec![None; 8];
+
         // Extreme tracking
         let mut coldest_record: Option<WeatherRecord> = None;
         let mut hottest_record: Option<WeatherRecord> = None;
@@ -893,208 +1372,29 @@ impl ParquetWriter {
 
         for batch_result in parquet_reader {
             let batch = batch_result?;
-            let num_rows = batch.num_rows();
 
-            if batch.num_columns() < 13 {
+            if batch.schema().index_of("temp_min").is_err() {
                 continue; // Skip if not WeatherRecord format
             }
 
-            let has_validation_fields = batch.num_columns() >= 16;
+            use crate::models::weather::{DataQuality, PhysicalValidity};
 
-            // Extract arrays
-            let station_ids = batch
-                .column(0)
-                .as_any()
-                .downcast_ref::<UInt32Array>()
-                .unwrap();
-            let station_names = batch
-                .column(1)
-                .as_any()
-                .downcast_ref::<StringArray>()
-                .unwrap();
-            let dates = batch
-                .column(2)
-                .as_any()
-                .downcast_ref::<Date32Array>()
-                .unwrap();
-            let latitudes = batch
-                .column(3)
-                .as_any()
-                .downcast_ref::<Float64Array>()
-                .unwrap();
-            let longitudes = batch
-                .column(4)
-                .as_any()
-                .downcast_ref::<Float64Array>()
-                .unwrap();
-            let temp_mins = batch
-                .column(5)
-                .as_any()
-                .downcast_ref::<Float32Array>()
-                .unwrap();
-            let temp_maxs = batch
-                .column(6)
-                .as_any()
-                .downcast_ref::<Float32Array>()
-                .unwrap();
-            let temp_avgs = batch
-                .column(7)
-                .as_any()
-                .downcast_ref::<Float32Array>()
-                .unwrap();
-            let precipitations = batch
-                .column(8)
-                .as_any()
-                .downcast_ref::<Float32Array>()
-                .unwrap();
-            let wind_speeds = batch
-                .column(9)
-                .as_any()
-                .downcast_ref::<Float32Array>()
-                .unwrap();
-            let temp_qualities = batch
-                .column(10)
-                .as_any()
-                .downcast_ref::<StringArray>()
-                .unwrap();
-            let precip_qualities = batch
-                .column(11)
-                .as_any()
-                .downcast_ref::<StringArray>()
-                .unwrap();
-            let wind_qualities = batch
-                .column(12)
-                .as_any()
-                .downcast_ref::<StringArray>()
-                .unwrap();
-
-            // Optional validation fields
-            let temp_validations = if has_validation_fields {
-                Some(
-                    batch
-                        .column(13)
-                        .as_any()
-                        .downcast_ref::<StringArray>()
-                        .unwrap(),
-                )
-            } else {
-                None
-            };
-            let precip_validations = if has_validation_fields {
-                Some(
-                    batch
-                        .column(14)
-                        .as_any()
-                        .downcast_ref::<StringArray>()
-                        .unwrap(),
-                )
-            } else {
-                None
-            };
-            let wind_validations = if has_validation_fields {
-                Some(
-                    batch
-                        .column(15)
-                        .as_any()
-                        .downcast_ref::<StringArray>()
-                        .unwrap(),
-                )
-            } else {
-                None
-            };
-
-            for i in 0..num_rows {
+            for record in Self::weather_records_from_batch(&batch)? {
                 total_records += 1;
-                let station_id = station_ids.value(i);
+                let station_id = record.station_id;
                 stations.insert(station_id);
 
-                let date = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
-                    + chrono::Duration::days(dates.value(i) as i64);
+                let date = record.date;
 
                 // Update date bounds
                 min_date = Some(min_date.map_or(date, |d: chrono::NaiveDate| d.min(date)));
                 max_date = Some(max_date.map_or(date, |d: chrono::NaiveDate| d.max(date)));
 
                 // Update geographic bounds
-                let lat = latitudes.value(i);
-                let lon = longitudes.value(i);
-                min_lat = min_lat.min(lat);
-                max_lat = max_lat.max(lat);
-                min_lon = min_lon.min(lon);
-                max_lon = max_lon.max(lon);
-
-                // Create record for sampling and analysis
-                use crate::models::weather::{DataQuality, PhysicalValidity};
-
-                let record = WeatherRecord::new_raw(
-                    station_id,
-                    station_names.value(i).to_string(),
-                    date,
-                    lat,
-                    lon,
-                    if temp_mins.is_null(i) {
-                        None
-                    } else {
-                        Some(temp_mins.value(i))
-                    },
-                    if temp_maxs.is_null(i) {
-                        None
-                    } else {
-                        Some(temp_maxs.value(i))
-                    },
-                    if temp_avgs.is_null(i) {
-                        None
-                    } else {
-                        Some(temp_avgs.value(i))
-                    },
-                    if precipitations.is_null(i) {
-                        None
-                    } else {
-                        Some(precipitations.value(i))
-                    },
-                    if wind_speeds.is_null(i) {
-                        None
-                    } else {
-                        Some(wind_speeds.value(i))
-                    },
-                    if temp_qualities.is_null(i) {
-                        None
-                    } else {
-                        Some(temp_qualities.value(i).to_string())
-                    },
-                    if precip_qualities.is_null(i) {
-                        None
-                    } else {
-                        Some(precip_qualities.value(i).to_string())
-                    },
-                    if wind_qualities.is_null(i) {
-                        None
-                    } else {
-                        Some(wind_qualities.value(i).to_string())
-                    },
-                    // Parse validation fields if available
-                    temp_validations.and_then(|arr| {
-                        if arr.is_null(i) {
-                            None
-                        } else {
-                            PhysicalValidity::parse(arr.value(i))
-                        }
-                    }),
-                    precip_validations.and_then(|arr| {
-                        if arr.is_null(i) {
-                            None
-                        } else {
-                            PhysicalValidity::parse(arr.value(i))
-                        }
-                    }),
-                    wind_validations.and_then(|arr| {
-                        if arr.is_null(i) {
-                            None
-                        } else {
-                            PhysicalValidity::parse(arr.value(i))
-                        }
-                    }),
-                );
+                min_lat = min_lat.min(record.latitude);
+                max_lat = max_lat.max(record.latitude);
+                min_lon = min_lon.min(record.longitude);
+                max_lon = max_lon.max(record.longitude);
 
                 // Track metrics
                 if record.has_temperature_data() {
@@ -1155,6 +1455,49 @@ impl ParquetWriter {
                     }
                 }
 
+                for (idx, (_, value, quality, validation)) in
+                    record.supplementary_elements().into_iter().enumerate()
+                {
+                    let Some(value) = value else { continue };
+                    element_records[idx] += 1;
+                    element_stations[idx].insert(station_id);
+                    element_dates[idx] = Some(match element_dates[idx] {
+                        Some((start, end)) => (start.min(date), end.max(date)),
+                        None => (date, date),
+                    });
+
+                    // Value ranges exclude physically impossible values
+                    if !matches!(validation, Some(PhysicalValidity::Invalid)) {
+                        element_values[idx] = Some(match element_values[idx] {
+                            Some((min, max)) => (min.min(value), max.max(value)),
+                            None => (value, value),
+                        });
+                    }
+
+                    match quality {
+                        Some("0") => ecad_valid += 1,
+                        Some("1") => ecad_suspect += 1,
+                        Some("9") => ecad_missing += 1,
+                        _ => {}
+                    }
+
+                    match validation {
+                        Some(PhysicalValidity::Valid) => physically_valid += 1,
+                        Some(PhysicalValidity::Suspect) => physically_suspect += 1,
+                        Some(PhysicalValidity::Invalid) => physically_invalid += 1,
+                        None => {}
+                    }
+
+                    match WeatherRecord::assess_element_quality(quality, validation) {
+                        DataQuality::Valid => combined_valid += 1,
+                        DataQuality::SuspectOriginal => combined_suspect_original += 1,
+                        DataQuality::SuspectRange => combined_suspect_range += 1,
+                        DataQuality::SuspectBoth => combined_suspect_both += 1,
+                        DataQuality::Invalid => combined_invalid += 1,
+                        DataQuality::Missing => combined_missing += 1,
+                    }
+                }
+
                 // Enhanced data quality analysis
 
                 // Track ECAD quality flags for each metric present
@@ -1318,6 +1661,18 @@ impl ParquetWriter {
                 } else {
                     None
                 },
+                supplementary: WeatherMetric::supplementary()
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(idx, _)| element_records[*idx] > 0)
+                    .map(|(idx, metric)| ElementStatistics {
+                        metric,
+                        records: element_records[idx],
+                        stations: element_stations[idx].len(),
+                        date_range: element_dates[idx],
+                        value_range: element_values[idx],
+                    })
+                    .collect(),
             },
             data_quality: EnhancedDataQuality {
                 ecad_valid,
@@ -1401,6 +1756,63 @@ impl ParquetWriter {
     }
 }
 
+/// Incremental WeatherRecord writer with bounded buffering.
+///
+/// Records are encoded in batches of `batch_size`, and the underlying Arrow
+/// writer flushes a row group every `row_group_size` rows, so memory use does
+/// not grow with the size of the dataset.
+pub struct WeatherStreamWriter {
+    encoder: ParquetWriter,
+    writer: ArrowWriter<File>,
+    schema: Arc<Schema>,
+    buffer: Vec<WeatherRecord>,
+    batch_size: usize,
+    records_written: usize,
+}
+
+impl WeatherStreamWriter {
+    /// Queue records for writing, encoding full batches as they fill up
+    pub fn write_records(&mut self, records: Vec<WeatherRecord>) -> Result<()> {
+        for record in records {
+            self.buffer.push(record);
+            if self.buffer.len() >= self.batch_size {
+                self.flush_buffer()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush_buffer(&mut self) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let batch = self
+            .encoder
+            .weather_records_to_batch(&self.buffer, self.schema.clone())?;
+        self.writer.write(&batch)?;
+        self.records_written += self.buffer.len();
+        debug!(
+            records = self.buffer.len(),
+            total = self.records_written,
+            "Flushed record batch"
+        );
+        self.buffer.clear();
+        Ok(())
+    }
+
+    pub fn records_written(&self) -> usize {
+        self.records_written + self.buffer.len()
+    }
+
+    /// Flush remaining records and close the file, returning the total written
+    pub fn finish(mut self) -> Result<usize> {
+        self.flush_buffer()?;
+        self.writer.close()?;
+        Ok(self.records_written)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum SchemaType {
     ConsolidatedRecord,
@@ -1449,6 +1861,17 @@ pub struct MetricStatistics {
     pub temperature_range: Option<(f32, f32)>,
     pub precipitation_range: Option<(f32, f32)>,
     pub wind_range: Option<(f32, f32)>,
+    /// Statistics for supplementary elements present in the dataset
+    pub supplementary: Vec<ElementStatistics>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ElementStatistics {
+    pub metric: WeatherMetric,
+    pub records: usize,
+    pub stations: usize,
+    pub date_range: Option<(chrono::NaiveDate, chrono::NaiveDate)>,
+    pub value_range: Option<(f32, f32)>,
 }
 
 #[derive(Debug, Clone)]
@@ -1588,6 +2011,30 @@ impl WeatherDatasetSummary {
             wind_range
         ));
 
+        // Supplementary element rows
+        for element in &self.metric_statistics.supplementary {
+            let coverage = if self.total_records > 0 {
+                (element.records as f32 / self.total_records as f32) * 100.0
+            } else {
+                0.0
+            };
+
+            let range = if let Some((start, end)) = element.date_range {
+                format!("{}-{}", start.year(), end.year())
+            } else {
+                "N/A".to_string()
+            };
+
+            summary.push_str(&format!(
+                "│ {:15} │ {:8} │ {:11} │ {:10.1}%  │ {:11} │\n",
+                element.metric.display_name(),
+                element.stations,
+                element.records,
+                coverage,
+                range
+            ));
+        }
+
         summary.push_str(
             "└─────────────────┴──────────┴─────────────┴──────────────┴─────────────┘\n\n",
         );
@@ -1620,6 +2067,12 @@ impl WeatherDatasetSummary {
                     metrics_display.push(format!("wind={:.1}m/s", wind));
                 }
 
+                for (metric, value, _, _) in record.supplementary_elements() {
+                    if let Some(value) = value {
+                        metrics_display.push(format!("{}={:.1}", metric.column_name(), value));
+                    }
+                }
+
                 let metrics_str = if metrics_display.is_empty() {
                     "no data".to_string()
                 } else {
@@ -1786,12 +2239,14 @@ pub struct ParquetFileInfo {
     pub row_groups: i32,
     pub row_group_sizes: Vec<i64>,
     pub file_size: u64,
+    /// Number of part files (1 for a single Parquet file)
+    pub file_count: usize,
     pub compression: Compression,
 }
 
 impl ParquetFileInfo {
     pub fn summary(&self) -> String {
-        format!(
+        let mut summary = format!(
             "Parquet File Summary:\n\
             - Total rows: {}\n\
             - Row groups: {}\n\
@@ -1803,7 +2258,11 @@ impl ParquetFileInfo {
             self.file_size as f64 / 1_048_576.0, // Convert to MB
             self.compression,
             self.total_rows as f64 / self.row_groups as f64
-        )
+        );
+        if self.file_count > 1 {
+            summary.push_str(&format!("\n- Part files: {}", self.file_count));
+        }
+        summary
     }
 }
 
@@ -1852,7 +2311,7 @@ mod tests {
 
     #[test]
     fn test_different_compressions() -> Result<()> {
-        let compressions = ["snappy", "gzip", "lz4", "zstd", "none"];
+        let compressions = ["snappy", "gzip", "gzip:9", "lz4", "zstd", "zstd:19", "none"];
 
         for compression in &compressions {
             let writer = ParquetWriter::new().with_compression(compression)?;
@@ -1877,4 +2336,166 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_compression_levels() {
+        let writer = ParquetWriter::new().with_compression("ZSTD:3").unwrap();
+        assert_eq!(
+            writer.compression,
+            Compression::ZSTD(ZstdLevel::try_new(3).unwrap())
+        );
+
+        assert!(ParquetWriter::new().with_compression("zstd:23").is_err());
+        assert!(ParquetWriter::new().with_compression("zstd:fast").is_err());
+        assert!(ParquetWriter::new().with_compression("snappy:1").is_err());
+        assert!(ParquetWriter::new().with_compression("brotli").is_err());
+    }
+
+    #[test]
+    fn test_weather_records_round_trip() -> Result<()> {
+        let writer = ParquetWriter::new();
+        let temp_file = NamedTempFile::new().unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2023, 7, 15).unwrap();
+        let mut record = WeatherRecord::new(
+            12345,
+            "Test Station".to_string(),
+            date,
+            51.5074,
+            -0.1278,
+            Some(10.0),
+            Some(20.0),
+            Some(15.0),
+            None,
+            None,
+            Some("000".to_string()),
+            None,
+            None,
+        );
+        record.set_supplementary_element(
+            &WeatherMetric::SnowDepth,
+            Some(12.0),
+            Some("0".to_string()),
+        );
+        record.set_source_id("snow_depth", Some(4242));
+        record.temp_avg_souid = Some(101);
+        record.perform_physical_validation();
+
+        writer.write_weather_records(&[record], temp_file.path())?;
+        assert_eq!(
+            writer.detect_schema_type(temp_file.path())?,
+            SchemaType::WeatherRecord
+        );
+
+        let records = writer.read_sample_weather_records(temp_file.path(), 10)?;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].temp_avg, Some(15.0));
+        assert_eq!(records[0].snow_depth, Some(12.0));
+        assert_eq!(records[0].snow_depth_quality.as_deref(), Some("0"));
+        assert_eq!(
+            records[0].snow_depth_validation,
+            Some(crate::models::weather::PhysicalValidity::Valid)
+        );
+        assert_eq!(records[0].pressure, None);
+        assert_eq!(records[0].temp_avg_souid, Some(101));
+        assert_eq!(records[0].snow_depth_souid, Some(4242));
+        assert_eq!(records[0].temp_min_souid, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_reasons_decoding() -> Result<()> {
+        let date = NaiveDate::from_ymd_opt(2023, 7, 15).unwrap();
+        let mut record = WeatherRecord::builder()
+            .station_id(12345)
+            .station_name("Test Station".to_string())
+            .date(date)
+            .coordinates(51.5074, -0.1278)
+            .build()?;
+        record.set_missing_reason("temp_avg", MissingReason::Flag9);
+        record.set_missing_reason("precipitation", MissingReason::Sentinel);
+
+        let reasons = encode_missing_reasons(&[&record])?;
+        assert_eq!(decode_missing_reasons(&reasons, 0)?, record.missing_reasons);
+
+        // Names from a newer writer are an error, not silently dropped
+        let mut builder = MapBuilder::new(None, StringBuilder::new(), StringBuilder::new());
+        builder.keys().append_value("temp_avg");
+        builder.values().append_value("Flagged");
+        builder.append(true)?;
+        let err = decode_missing_reasons(&builder.finish(), 0).unwrap_err();
+        assert!(err.to_string().contains("Flagged"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_sources() -> Result<()> {
+        let writer = ParquetWriter::new();
+        let temp_file = NamedTempFile::new().unwrap();
+
+        let source = SourceMetadata {
+            staid: 257,
+            souid: 100,
+            name: "CET CENTRAL ENGLAND".to_string(),
+            country: "GB".to_string(),
+            latitude: 52.42,
+            longitude: -1.83,
+            elevation: None,
+            element_id: "TG1".to_string(),
+            start_date: NaiveDate::from_ymd_opt(1878, 1, 1).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2023, 12, 31).unwrap(),
+            participant_id: Some(500),
+            participant_name: "Met Office".to_string(),
+        };
+
+        writer.write_sources(&[source], temp_file.path())?;
+
+        let info = writer.get_file_info(temp_file.path())?;
+        assert_eq!(info.total_rows, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_weather_stream_writer() -> Result<()> {
+        let writer = ParquetWriter::new().with_row_group_size(3);
+        let temp_file = NamedTempFile::new().unwrap();
+
+        let mut stream = writer.create_weather_stream(temp_file.path(), 2)?;
+        for station_id in 1..=3 {
+            let records: Vec<WeatherRecord> = (1..=3)
+                .map(|day| {
+                    WeatherRecord::new(
+                        station_id,
+                        format!("Station {}", station_id),
+                        NaiveDate::from_ymd_opt(2023, 1, day).unwrap(),
+                        51.5,
+                        -0.1,
+                        None,
+                        None,
+                        Some(5.0),
+                        None,
+                        None,
+                        Some("0".to_string()),
+                        None,
+                        None,
+                    )
+                })
+                .collect();
+            stream.write_records(records)?;
+        }
+        assert_eq!(stream.finish()?, 9);
+
+        let info = writer.get_file_info(temp_file.path())?;
+        assert_eq!(info.total_rows, 9);
+        assert_eq!(info.row_groups, 3);
+
+        let records = writer.read_sample_weather_records(temp_file.path(), 100)?;
+        assert_eq!(records.len(), 9);
+        assert_eq!(records[8].station_id, 3);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,248 @@
+use crate::error::{ProcessingError, Result};
+use crate::models::WeatherRecord;
+use arrow::array::{new_null_array, ArrayRef, StringArray, UInt32Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use chrono::Datelike;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Directory value used by Hive-style engines for a null partition value
+pub const HIVE_DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// `key=value` pairs taken from a part file's directory path
+pub type PartitionValues = Vec<(String, String)>;
+
+/// A column a directory dataset can be partitioned on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionKey {
+    Country,
+    Year,
+    Station,
+}
+
+impl PartitionKey {
+    /// Column name used in the `key=value` directory segment
+    pub fn column_name(&self) -> &'static str {
+        match self {
+            PartitionKey::Country => "country",
+            PartitionKey::Year => "year",
+            PartitionKey::Station => "station_id",
+        }
+    }
+
+    /// Whether the key is a stored column that is moved into the directory path
+    pub fn replaces_column(&self) -> bool {
+        !matches!(self, PartitionKey::Year)
+    }
+
+    pub fn value(&self, record: &WeatherRecord) -> String {
+        match self {
+            PartitionKey::Country => record
+                .country
+                .clone()
+                .unwrap_or_else(|| HIVE_DEFAULT_PARTITION.to_string()),
+            PartitionKey::Year => record.date.year().to_string(),
+            PartitionKey::Station => record.station_id.to_string(),
+        }
+    }
+
+    /// Parse a comma-separated key list, e.g. `country,year`
+    pub fn parse_list(keys: &str) -> Result<Vec<PartitionKey>> {
+        let keys = keys
+            .split(',')
+            .map(|key| key.parse())
+            .collect::<Result<Vec<PartitionKey>>>()?;
+
+        for (idx, key) in keys.iter().enumerate() {
+            if keys[..idx].contains(key) {
+                return Err(ProcessingError::Config(format!(
+                    "Duplicate partition key: {}",
+                    key
+                )));
+            }
+        }
+
+        Ok(keys)
+    }
+}
+
+impl FromStr for PartitionKey {
+    type Err = ProcessingError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "country" => Ok(PartitionKey::Country),
+            "year" => Ok(PartitionKey::Year),
+            "station" | "station_id" => Ok(PartitionKey::Station),
+            other => Err(ProcessingError::Config(format!(
+                "Unsupported partition key: {} (expected country, year or station)",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for PartitionKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.column_name())
+    }
+}
+
+/// Relative directory for a record, e.g. `country=GB/year=1990`
+pub fn partition_dir(keys: &[PartitionKey], record: &WeatherRecord) -> PathBuf {
+    keys.iter()
+        .map(|key| format!("{}={}", key.column_name(), key.value(record)))
+        .collect()
+}
+
+/// List the Parquet files of a dataset with the `key=value` pairs from their
+/// directory path. A plain file is returned on its own with no partitions.
+pub fn dataset_files(path: &Path) -> Result<Vec<(PathBuf, PartitionValues)>> {
+    if !path.is_dir() {
+        return Ok(vec![(path.to_path_buf(), Vec::new())]);
+    }
+
+    let mut files = Vec::new();
+    collect_parquet_files(path, &mut Vec::new(), &mut files)?;
+
+    if files.is_empty() {
+        return Err(ProcessingError::InvalidFormat(format!(
+            "No Parquet files found in dataset directory: {}",
+            path.display()
+        )));
+    }
+
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(files)
+}
+
+fn collect_parquet_files(
+    dir: &Path,
+    partitions: &mut PartitionValues,
+    files: &mut Vec<(PathBuf, PartitionValues)>,
+) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        // Skip hidden and in-progress files such as `.part-0.parquet` or `_SUCCESS`
+        if name.starts_with('.') || name.starts_with('_') {
+            continue;
+        }
+
+        if path.is_dir() {
+            let Some((key, value)) = name.split_once('=') else {
+                continue;
+            };
+            partitions.push((key.to_string(), value.to_string()));
+            collect_parquet_files(&path, partitions, files)?;
+            partitions.pop();
+        } else if path.extension().is_some_and(|ext| ext == "parquet") {
+            files.push((path, partitions.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Add partition values from the directory path back as columns, unless the
+/// file already stores them
+pub fn with_partition_columns(
+    batch: RecordBatch,
+    partitions: &[(String, String)],
+) -> Result<RecordBatch> {
+    let schema = batch.schema();
+    let mut fields: Vec<Field> = schema.fields().iter().map(|f| f.as_ref().clone()).collect();
+    let mut columns: Vec<ArrayRef> = batch.columns().to_vec();
+    let rows = batch.num_rows();
+
+    for (key, value) in partitions {
+        if schema.index_of(key).is_ok() {
+            continue;
+        }
+
+        let column: ArrayRef = if key == "station_id" {
+            let id = value.parse::<u32>().map_err(|_| {
+                ProcessingError::InvalidFormat(format!("Invalid station_id partition: {}", value))
+            })?;
+            Arc::new(UInt32Array::from(vec![id; rows]))
+        } else if value == HIVE_DEFAULT_PARTITION {
+            new_null_array(&DataType::Utf8, rows)
+        } else {
+            Arc::new(StringArray::from(vec![value.as_str(); rows]))
+        };
+
+        fields.push(Field::new(
+            key,
+            column.data_type().clone(),
+            key != "station_id",
+        ));
+        columns.push(column);
+    }
+
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_parse_partition_keys() {
+        assert_eq!(
+            PartitionKey::parse_list("country, year").unwrap(),
+            vec![PartitionKey::Country, PartitionKey::Year]
+        );
+        assert_eq!(
+            PartitionKey::parse_list("station").unwrap(),
+            vec![PartitionKey::Station]
+        );
+        assert!(PartitionKey::parse_list("month").is_err());
+        assert!(PartitionKey::parse_list("year,year").is_err());
+    }
+
+    #[test]
+    fn test_partition_dir() {
+        let mut record = WeatherRecord::new(
+            257,
+            "Test Station".to_string(),
+            NaiveDate::from_ymd_opt(1990, 6, 1).unwrap(),
+            52.4,
+            -1.8,
+            Some(10.0),
+            None,
+            None,
+            None,
+            None,
+            Some("0".to_string()),
+            None,
+            None,
+        );
+
+        let keys = [PartitionKey::Country, PartitionKey::Year];
+        assert_eq!(
+            partition_dir(&keys, &record),
+            PathBuf::from("country=__HIVE_DEFAULT_PARTITION__/year=1990")
+        );
+
+        record.country = Some("GB".to_string());
+        assert_eq!(
+            partition_dir(&keys, &record),
+            PathBuf::from("country=GB/year=1990")
+        );
+        assert_eq!(
+            partition_dir(&[PartitionKey::Station], &record),
+            PathBuf::from("station_id=257")
+        );
+    }
+}
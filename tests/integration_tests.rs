@@ -1,8 +1,112 @@
-use chrono::NaiveDate;
-use ecad_processor::models::{ConsolidatedRecord, StationMetadata};
-use ecad_processor::writers::ParquetWriter;
+use chrono::{Datelike, NaiveDate};
+use clap::Parser;
+use ecad_processor::analyzers::{
+    BasePeriod, BreakpointDetector, CellElevations, ClimateIndexCalculator, GridSpec, Gridder,
+    IndexPeriod, NormalsCalculator, TemporalAggregator,
+};
+use ecad_processor::archive::{ArchiveProcessor, WeatherMetric, ELEMENTS};
+use ecad_processor::cli::args::{Cli, Commands};
+use ecad_processor::cli::run;
+use ecad_processor::error::ProcessingError;
+use ecad_processor::models::weather::{
+    InfillMethod, MissingReason, PhysicalValidity, SpatialConsistency,
+};
+use ecad_processor::models::{
+    BoundingBox, ConsolidatedRecord, DateFilter, SourceMetadata, StationMetadata, StationSelection,
+    ValidationRules,
+};
+use ecad_processor::pipeline::{CancellationToken, Pipeline, PipelineProgress};
+use ecad_processor::processors::{
+    CompletenessChecker, DatasetUpdater, Infiller, IntegrityChecker, SpatialQualityControl,
+    TemporalQualityControl, EXIT_CONDITION_CODE,
+};
+use ecad_processor::writers::{
+    write_breakpoints, write_climate_indices, write_climate_normals, write_completeness_report,
+    write_period_aggregates, write_weather_records_with_anomalies, GridStreamWriter, ParquetWriter,
+    PartitionKey, SchemaType,
+};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tempfile::TempDir;
 use validator::Validate;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+const CET: &str = "257, CET CENTRAL ENGLAND                     , GB,+52:25:12,-001:49:48,   78";
+const ARMAGH: &str = "258, ARMAGH                                  , GB,+54:21:10,-006:38:57,   62";
+
+/// Builder for ECA&D-style zip archives
+#[derive(Default)]
+struct TestArchive {
+    stations: Vec<&'static str>,
+    files: Vec<(String, String)>,
+}
+
+impl TestArchive {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a stations.txt row
+    fn station(mut self, row: &'static str) -> Self {
+        self.stations.push(row);
+        self
+    }
+
+    /// Add a data file; `lines` follow the 20 line header every data file
+    /// carries before the first value
+    fn data_file(self, name: impl Into<String>, lines: &str) -> Self {
+        let header: String = (0..20).map(|i| format!("Header line {}\n", i)).collect();
+        self.file(name, header + lines)
+    }
+
+    /// Add a file with exactly these contents, e.g. sources.txt
+    fn file(mut self, name: impl Into<String>, contents: impl Into<String>) -> Self {
+        self.files.push((name.into(), contents.into()));
+        self
+    }
+
+    fn write(self, path: &Path) -> PathBuf {
+        let mut zip = ZipWriter::new(std::fs::File::create(path).unwrap());
+        let options = FileOptions::default();
+
+        let mut stations =
+            "STAID, STANAME                                 , CN, LAT    , LON     , HGHT\n"
+                .to_string();
+        for row in &self.stations {
+            stations.push_str(row);
+            stations.push('\n');
+        }
+        zip.start_file("stations.txt", options).unwrap();
+        zip.write_all(stations.as_bytes()).unwrap();
+
+        for (name, contents) in &self.files {
+            zip.start_file(name.as_str(), options).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+        path.to_path_buf()
+    }
+}
+
+/// Build a small ECA&D-style archive: two stations, TG for both, RR for one
+fn create_test_archive(dir: &Path) -> PathBuf {
+    TestArchive::new()
+        .station(CET)
+        .station(ARMAGH)
+        .data_file(
+            "TG_STAID000257.txt",
+            "  101,20230101,  55,    0\n  101,20230102,  61,    0\n",
+        )
+        .data_file("TG_STAID000258.txt", "  201,20230101,  42,    0\n")
+        .data_file(
+            "RR_STAID000257.txt",
+            "  102,20230101,  25,    0\n  102,20230103,  10,    1\n",
+        )
+        .write(&dir.join("ECA_test.zip"))
+}
 
 #[tokio::test]
 async fn test_cli_integration() {
@@ -53,3 +157,1206 @@ fn test_station_metadata() {
     assert!(station.is_within_uk_bounds());
     assert!(station.validate().is_ok());
 }
+
+#[tokio::test]
+async fn test_streaming_matches_in_memory_processing() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    let processor = ArchiveProcessor::from_zip(&archive).await.unwrap();
+    let (records, report) = processor.process_data(&archive).await.unwrap();
+
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let streamed = Pipeline::new()
+        .with_archive(&archive)
+        .with_record_sink({
+            let batches = Arc::clone(&batches);
+            move |station_records| {
+                batches.lock().unwrap().push(station_records);
+                Ok(())
+            }
+        })
+        .run()
+        .await
+        .unwrap();
+    let streamed_report = streamed.integrity;
+    let batches = std::mem::take(&mut *batches.lock().unwrap());
+
+    // One batch per station, each holding only that station's records
+    assert_eq!(batches.len(), 2);
+    assert!(batches[0].iter().all(|r| r.station_id == 257));
+    assert_eq!(batches[0].len(), 3);
+    assert_eq!(batches[1].len(), 1);
+
+    assert_eq!(records.len(), 4);
+    assert_eq!(streamed_report.total_records, report.total_records);
+    assert_eq!(streamed_report.suspect_records, report.suspect_records);
+
+    // Metrics from separate files are merged onto the same record
+    let merged = batches[0]
+        .iter()
+        .find(|r| r.date == NaiveDate::from_ymd_opt(2023, 1, 1).unwrap())
+        .unwrap();
+    assert_eq!(merged.temp_avg, Some(5.5));
+    assert_eq!(merged.precipitation, Some(2.5));
+    assert_eq!(merged.temp_avg_souid, Some(101));
+    assert_eq!(merged.precip_souid, Some(102));
+}
+
+#[tokio::test]
+async fn test_data_entries_read_without_extraction() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    let mut processor = ArchiveProcessor::from_zip(&archive).await.unwrap();
+    let index = processor.index_archive(&archive).unwrap();
+    assert_eq!(index.station_files[&257].len(), 2);
+
+    let mut weather_data = HashMap::new();
+    processor
+        .load_station(&index, 257, &mut weather_data)
+        .unwrap();
+    assert_eq!(weather_data.len(), 3);
+
+    // Only metadata is extracted; data files are parsed from the zip itself
+    let extracted: Vec<_> = std::fs::read_dir(processor.temp_dir_path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert!(extracted
+        .iter()
+        .all(|name| !name.to_string_lossy().contains("_STAID")));
+}
+
+#[tokio::test]
+async fn test_update_dataset_from_newer_release() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    let processor = ArchiveProcessor::from_zip(&archive).await.unwrap();
+    let (records, _) = processor.process_data(&archive).await.unwrap();
+    let dataset = temp_dir.path().join("weather.parquet");
+    let writer = ParquetWriter::new();
+    writer
+        .write_weather_records_batched(&records, &dataset, 1000)
+        .unwrap();
+
+    // A newer release revises one value, re-flags another and adds a day
+    let release = TestArchive::new()
+        .station(CET)
+        .data_file(
+            "TG_STAID000257.txt",
+            "  101,20230101,  57,    0\n  101,20230102,  61,    1\n  101,20230103,  40,    0\n",
+        )
+        .write(&temp_dir.path().join("ECA_release.zip"));
+
+    let processor = ArchiveProcessor::from_zip(&release).await.unwrap();
+    let (incoming, _) = processor.process_data(&release).await.unwrap();
+
+    let existing = writer.read_weather_records(&dataset).unwrap();
+    assert_eq!(existing.len(), 4);
+
+    let (updated, changelog) = DatasetUpdater::new().apply(existing, incoming).unwrap();
+
+    assert_eq!(updated.len(), 4);
+    assert_eq!(changelog.value_revisions, 2); // 2023-01-01 value, 2023-01-03 temperature added
+    assert_eq!(changelog.quality_changes, 1);
+    assert_eq!(changelog.new_records, 0);
+
+    // Station 258 is not in the release and is carried over untouched
+    assert!(updated.iter().any(|r| r.station_id == 258));
+
+    let day_three = updated
+        .iter()
+        .find(|r| r.station_id == 257 && r.date == NaiveDate::from_ymd_opt(2023, 1, 3).unwrap())
+        .unwrap();
+    assert_eq!(day_three.temp_avg, Some(4.0));
+    assert_eq!(day_three.precipitation, Some(1.0));
+
+    // The command also refreshes the sources table next to the dataset
+    let source = |staid: u32, souid: u32| SourceMetadata {
+        staid,
+        souid,
+        name: "TEST".to_string(),
+        country: "GB".to_string(),
+        latitude: 52.42,
+        longitude: -1.83,
+        elevation: Some(78),
+        element_id: "TG1".to_string(),
+        start_date: NaiveDate::from_ymd_opt(1878, 1, 1).unwrap(),
+        end_date: NaiveDate::from_ymd_opt(2022, 12, 31).unwrap(),
+        participant_id: None,
+        participant_name: "Met Office".to_string(),
+    };
+    writer
+        .write_sources(
+            &[source(257, 101), source(258, 201)],
+            &temp_dir.path().join("weather_sources.parquet"),
+        )
+        .unwrap();
+    TestArchive::new()
+        .station(CET)
+        .file(
+            "sources.txt",
+            "STAID, SOUID,SOUNAME                                 ,CN, LAT    , LON     ,HGHT,ELEI,START   ,STOP    ,PARID,PARNAME\n\
+             257,  101,CET CENTRAL ENGLAND                     ,GB,+52:25:12,-001:49:48,  78,TG1 ,18780101,20231231,  500,Met Office\n\
+             257,  103,CET CENTRAL ENGLAND                     ,GB,+52:25:12,-001:49:48,  78,TX1 ,20230101,20231231,  500,Met Office\n",
+        )
+        .data_file("TG_STAID000257.txt", "  101,20230101,  57,    0\n")
+        .write(&release);
+
+    let update = |dataset: &Path| {
+        Cli::try_parse_from([
+            "ecad-processor",
+            "update",
+            "--dataset",
+            dataset.to_str().unwrap(),
+            "--input-archive",
+            release.to_str().unwrap(),
+        ])
+        .unwrap()
+    };
+    run(update(&dataset)).await.unwrap();
+    let sources = writer
+        .read_sources(&temp_dir.path().join("weather_sources.parquet"))
+        .unwrap();
+    let souids: Vec<u32> = sources.iter().map(|s| s.souid).collect();
+    assert_eq!(souids, vec![101, 103, 201]);
+    let revised = sources.iter().find(|s| s.souid == 101).unwrap();
+    assert_eq!(
+        revised.end_date,
+        NaiveDate::from_ymd_opt(2023, 12, 31).unwrap()
+    );
+
+    // A partitioned dataset cannot be replaced by a single file
+    let partitioned = temp_dir.path().join("partitioned");
+    writer
+        .write_weather_records_partitioned(
+            &records,
+            &partitioned,
+            &PartitionKey::parse_list("year").unwrap(),
+            1000,
+        )
+        .unwrap();
+    let err = run(update(&partitioned)).await.unwrap_err();
+    assert!(err.to_string().contains("--output-file"));
+}
+
+#[tokio::test]
+async fn test_partitioned_dataset_round_trip() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    let processor = ArchiveProcessor::from_zip(&archive).await.unwrap();
+    let (records, _) = processor.process_data(&archive).await.unwrap();
+    assert!(records.iter().all(|r| r.country.as_deref() == Some("GB")));
+
+    let dataset = temp_dir.path().join("weather");
+    let writer = ParquetWriter::new();
+    let keys = PartitionKey::parse_list("country,year").unwrap();
+    let part_files = writer
+        .write_weather_records_partitioned(&records, &dataset, &keys, 1000)
+        .unwrap();
+
+    assert_eq!(
+        part_files,
+        vec![dataset.join("country=GB/year=2023/part-0.parquet")]
+    );
+
+    // Partition columns are restored from the directory path when reading
+    let read_back = writer.read_weather_records(&dataset).unwrap();
+    assert_eq!(read_back.len(), records.len());
+    assert!(read_back.iter().all(|r| r.country.as_deref() == Some("GB")));
+
+    let info = writer.get_file_info(&dataset).unwrap();
+    assert_eq!(info.total_rows, records.len() as i64);
+    assert_eq!(info.file_count, 1);
+    assert_eq!(
+        writer.detect_schema_type(&dataset).unwrap(),
+        SchemaType::WeatherRecord
+    );
+
+    // Station partitions move station_id out of the files entirely
+    let by_station = temp_dir.path().join("by_station");
+    let keys = PartitionKey::parse_list("station").unwrap();
+    writer
+        .write_weather_records_partitioned(&records, &by_station, &keys, 1000)
+        .unwrap();
+    let read_back = writer.read_weather_records(&by_station).unwrap();
+    assert_eq!(read_back.iter().filter(|r| r.station_id == 258).count(), 1);
+
+    // Refuse to mix a new dataset into an existing one
+    assert!(writer
+        .write_weather_records_partitioned(&records, &by_station, &keys, 1000)
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_climate_indices_written_to_parquet() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    let processor = ArchiveProcessor::from_zip(&archive).await.unwrap();
+    let (records, _) = processor.process_data(&archive).await.unwrap();
+
+    let values = ClimateIndexCalculator::new(IndexPeriod::Monthly).compute(&records);
+
+    // Station 257 reports mean temperature and precipitation but no TX
+    let station_indices: Vec<&str> = values
+        .iter()
+        .filter(|v| v.station_id == 257 && v.period == "01")
+        .map(|v| v.index)
+        .collect();
+    assert!(station_indices.contains(&"PRCPTOT"));
+    assert!(!station_indices.contains(&"TXx"));
+
+    // Two days of January is far too incomplete to report a value
+    assert!(values.iter().all(|v| v.value.is_none()));
+
+    let output = temp_dir.path().join("indices.parquet");
+    let writer = ParquetWriter::new();
+    write_climate_indices(&writer, &values, &output).unwrap();
+    let info = writer.get_file_info(&output).unwrap();
+    assert_eq!(info.total_rows, values.len() as i64);
+}
+
+#[tokio::test]
+async fn test_monthly_aggregates_written_to_parquet() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    let processor = ArchiveProcessor::from_zip(&archive).await.unwrap();
+    let (records, _) = processor.process_data(&archive).await.unwrap();
+
+    let aggregates = TemporalAggregator::new(IndexPeriod::Monthly).aggregate(&records);
+    assert_eq!(aggregates.len(), 2);
+
+    // Station 257 has no precipitation reported on 2023-01-02
+    let station = aggregates.iter().find(|a| a.station_id == 257).unwrap();
+    assert_eq!(station.period, "01");
+    assert_eq!(station.precipitation_days, 2);
+    assert!(station.precipitation_total.is_some());
+    assert_eq!(station.temp_max_mean, None);
+
+    let output = temp_dir.path().join("monthly.parquet");
+    let writer = ParquetWriter::new();
+    write_period_aggregates(&writer, &aggregates, &output).unwrap();
+    let info = writer.get_file_info(&output).unwrap();
+    assert_eq!(info.total_rows, 2);
+}
+
+#[tokio::test]
+async fn test_normals_and_anomaly_columns() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    let processor = ArchiveProcessor::from_zip(&archive).await.unwrap();
+    let (records, _) = processor.process_data(&archive).await.unwrap();
+
+    let table = NormalsCalculator::new(BasePeriod::new(2023, 2023).unwrap())
+        .with_granularity("day-of-year".parse().unwrap())
+        .compute(&records);
+    assert!(table
+        .normals
+        .iter()
+        .any(|n| n.station_id == 257 && n.element == "temp_avg" && n.normal.is_some()));
+
+    // Every day is its own normal within a one-year base period
+    let anomalies = table.anomalies(&records);
+    assert!(anomalies
+        .iter()
+        .all(|a| a.temp_avg_anomaly.map_or(true, |t| t.abs() < 1e-4)));
+
+    let writer = ParquetWriter::new();
+    let normals_path = temp_dir.path().join("normals.parquet");
+    write_climate_normals(&writer, &table, &normals_path).unwrap();
+    assert_eq!(
+        writer.get_file_info(&normals_path).unwrap().total_rows,
+        table.normals.len() as i64
+    );
+
+    // The anomaly file is still a readable weather dataset
+    let anomalies_path = temp_dir.path().join("anomalies.parquet");
+    write_weather_records_with_anomalies(&writer, &records, &anomalies, &anomalies_path).unwrap();
+    let read_back = writer.read_weather_records(&anomalies_path).unwrap();
+    assert_eq!(read_back.len(), records.len());
+}
+
+#[tokio::test]
+async fn test_homogeneity_breakpoints_linked_to_sources() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    let processor = ArchiveProcessor::from_zip(&archive).await.unwrap();
+    let (records, _) = processor.process_data(&archive).await.unwrap();
+    let template = records.iter().find(|r| r.station_id == 257).unwrap();
+
+    // Thirty years of daily values; the station moves to a warmer site with
+    // a new source series in 2001
+    let records: Vec<_> = NaiveDate::from_ymd_opt(1986, 1, 1)
+        .unwrap()
+        .iter_days()
+        .take_while(|d| *d < NaiveDate::from_ymd_opt(2016, 1, 1).unwrap())
+        .map(|date| {
+            let moved = date >= NaiveDate::from_ymd_opt(2001, 1, 1).unwrap();
+            let mut record = template.clone();
+            record.date = date;
+            record.temp_avg =
+                Some(9.0 + if moved { 1.5 } else { 0.0 } + (date.year() % 3) as f32 * 0.2);
+            record.temp_avg_souid = Some(if moved { 103 } else { 101 });
+            record
+        })
+        .collect();
+
+    // Sources survive a round trip through the companion table
+    let source = |souid, start, end| SourceMetadata {
+        staid: 257,
+        souid,
+        name: "CET CENTRAL ENGLAND".to_string(),
+        country: "GB".to_string(),
+        latitude: 52.42,
+        longitude: -1.83,
+        elevation: Some(78),
+        element_id: "TG1".to_string(),
+        start_date: NaiveDate::from_ymd_opt(start, 1, 1).unwrap(),
+        end_date: NaiveDate::from_ymd_opt(end, 12, 31).unwrap(),
+        participant_id: None,
+        participant_name: "Met Office".to_string(),
+    };
+    let writer = ParquetWriter::new();
+    let sources_path = temp_dir.path().join("weather_sources.parquet");
+    writer
+        .write_sources(
+            &[source(101, 1986, 2000), source(103, 2001, 2015)],
+            &sources_path,
+        )
+        .unwrap();
+    let sources = writer.read_sources(&sources_path).unwrap();
+    assert_eq!(sources.len(), 2);
+    assert_eq!(sources[0].elevation, Some(78));
+
+    let breakpoints = BreakpointDetector::new(IndexPeriod::Annual)
+        .with_sources(sources)
+        .detect(&records);
+    let snht = breakpoints
+        .iter()
+        .find(|b| b.element == "temp_avg" && b.test.to_string() == "snht")
+        .unwrap();
+    assert!(snht.significant);
+    assert_eq!(snht.break_year, 2001);
+    assert_eq!(
+        (snht.souid_before, snht.souid_after),
+        (Some(101), Some(103))
+    );
+    assert_eq!(snht.source_change_date, NaiveDate::from_ymd_opt(2001, 1, 1));
+
+    let breakpoints_path = temp_dir.path().join("weather_breakpoints.parquet");
+    write_breakpoints(&writer, &breakpoints, &breakpoints_path).unwrap();
+    assert_eq!(
+        writer.get_file_info(&breakpoints_path).unwrap().total_rows,
+        breakpoints.len() as i64
+    );
+}
+
+#[tokio::test]
+async fn test_region_validation_rules_from_file() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    // Station 257 sits at 52.4°N and 78 m, station 258 at 54.4°N and 62 m
+    let toml_path = temp_dir.path().join("rules.toml");
+    std::fs::write(
+        &toml_path,
+        "[[regions]]\nname = \"Midlands\"\ncountries = [\"gb\"]\nmax_latitude = 53.0\n\
+         [regions.temperature]\nsuspect_max = 5.0\n",
+    )
+    .unwrap();
+    let yaml_path = temp_dir.path().join("rules.yaml");
+    std::fs::write(
+        &yaml_path,
+        "regions:\n  - min_elevation: 70\n    temperature:\n      suspect_max: 5.8\n      max: 5.8\n",
+    )
+    .unwrap();
+
+    let validity = |records: &[ecad_processor::models::WeatherRecord], station, day| {
+        records
+            .iter()
+            .find(|r| {
+                r.station_id == station && r.date == NaiveDate::from_ymd_opt(2023, 1, day).unwrap()
+            })
+            .and_then(|r| r.temp_validation)
+    };
+
+    let processor = ArchiveProcessor::from_zip(&archive).await.unwrap();
+    let (records, _) = processor.process_data(&archive).await.unwrap();
+    assert_eq!(validity(&records, 257, 1), Some(PhysicalValidity::Valid));
+
+    let rules = ValidationRules::from_file(&toml_path).unwrap();
+    let processor = ArchiveProcessor::from_zip(&archive)
+        .await
+        .unwrap()
+        .with_validation_rules(std::sync::Arc::new(rules));
+    let (records, _) = processor.process_data(&archive).await.unwrap();
+    assert_eq!(validity(&records, 257, 1), Some(PhysicalValidity::Suspect));
+    assert_eq!(validity(&records, 258, 1), Some(PhysicalValidity::Valid));
+
+    let rules = ValidationRules::from_file(&yaml_path).unwrap();
+    let streamed = Arc::new(Mutex::new(Vec::new()));
+    Pipeline::new()
+        .with_archive(&archive)
+        .with_validation_rules(Arc::new(rules))
+        .with_record_sink({
+            let streamed = Arc::clone(&streamed);
+            move |records| {
+                streamed.lock().unwrap().extend(records);
+                Ok(())
+            }
+        })
+        .run()
+        .await
+        .unwrap();
+    let streamed = std::mem::take(&mut *streamed.lock().unwrap());
+    assert_eq!(validity(&streamed, 257, 1), Some(PhysicalValidity::Valid));
+    assert_eq!(validity(&streamed, 257, 2), Some(PhysicalValidity::Invalid));
+
+    assert!(ValidationRules::from_file(&temp_dir.path().join("missing.toml")).is_err());
+}
+
+#[tokio::test]
+async fn test_infilled_values_marked_and_round_tripped() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    let processor = ArchiveProcessor::from_zip(&archive).await.unwrap();
+    let (records, _) = processor.process_data(&archive).await.unwrap();
+    let template = records.iter().find(|r| r.station_id == 257).unwrap();
+
+    // Ten January days with the 4th and 5th missing
+    let records: Vec<_> = (1..=10)
+        .filter(|day| *day != 4 && *day != 5)
+        .map(|day| {
+            let mut record = template.clone();
+            record.date = NaiveDate::from_ymd_opt(2023, 1, day).unwrap();
+            record.temp_avg = Some(day as f32);
+            record
+        })
+        .collect();
+
+    let (records, summary) = Infiller::new().infill(records);
+    assert_eq!(summary.interpolated, 2);
+    assert_eq!(summary.records_added, 2);
+
+    let writer = ParquetWriter::new();
+    let path = temp_dir.path().join("infilled.parquet");
+    writer.write_weather_records(&records, &path).unwrap();
+    let read_back = writer.read_weather_records(&path).unwrap();
+    assert_eq!(read_back.len(), 10);
+
+    let fourth = read_back
+        .iter()
+        .find(|r| r.date == NaiveDate::from_ymd_opt(2023, 1, 4).unwrap())
+        .unwrap();
+    assert_eq!(fourth.temp_avg, Some(4.0));
+    assert_eq!(fourth.temp_avg_infilled, Some(InfillMethod::Interpolation));
+    assert_eq!(fourth.country.as_deref(), Some("GB"));
+    assert!(read_back
+        .iter()
+        .filter(|r| r.temp_avg_infilled.is_none())
+        .all(|r| r.date.day() != 4 && r.date.day() != 5));
+}
+
+#[tokio::test]
+async fn test_every_element_round_trips_in_output_units() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    // A plausible raw value per element, plus a missing day for each
+    let raw_values = [52, 118, 85, 34, 45, 62, 81, 10132, 6, 12, 153, 225, 140];
+    let mut archive = TestArchive::new().station(CET);
+    for (spec, raw) in ELEMENTS.iter().zip(raw_values) {
+        archive = archive.data_file(
+            format!("{}_STAID000257.txt", spec.prefix),
+            &format!(
+                "  101,20230101,{:>6},    0\n  101,20230102,{:>6},    9\n",
+                raw, spec.missing
+            ),
+        );
+    }
+    let path = archive.write(&temp_dir.path().join("ECA_all_elements.zip"));
+
+    let processor = ArchiveProcessor::from_zip(&path).await.unwrap();
+    let (records, _) = processor.process_data(&path).await.unwrap();
+
+    let output = temp_dir.path().join("all_elements.parquet");
+    let writer = ParquetWriter::new();
+    writer.write_weather_records(&records, &output).unwrap();
+    let read_back = writer.read_weather_records(&output).unwrap();
+    let units = writer.read_column_units(&output).unwrap();
+
+    let first = read_back
+        .iter()
+        .find(|r| r.date == NaiveDate::from_ymd_opt(2023, 1, 1).unwrap())
+        .unwrap();
+    let values: HashMap<_, _> = first.value_columns().into_iter().collect();
+    for (spec, raw) in ELEMENTS.iter().zip(raw_values) {
+        let expected = (raw as f64 * spec.scale_factor) as f32;
+        assert_eq!(values[spec.column], Some(expected), "{}", spec.prefix);
+        assert_eq!(units.get(spec.column).map(String::as_str), Some(spec.unit));
+    }
+
+    // The sentinel is never read as a value
+    assert!(read_back
+        .iter()
+        .filter(|r| r.date == NaiveDate::from_ymd_opt(2023, 1, 2).unwrap())
+        .all(|r| r.value_columns().iter().all(|(_, v)| v.is_none())));
+}
+
+#[tokio::test]
+async fn test_missing_observations_kept_with_reasons() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = TestArchive::new()
+        .station(CET)
+        .data_file(
+            "TG_STAID000257.txt",
+            "  101,20230101,   52,    0\n  101,20230102,-9999,    9\n  101,20230103,-9999,    0\n  101,20230104,   48,    0\n",
+        )
+        .data_file(
+            "RR_STAID000257.txt",
+            "  102,20230101,   12,    0\n  102,20230102\n  102,20230104,    0,    0\n",
+        )
+        // Station 999 is not in stations.txt
+        .data_file("TG_STAID000999.txt", "  900,20230101,   61,    0\n")
+        .write(&temp_dir.path().join("ECA_missing.zip"));
+
+    // By default only days with a value become records
+    let processor = ArchiveProcessor::from_zip(&path).await.unwrap();
+    let (records, _) = processor.process_data(&path).await.unwrap();
+    assert_eq!(records.len(), 2);
+
+    let processor = ArchiveProcessor::from_zip(&path)
+        .await
+        .unwrap()
+        .with_keep_missing(true);
+    let (records, _) = processor.process_data(&path).await.unwrap();
+
+    let output = temp_dir.path().join("missing.parquet");
+    let writer = ParquetWriter::new();
+    writer.write_weather_records(&records, &output).unwrap();
+    let mut read_back = writer.read_weather_records(&output).unwrap();
+    read_back.sort_by_key(|r| (r.station_id, r.date));
+    assert_eq!(read_back.len(), 5);
+
+    let day = |d: u32| NaiveDate::from_ymd_opt(2023, 1, d).unwrap();
+    let record = |station: u32, d: u32| {
+        read_back
+            .iter()
+            .find(|r| r.station_id == station && r.date == day(d))
+            .unwrap()
+    };
+    assert_eq!(
+        record(257, 2).missing_reason("temp_avg"),
+        Some(MissingReason::Flag9)
+    );
+    assert_eq!(
+        record(257, 2).missing_reason("precipitation"),
+        Some(MissingReason::MalformedLine)
+    );
+    assert_eq!(record(257, 2).temp_avg, None);
+    assert_eq!(
+        record(257, 3).missing_reason("temp_avg"),
+        Some(MissingReason::Sentinel)
+    );
+    // Precipitation on the 3rd was never reported, so has no reason
+    assert_eq!(record(257, 3).missing_reason("precipitation"), None);
+    assert!(record(257, 1).missing_reasons.is_empty());
+
+    let unknown = record(999, 1);
+    assert_eq!(unknown.temp_avg, None);
+    assert_eq!(unknown.country, None);
+    assert_eq!(
+        unknown.missing_reason("temp_avg"),
+        Some(MissingReason::UnknownStation)
+    );
+
+    // Completeness counts reported days against the series
+    let report = CompletenessChecker::new().check(&read_back);
+    let series = |station: u32, metric: &str| {
+        report
+            .series
+            .iter()
+            .find(|s| s.station_id == station && s.metric == metric)
+            .unwrap()
+    };
+    let tg = series(257, "temp_avg");
+    assert_eq!((tg.days_present, tg.days_reported_missing), (2, 2));
+    assert_eq!(tg.gaps[0].days_reported_missing, 2);
+    let rr = series(257, "precipitation");
+    assert_eq!((rr.days_present, rr.days_reported_missing), (2, 1));
+    assert_eq!(rr.gaps[0].days, 2);
+    assert_eq!(rr.gaps[0].days_reported_missing, 1);
+    assert_eq!(series(999, "temp_avg").completeness_percent, 0.0);
+}
+
+#[tokio::test]
+async fn test_run_report_and_exit_conditions() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+    let output = temp_dir.path().join("weather.parquet");
+    let args = |extra: &[&str]| {
+        let mut args = vec![
+            "ecad-processor",
+            "process",
+            "-i",
+            archive.to_str().unwrap(),
+            "-o",
+            output.to_str().unwrap(),
+        ];
+        args.extend_from_slice(extra);
+        Cli::try_parse_from(args).unwrap()
+    };
+
+    // A sources table left by an earlier run is not this run's output
+    std::fs::write(temp_dir.path().join("weather_sources.parquet"), b"stale").unwrap();
+
+    // The report defaults to a path next to the Parquet file
+    run(args(&[
+        "--report",
+        "--fail-on",
+        "no-records,invalid-records",
+    ]))
+    .await
+    .unwrap();
+    let report_path = temp_dir.path().join("weather_report.json");
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report["command"], "process");
+    assert_eq!(report["exit_code"], 0);
+    assert_eq!(report["inputs"][0]["sha256"].as_str().unwrap().len(), 64);
+    assert_eq!(report["archives"][0]["country"], "GB");
+    assert_eq!(report["archives"][0]["file_counts"]["temp_avg"], 2);
+    assert_eq!(report["counts"]["total_records"], 4);
+    assert_eq!(report["counts"]["metrics"]["temp_avg"], 3);
+    assert_eq!(
+        report["counts"]["stations"]["257"]["metrics"]["precipitation"],
+        2
+    );
+    assert_eq!(report["integrity"]["total_records"], 4);
+    let phases: Vec<&str> = report["timings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["phase"].as_str().unwrap())
+        .collect();
+    assert_eq!(phases, vec!["inspect", "process", "write", "total"]);
+    assert_eq!(report["outputs"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        report["outputs"][0]["path"].as_str().unwrap(),
+        output.to_str().unwrap()
+    );
+
+    // Values inside the built-in ranges count as invalid when the rules say so
+    let rules = temp_dir.path().join("rules.yaml");
+    std::fs::write(
+        &rules,
+        "regions:\n  - temperature:\n      suspect_max: 5.8\n      max: 5.8\n",
+    )
+    .unwrap();
+    let result = run(args(&[
+        "--qc-rules",
+        rules.to_str().unwrap(),
+        "--report",
+        "--fail-on",
+        "invalid-records",
+    ]))
+    .await;
+    assert!(matches!(result, Err(ProcessingError::ExitConditions(_))));
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report["counts"]["invalid_records"], 1);
+    assert_eq!(report["failed_conditions"][0], "invalid-records");
+
+    // Selecting an unknown station leaves nothing to write
+    let custom_path = temp_dir.path().join("reports/empty.json");
+    let result = run(args(&[
+        "--station-id",
+        "999",
+        "--report",
+        custom_path.to_str().unwrap(),
+        "--fail-on",
+        "warnings",
+    ]))
+    .await;
+    assert!(matches!(result, Err(ProcessingError::ExitConditions(_))));
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&custom_path).unwrap()).unwrap();
+    assert_eq!(report["exit_code"], EXIT_CONDITION_CODE);
+    assert_eq!(report["failed_conditions"][0], "warnings");
+    assert_eq!(report["warnings"][0]["code"], "no_records");
+
+    let Commands::Process { run_report, .. } = args(&["--fail-on", "typo"]).command else {
+        panic!("expected process command");
+    };
+    assert!(run_report.conditions().is_err());
+}
+
+#[test]
+fn test_json_logs_written_to_log_file() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+    let output = temp_dir.path().join("weather.parquet");
+    let log_file = temp_dir.path().join("run.log");
+
+    // The subscriber is process-wide, so run the binary rather than `run`
+    let result = std::process::Command::new(env!("CARGO_BIN_EXE_ecad-processor"))
+        .env_remove("RUST_LOG")
+        .args(["--verbose", "--log-format", "json", "--log-file"])
+        .arg(&log_file)
+        .args(["process", "-i"])
+        .arg(&archive)
+        .arg("-o")
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+    // Library events go to the log, leaving stdout to the command's summary
+    assert!(!String::from_utf8_lossy(&result.stdout).contains("Loaded station metadata"));
+
+    let lines: Vec<serde_json::Value> = std::fs::read_to_string(&log_file)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let loaded = lines
+        .iter()
+        .find(|l| l["fields"]["message"] == "Loaded station metadata")
+        .unwrap();
+    assert_eq!(loaded["level"], "INFO");
+    assert_eq!(loaded["fields"]["stations"], 2);
+    assert_eq!(loaded["span"]["name"], "archive");
+    assert_eq!(loaded["span"]["path"], archive.to_str().unwrap());
+
+    // Verbose logging reaches per-station spans inside each archive
+    let station_event = lines
+        .iter()
+        .find(|l| l["span"]["name"] == "station" && l["span"]["station_id"] == 257)
+        .unwrap();
+    assert_eq!(station_event["level"], "DEBUG");
+    assert_eq!(station_event["spans"][0]["name"], "archive");
+}
+
+#[tokio::test]
+async fn test_pipeline_sinks_progress_and_cancellation() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+    let output = temp_dir.path().join("out/weather.parquet");
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let collected = Arc::new(Mutex::new(Vec::new()));
+    let pipeline = {
+        let events = Arc::clone(&events);
+        let collected = Arc::clone(&collected);
+        Pipeline::new()
+            .with_archive(&archive)
+            .with_temporal_qc(TemporalQualityControl::new())
+            .with_parquet_output(&output, ParquetWriter::new())
+            .with_record_sink(move |records| {
+                collected.lock().unwrap().extend(records);
+                Ok(())
+            })
+            .with_progress(move |progress| events.lock().unwrap().push(progress.clone()))
+    };
+
+    // The run is Send, so services can spawn it
+    let result = tokio::spawn(pipeline.run()).await.unwrap().unwrap();
+    assert_eq!(result.counts.total_records, 4);
+    assert_eq!(result.integrity.temporal_qc.records_checked, 4);
+    assert_eq!(collected.lock().unwrap().len(), 4);
+    assert_eq!(result.outputs, vec![output.clone()]);
+    let written = ParquetWriter::new().read_weather_records(&output).unwrap();
+    assert_eq!(written.len(), 4);
+
+    let events = std::mem::take(&mut *events.lock().unwrap());
+    assert_eq!(
+        events[0],
+        PipelineProgress::ArchiveIndexed {
+            path: archive.clone(),
+            stations: 2
+        }
+    );
+    assert!(matches!(
+        events[2],
+        PipelineProgress::StationProcessed {
+            station_id: 258,
+            records: 1,
+            completed: 2,
+            total: 2
+        }
+    ));
+    assert_eq!(
+        events[3],
+        PipelineProgress::OutputWritten {
+            path: output.clone(),
+            records: 4
+        }
+    );
+
+    // Only the selected elements are read
+    let precipitation_only = Pipeline::new()
+        .with_archive(&archive)
+        .with_metrics(vec![WeatherMetric::Precipitation])
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(precipitation_only.counts.total_records, 2);
+    assert_eq!(
+        precipitation_only.counts.metrics.keys().collect::<Vec<_>>(),
+        vec![&"precipitation"]
+    );
+
+    // Cancelling mid-run stops before the next station and removes the output
+    let cancelled_output = temp_dir.path().join("cancelled.parquet");
+    let token = CancellationToken::new();
+    let handle = token.clone();
+    let result = Pipeline::new()
+        .with_archive(&archive)
+        .with_parquet_output(&cancelled_output, ParquetWriter::new())
+        .with_progress(move |progress| {
+            if matches!(progress, PipelineProgress::StationProcessed { .. }) {
+                handle.cancel();
+            }
+        })
+        .with_cancellation(token)
+        .run()
+        .await;
+    assert!(matches!(result, Err(ProcessingError::Cancelled)));
+    assert!(!cancelled_output.exists());
+}
+
+#[tokio::test]
+async fn test_pipeline_sink_finish_failure_removes_outputs() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+    let first = temp_dir.path().join("first.parquet");
+    let dataset = temp_dir.path().join("dataset");
+    let last = temp_dir.path().join("last.parquet");
+
+    // A file takes the dataset path once the run is under way, so the
+    // partitioned sink fails when it is finished
+    let blocker = dataset.clone();
+    let result = Pipeline::new()
+        .with_archive(&archive)
+        .with_parquet_output(&first, ParquetWriter::new())
+        .with_partitioned_output(&dataset, vec![PartitionKey::Year], ParquetWriter::new())
+        .with_parquet_output(&last, ParquetWriter::new())
+        .with_record_sink(move |_| {
+            std::fs::write(&blocker, "")?;
+            Ok(())
+        })
+        .run()
+        .await;
+
+    assert!(result.is_err());
+    assert!(!first.exists(), "finished output removed");
+    assert!(!last.exists(), "unfinished output removed");
+    assert!(dataset.is_file(), "files the run didn't write are kept");
+}
+
+#[tokio::test]
+async fn test_gridded_values_with_lapse_rate() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    let processor = ArchiveProcessor::from_zip(&archive).await.unwrap();
+    let (records, _) = processor.process_data(&archive).await.unwrap();
+
+    // One-degree cells; the cell holding CET (52.42N 1.83W, 78 m) is 578 m high
+    let grid = GridSpec::from_bbox(&"52,-7,55,-1".parse().unwrap(), 1.0).unwrap();
+    let elevation_file = temp_dir.path().join("elevation.csv");
+    std::fs::write(
+        &elevation_file,
+        "latitude,longitude,elevation\n52.5,-1.5,578\n52.5,-2.5,100\n",
+    )
+    .unwrap();
+    let elevations = CellElevations::from_csv(&elevation_file, &grid).unwrap();
+    assert_eq!(elevations.known(), 2);
+
+    let gridder = Gridder::new(grid).with_lapse_rate(6.5, elevations).unwrap();
+    let output = temp_dir.path().join("grid.parquet");
+    let writer = ParquetWriter::new();
+    let mut stream = GridStreamWriter::create(&writer, &output).unwrap();
+    let mut cet_cell = Vec::new();
+    let summary = gridder
+        .grid(&records, |values| {
+            cet_cell.extend(
+                values
+                    .iter()
+                    .filter(|v| (v.latitude, v.longitude) == (52.5, -1.5))
+                    .cloned(),
+            );
+            stream.write_values(values)
+        })
+        .unwrap();
+    let written = stream.finish().unwrap();
+
+    // Only cells with an elevation get a lapse-adjusted temperature, and
+    // the precipitation-only 3rd of January has no temperatures at all
+    assert_eq!(summary.days, 3);
+    assert_eq!(written, 4);
+    assert_eq!(writer.get_file_info(&output).unwrap().total_rows, 4);
+    assert_eq!(cet_cell.len(), 2);
+    assert_eq!(cet_cell[0].station_count, 1);
+    // 5.5°C at 78 m is 2.25°C at 578 m
+    assert!((cet_cell[0].value - 2.25).abs() < 1e-6);
+
+    let cli = Cli::try_parse_from([
+        "ecad-processor",
+        "grid",
+        "--input",
+        "weather.parquet",
+        "--lapse-rate",
+        "6.5",
+    ]);
+    assert!(cli.is_err(), "--lapse-rate requires --elevation-file");
+
+    // The grid extent takes the same corner order as station selection
+    let cli = Cli::try_parse_from([
+        "ecad-processor",
+        "grid",
+        "--input",
+        "weather.parquet",
+        "--bbox",
+        "-54,-8,55,-5",
+    ])
+    .unwrap();
+    let Commands::Grid { bbox, .. } = cli.command else {
+        panic!("expected grid command");
+    };
+    let bbox: BoundingBox = bbox.unwrap().parse().unwrap();
+    let grid = GridSpec::from_bbox(&bbox, 1.0).unwrap();
+    assert_eq!((grid.min_latitude, grid.max_latitude), (-54.0, 55.0));
+    assert_eq!((grid.min_longitude, grid.max_longitude), (-8.0, -5.0));
+}
+
+#[tokio::test]
+async fn test_spatial_qc_columns_round_trip() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    let processor = ArchiveProcessor::from_zip(&archive).await.unwrap();
+    let (mut records, _) = processor.process_data(&archive).await.unwrap();
+
+    // Elevation is carried over from stations.txt
+    let armagh = records.iter().find(|r| r.station_id == 258).unwrap();
+    assert_eq!(armagh.elevation, Some(62));
+
+    // Two stations are too few to estimate either from the other
+    let summary = SpatialQualityControl::new()
+        .with_max_distance_km(500.0)
+        .check(&mut records);
+    assert_eq!(summary.stations_with_neighbours, 2);
+    assert_eq!(summary.values_scored, 0);
+
+    // Add three close neighbours of station 258 reporting a far colder day
+    let template = records
+        .iter()
+        .find(|r| r.station_id == 258)
+        .unwrap()
+        .clone();
+    for (offset, station_id) in [(0.1, 901), (-0.1, 902), (0.2, 903)] {
+        let mut neighbour = template.clone();
+        neighbour.station_id = station_id;
+        neighbour.latitude += offset;
+        neighbour.temp_avg = Some(-10.0);
+        records.push(neighbour);
+    }
+    SpatialQualityControl::new().check(&mut records);
+
+    let output = temp_dir.path().join("spatial.parquet");
+    let writer = ParquetWriter::new();
+    writer.write_weather_records(&records, &output).unwrap();
+    let read_back = writer.read_weather_records(&output).unwrap();
+    let armagh = read_back.iter().find(|r| r.station_id == 258).unwrap();
+    assert_eq!(armagh.elevation, Some(62));
+    assert!(armagh.temp_spatial_score.unwrap() > 5.0);
+    assert_eq!(armagh.temp_spatial, Some(SpatialConsistency::Outlier));
+}
+
+#[tokio::test]
+async fn test_temporal_qc_flags_and_report() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    let processor = ArchiveProcessor::from_zip(&archive).await.unwrap();
+    let (mut records, mut report) = processor.process_data(&archive).await.unwrap();
+
+    // Station 257's mean temperature leaps from 6.1°C to 31°C on 2023-01-03
+    let mut jump = records
+        .iter()
+        .find(|r| r.station_id == 257 && r.date == NaiveDate::from_ymd_opt(2023, 1, 2).unwrap())
+        .unwrap()
+        .clone();
+    jump.date = NaiveDate::from_ymd_opt(2023, 1, 3).unwrap();
+    jump.temp_avg = Some(31.0);
+    records.retain(|r| !(r.station_id == 257 && r.date == jump.date));
+    records.push(jump);
+
+    report.temporal_qc = TemporalQualityControl::new().check(&mut records);
+    assert_eq!(report.temporal_qc.records_checked, records.len());
+    assert_eq!(report.temporal_qc.spikes, 1);
+    assert!(IntegrityChecker::new()
+        .generate_summary(&report)
+        .contains("Day-to-day spikes: 1"));
+
+    let output = temp_dir.path().join("temporal.parquet");
+    let writer = ParquetWriter::new();
+    writer.write_weather_records(&records, &output).unwrap();
+    let read_back = writer.read_weather_records(&output).unwrap();
+    assert_eq!(
+        read_back
+            .iter()
+            .filter(|r| r.qc_spike == Some(true))
+            .count(),
+        1
+    );
+    assert!(read_back.iter().all(|r| r.qc_stuck == Some(false)));
+}
+
+#[tokio::test]
+async fn test_completeness_report_and_threshold() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    let processor = ArchiveProcessor::from_zip(&archive).await.unwrap();
+    let (records, _) = processor.process_data(&archive).await.unwrap();
+
+    let report = CompletenessChecker::new().check(&records);
+
+    // Station 257 precipitation is missing 2023-01-02
+    let rr = report
+        .series
+        .iter()
+        .find(|s| s.station_id == 257 && s.metric == "precipitation")
+        .unwrap();
+    assert_eq!(rr.days_present, 2);
+    assert_eq!(rr.days_expected, 3);
+    assert_eq!(rr.longest_gap_days, 1);
+    assert_eq!(
+        rr.gaps[0].start,
+        NaiveDate::from_ymd_opt(2023, 1, 2).unwrap()
+    );
+
+    // Mean temperature for both stations has no gaps
+    assert!(report.stations_below(100.0).is_empty());
+    assert_eq!(report.station_completeness(257), Some(100.0));
+
+    let writer = ParquetWriter::new();
+    let parquet_path = temp_dir.path().join("completeness.parquet");
+    write_completeness_report(&writer, &report, &parquet_path).unwrap();
+    let info = writer.get_file_info(&parquet_path).unwrap();
+    assert_eq!(info.total_rows, report.series.len() as i64);
+
+    let json_path = temp_dir.path().join("completeness.json");
+    report.write_json(&json_path).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+    assert_eq!(
+        json["series"].as_array().unwrap().len(),
+        report.series.len()
+    );
+}
+
+#[tokio::test]
+async fn test_station_selection_before_reading() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    // Armagh lies west of 5°W; Central England does not
+    let selection = StationSelection::new()
+        .with_countries(["GB"])
+        .with_bounding_box("54,-8,55,-5".parse().unwrap());
+
+    let mut processor = ArchiveProcessor::from_zip(&archive)
+        .await
+        .unwrap()
+        .with_station_selection(selection.clone());
+    let index = processor.index_archive(&archive).unwrap();
+    assert_eq!(
+        index.station_files.keys().copied().collect::<Vec<_>>(),
+        [258]
+    );
+
+    let processor = ArchiveProcessor::from_zip(&archive)
+        .await
+        .unwrap()
+        .with_station_selection(selection);
+    let (records, _) = processor.process_data(&archive).await.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].station_id, 258);
+
+    // Negative coordinates parse as option values on the command line
+    let cli = Cli::try_parse_from([
+        "ecad-processor",
+        "process",
+        "-i",
+        "archive.zip",
+        "--bbox",
+        "-54,-8,55,-5",
+        "--station-id",
+        "257,258",
+    ])
+    .unwrap();
+    let Commands::Process { selection, .. } = cli.command else {
+        panic!("expected process command");
+    };
+    assert_eq!(selection.station_ids, vec![257, 258]);
+    assert_eq!(selection.bbox.as_deref(), Some("-54,-8,55,-5"));
+    assert!(selection.to_selection().is_ok());
+}
+
+#[tokio::test]
+async fn test_date_filter_applied_while_parsing() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let archive = create_test_archive(temp_dir.path());
+
+    let cli = Cli::try_parse_from([
+        "ecad-processor",
+        "process",
+        "-i",
+        "archive.zip",
+        "--start-date",
+        "2023-01-02",
+        "--months",
+        "DJF",
+    ])
+    .unwrap();
+    let Commands::Process { dates, .. } = cli.command else {
+        panic!("expected process command");
+    };
+    let filter = dates.to_filter().unwrap();
+
+    let processor = ArchiveProcessor::from_zip(&archive)
+        .await
+        .unwrap()
+        .with_date_filter(filter);
+    let (mut records, _) = processor.process_data(&archive).await.unwrap();
+    records.sort_by_key(|r| (r.station_id, r.date));
+
+    // Station 258 only reported on 1 January
+    let dates: Vec<(u32, NaiveDate)> = records.iter().map(|r| (r.station_id, r.date)).collect();
+    assert_eq!(
+        dates,
+        vec![
+            (257, NaiveDate::from_ymd_opt(2023, 1, 2).unwrap()),
+            (257, NaiveDate::from_ymd_opt(2023, 1, 3).unwrap()),
+        ]
+    );
+    assert_eq!(records[0].temp_avg, Some(6.1));
+    assert_eq!(records[1].precipitation, Some(1.0));
+
+    // Summer-only reads leave nothing from a January archive
+    let summer = DateFilter::new().with_months("JJA".parse().unwrap());
+    let processor = ArchiveProcessor::from_zip(&archive)
+        .await
+        .unwrap()
+        .with_date_filter(summer);
+    let (records, _) = processor.process_data(&archive).await.unwrap();
+    assert!(records.is_empty());
+}